@@ -5,31 +5,132 @@ use crate::{
     context::Context,
     failpoint::fail_point,
     metrics::metrics,
-    param::{AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam},
+    page::MAX_PAGE_SIZE,
+    param::{AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam, Param},
     version::Version,
 };
 
 use aptos_api_types::{
-    AccountData, Address, AsConverter, Error, LedgerInfo, MoveModuleBytecode, Response,
-    TransactionId,
+    AccountData, Address, AsConverter, Error, HexEncodedBytes, LedgerInfo, MoveModuleBytecode,
+    Response, TransactionId,
 };
 use aptos_types::{
+    account_address::AccountAddress,
     account_config::AccountResource,
     account_state::AccountState,
     event::{EventHandle, EventKey},
 };
 
-use anyhow::Result;
-use aptos_types::{access_path::AccessPath, state_store::state_key::StateKey};
+use anyhow::{ensure, Result};
+use aptos_types::{
+    access_path::{AccessPath, Path},
+    state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix},
+};
 use move_deps::move_core_types::{
     identifier::Identifier,
-    language_storage::{ResourceKey, StructTag},
+    language_storage::{ModuleId, ResourceKey, StructTag},
     move_resource::MoveStructType,
     value::MoveValue,
 };
-use std::convert::TryInto;
+use serde::Deserialize;
+use std::{
+    convert::{TryFrom, TryInto},
+    num::NonZeroU16,
+    str::FromStr,
+};
 use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
 
+/// Maximum number of state values read from storage to serve a page of account resources or
+/// modules, so that neither a request without `limit` nor a `prefix` matching few values reads
+/// an arbitrarily large account.
+const MAX_STATE_VALUES_TO_SCAN: usize = 10_000;
+
+#[derive(Clone, Debug, Deserialize)]
+struct AccountStateQuery {
+    limit: Option<Param<NonZeroU16>>,
+    cursor: Option<Param<HexEncodedBytes>>,
+    prefix: Option<Param<StructTagPrefix>>,
+}
+
+impl AccountStateQuery {
+    /// Returns the page size, or `None` if all the values must be returned, up to
+    /// `MAX_STATE_VALUES_TO_SCAN`.
+    fn limit(&self) -> Result<Option<u16>, Error> {
+        let limit = match self.limit.clone() {
+            Some(limit) => limit.parse("limit")?.get(),
+            None => return Ok(None),
+        };
+        if limit > MAX_PAGE_SIZE {
+            return Err(Error::invalid_param(
+                "limit",
+                format!("{}, exceed limit {}", limit, MAX_PAGE_SIZE),
+            ));
+        }
+        Ok(Some(limit))
+    }
+
+    fn cursor(&self) -> Result<Option<StateKey>, Error> {
+        self.cursor
+            .clone()
+            .map(|cursor| {
+                let bytes = cursor.parse("cursor")?;
+                StateKey::decode(bytes.inner()).map_err(|_| Error::invalid_param("cursor", bytes))
+            })
+            .transpose()
+    }
+
+    fn prefix(&self) -> Result<Option<StructTagPrefix>, Error> {
+        self.prefix.clone().map(|v| v.parse("prefix")).transpose()
+    }
+}
+
+/// A prefix of struct tags: `address`, `address::module` or `address::module::name`, matching all
+/// the instantiations of the generic structs. Modules are matched by `address` or
+/// `address::module`.
+#[derive(Clone, Debug)]
+struct StructTagPrefix {
+    address: AccountAddress,
+    module: Option<Identifier>,
+    name: Option<Identifier>,
+}
+
+impl StructTagPrefix {
+    fn matches_resource(&self, struct_tag: &StructTag) -> bool {
+        self.address == struct_tag.address
+            && self
+                .module
+                .as_ref()
+                .map_or(true, |m| m == &struct_tag.module)
+            && self.name.as_ref().map_or(true, |n| n == &struct_tag.name)
+    }
+
+    fn matches_module(&self, module_id: &ModuleId) -> bool {
+        &self.address == module_id.address()
+            && self
+                .module
+                .as_ref()
+                .map_or(true, |m| m.as_ident_str() == module_id.name())
+            && self.name.is_none()
+    }
+}
+
+impl FromStr for StructTagPrefix {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split("::");
+        let address = parts.next().unwrap_or_default().parse::<Address>()?.into();
+        let module = parts.next().map(Identifier::new).transpose()?;
+        let name = parts.next().map(Identifier::new).transpose()?;
+        ensure!(parts.next().is_none(), "invalid struct tag prefix: {}", s);
+        Ok(Self {
+            address,
+            module,
+            name,
+        })
+    }
+}
+
 // GET /accounts/<address>
 pub fn get_account(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam)
@@ -40,26 +141,28 @@ pub fn get_account(context: Context) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
-// GET /accounts/<address>/resources
+// GET /accounts/<address>/resources?limit={u16}&cursor={hex}&prefix={struct_tag_prefix}
 pub fn get_account_resources(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "resources")
         .and(warp::get())
         .and(context.filter())
         .and(warp::query::<Version>())
-        .map(|address, ctx, version: Version| (version.version, address, ctx))
+        .and(warp::query::<AccountStateQuery>())
+        .map(|address, ctx, version: Version, query| (version.version, query, address, ctx))
         .untuple_one()
         .and_then(handle_get_account_resources)
         .with(metrics("get_account_resources"))
         .boxed()
 }
 
-// GET /accounts/<address>/modules
+// GET /accounts/<address>/modules?limit={u16}&cursor={hex}&prefix={module_id_prefix}
 pub fn get_account_modules(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "modules")
         .and(warp::get())
         .and(context.filter())
         .and(warp::query::<Version>())
-        .map(|address, ctx, version: Version| (version.version, address, ctx))
+        .and(warp::query::<AccountStateQuery>())
+        .map(|address, ctx, version: Version, query| (version.version, query, address, ctx))
         .untuple_one()
         .and_then(handle_get_account_modules)
         .with(metrics("get_account_modules"))
@@ -76,20 +179,22 @@ async fn handle_get_account(
 
 async fn handle_get_account_resources(
     ledger_version: Option<LedgerVersionParam>,
+    query: AccountStateQuery,
     address: AddressParam,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account_resources")?;
-    Ok(Account::new(ledger_version, address, context)?.resources()?)
+    Ok(Account::new(ledger_version, address, context)?.resources(query)?)
 }
 
 async fn handle_get_account_modules(
     ledger_version: Option<LedgerVersionParam>,
+    query: AccountStateQuery,
     address: AddressParam,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account_modules")?;
-    Ok(Account::new(ledger_version, address, context)?.modules()?)
+    Ok(Account::new(ledger_version, address, context)?.modules(query)?)
 }
 
 pub(crate) struct Account {
@@ -147,23 +252,112 @@ impl Account {
         Response::new(self.latest_ledger_info, &account)
     }
 
-    pub fn resources(self) -> Result<impl Reply, Error> {
+    fn resources(self, query: AccountStateQuery) -> Result<impl Reply, Error> {
+        let prefix = query.prefix()?;
+        let (values, cursor) = self.state_values(&query, |path| match path {
+            Path::Resource(struct_tag)
+                if prefix
+                    .as_ref()
+                    .map_or(true, |p| p.matches_resource(&struct_tag)) =>
+            {
+                Some(struct_tag)
+            }
+            _ => None,
+        })?;
         let resources = self
             .context
             .move_resolver()?
             .as_converter()
-            .try_into_resources(self.account_state()?.get_resources())?;
-        Response::new(self.latest_ledger_info, &resources)
+            .try_into_resources(
+                values
+                    .iter()
+                    .map(|(struct_tag, bytes)| (struct_tag.clone(), bytes.as_slice())),
+            )?;
+        Ok(Response::new(self.latest_ledger_info, &resources)?.with_cursor(cursor))
     }
 
-    pub fn modules(self) -> Result<impl Reply, Error> {
-        let modules = self
-            .account_state()?
-            .into_modules()
-            .map(MoveModuleBytecode::new)
-            .map(|m| m.try_parse_abi())
+    fn modules(self, query: AccountStateQuery) -> Result<impl Reply, Error> {
+        let prefix = query.prefix()?;
+        let (values, cursor) = self.state_values(&query, |path| match path {
+            Path::Code(module_id)
+                if prefix
+                    .as_ref()
+                    .map_or(true, |p| p.matches_module(&module_id)) =>
+            {
+                Some(())
+            }
+            _ => None,
+        })?;
+        let modules = values
+            .into_iter()
+            .map(|(_, bytes)| MoveModuleBytecode::new(bytes).try_parse_abi())
             .collect::<Result<Vec<MoveModuleBytecode>>>()?;
-        Response::new(self.latest_ledger_info, &modules)
+        Ok(Response::new(self.latest_ledger_info, &modules)?.with_cursor(cursor))
+    }
+
+    /// Reads the page of account state values selected by the query, in state key order.
+    /// `select` filters the values by their access path, and extracts what the caller needs from
+    /// it. Returns the selected values with their bytes, and the cursor to the next page if any.
+    ///
+    /// At most `MAX_STATE_VALUES_TO_SCAN` values are read from storage per request: a page cut
+    /// short by the filter ends there with a cursor, and without a `limit` the request fails.
+    fn state_values<T>(
+        &self,
+        query: &AccountStateQuery,
+        select: impl Fn(Path) -> Option<T>,
+    ) -> Result<(Vec<(T, Vec<u8>)>, Option<String>), Error> {
+        let limit = query.limit()?.map(usize::from);
+        let cursor = query.cursor()?;
+        let address: AccountAddress = self.address.into();
+        if let Some(cursor) = &cursor {
+            if !StateKeyPrefix::from(address).is_prefix(cursor)? {
+                return Err(Error::invalid_param(
+                    "cursor",
+                    HexEncodedBytes::from(cursor.encode()?),
+                ));
+            }
+        }
+
+        let mut scanned = 0;
+        let mut values = vec![];
+        for item in
+            self.context
+                .get_account_state_values(address, cursor.as_ref(), self.ledger_version)?
+        {
+            let (state_key, state_value) = item?;
+            if scanned == MAX_STATE_VALUES_TO_SCAN {
+                if limit.is_none() {
+                    return Err(Error::bad_request(format!(
+                        "account has more than {} state values, set limit to paginate them",
+                        MAX_STATE_VALUES_TO_SCAN
+                    )));
+                }
+                let next_cursor = HexEncodedBytes::from(state_key.encode()?);
+                return Ok((values, Some(next_cursor.to_string())));
+            }
+            scanned += 1;
+            let selected = match &state_key {
+                StateKey::AccessPath(access_path) => {
+                    select(Path::try_from(&access_path.path).map_err(anyhow::Error::from)?)
+                }
+                _ => None,
+            };
+            let (selected, bytes) = match (selected, state_value.maybe_bytes) {
+                (Some(selected), Some(bytes)) => (selected, bytes),
+                _ => continue,
+            };
+            if limit == Some(values.len()) {
+                let next_cursor = HexEncodedBytes::from(state_key.encode()?);
+                return Ok((values, Some(next_cursor.to_string())));
+            }
+            values.push((selected, bytes));
+        }
+
+        // An account without any state value does not exist, unless the cursor went past them.
+        if scanned == 0 && cursor.is_none() {
+            return Err(self.account_not_found());
+        }
+        Ok((values, None))
     }
 
     pub fn find_event_key(
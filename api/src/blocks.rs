@@ -0,0 +1,185 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    param::{BlockHeightParam, Param, TransactionVersionParam},
+};
+
+use aptos_api_types::{AsConverter, Block, Error, LedgerInfo, Response, Transaction};
+use aptos_types::transaction::Transaction as CoreTransaction;
+
+use anyhow::Result;
+use serde::Deserialize;
+use std::convert::TryFrom;
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+#[derive(Clone, Debug, Deserialize)]
+struct BlockQuery {
+    with_transactions: Option<Param<bool>>,
+}
+
+impl BlockQuery {
+    fn with_transactions(self) -> Result<bool, Error> {
+        self.with_transactions
+            .map(|v| v.parse("with_transactions"))
+            .unwrap_or(Ok(false))
+    }
+}
+
+// GET /blocks/by_height/{height}?with_transactions={bool}
+pub fn get_block_by_height(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("blocks" / "by_height" / BlockHeightParam)
+        .and(warp::get())
+        .and(warp::query::<BlockQuery>())
+        .and(context.filter())
+        .and_then(handle_get_block_by_height)
+        .with(metrics("get_block_by_height"))
+        .boxed()
+}
+
+// GET /blocks/by_version/{version}?with_transactions={bool}
+pub fn get_block_by_version(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("blocks" / "by_version" / TransactionVersionParam)
+        .and(warp::get())
+        .and(warp::query::<BlockQuery>())
+        .and(context.filter())
+        .and_then(handle_get_block_by_version)
+        .with(metrics("get_block_by_version"))
+        .boxed()
+}
+
+async fn handle_get_block_by_height(
+    height: BlockHeightParam,
+    query: BlockQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_block_by_height")?;
+    Ok(Blocks::new(context)?
+        .get_by_height(height.parse("block height")?, query.with_transactions()?)?)
+}
+
+async fn handle_get_block_by_version(
+    version: TransactionVersionParam,
+    query: BlockQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_block_by_version")?;
+    Ok(Blocks::new(context)?
+        .get_by_version(version.parse("version")?, query.with_transactions()?)?)
+}
+
+struct Blocks {
+    ledger_info: LedgerInfo,
+    context: Context,
+}
+
+impl Blocks {
+    fn new(context: Context) -> Result<Self, Error> {
+        let ledger_info = context.get_latest_ledger_info()?;
+        Ok(Self {
+            ledger_info,
+            context,
+        })
+    }
+
+    pub fn get_by_height(self, height: u64, with_transactions: bool) -> Result<impl Reply, Error> {
+        let ledger_version = self.ledger_info.version();
+        let latest_height = self
+            .context
+            .get_block_height_by_version(ledger_version, ledger_version)?;
+        if latest_height.map_or(true, |latest| height > latest) {
+            return Err(self.block_not_found(format!("height({})", height)));
+        }
+        self.render_block(height, with_transactions)
+    }
+
+    pub fn get_by_version(
+        self,
+        version: u64,
+        with_transactions: bool,
+    ) -> Result<impl Reply, Error> {
+        let ledger_version = self.ledger_info.version();
+        if version > ledger_version {
+            return Err(self.block_not_found(format!("version({})", version)));
+        }
+        let height = self
+            .context
+            .get_block_height_by_version(version, ledger_version)?
+            .ok_or_else(|| self.block_not_found(format!("version({})", version)))?;
+        self.render_block(height, with_transactions)
+    }
+
+    fn render_block(self, height: u64, with_transactions: bool) -> Result<impl Reply, Error> {
+        let ledger_version = self.ledger_info.version();
+        let (first_version, last_version, new_block_event) = self
+            .context
+            .get_block_info_by_height(height, ledger_version)?;
+
+        let block_metadata = match self
+            .context
+            .get_transaction_by_version(first_version, ledger_version)?
+            .transaction
+        {
+            CoreTransaction::BlockMetadata(block_metadata) => block_metadata,
+            _ => {
+                return Err(Error::internal(anyhow::format_err!(
+                    "transaction at version {} is not a block metadata transaction",
+                    first_version
+                )))
+            }
+        };
+
+        let transactions = if with_transactions {
+            Some(self.render_transactions(
+                first_version,
+                last_version,
+                new_block_event.proposed_time(),
+            )?)
+        } else {
+            None
+        };
+
+        let block = Block {
+            block_height: height.into(),
+            block_hash: block_metadata.id().into(),
+            block_timestamp: new_block_event.proposed_time().into(),
+            first_version: first_version.into(),
+            last_version: last_version.into(),
+            proposer: new_block_event.proposer().into(),
+            transactions,
+        };
+        Response::new(self.ledger_info, &block)
+    }
+
+    fn render_transactions(
+        &self,
+        first_version: u64,
+        last_version: u64,
+        timestamp: u64,
+    ) -> Result<Vec<Transaction>, Error> {
+        let limit = u16::try_from(last_version - first_version + 1).map_err(|_| {
+            Error::bad_request(format!(
+                "block has too many transactions to render: {}",
+                last_version - first_version + 1
+            ))
+        })?;
+        let data =
+            self.context
+                .get_transactions(first_version, limit, self.ledger_info.version())?;
+
+        let resolver = self.context.move_resolver()?;
+        let converter = resolver.as_converter();
+        let txns = data
+            .into_iter()
+            .map(|t| converter.try_into_onchain_transaction(timestamp, t))
+            .collect::<Result<_>>()?;
+        Ok(txns)
+    }
+
+    fn block_not_found(&self, identifier: String) -> Error {
+        Error::not_found("block", identifier, self.ledger_info.version())
+    }
+}
@@ -1,33 +1,42 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::rate_limit::RateLimiter;
+
 use aptos_api_types::{Error, LedgerInfo, TransactionOnChainData};
 use aptos_config::config::ApiConfig;
 use aptos_crypto::HashValue;
 use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
 use aptos_types::{
     account_address::AccountAddress,
+    account_config::NewBlockEvent,
     account_state::AccountState,
     chain_id::ChainId,
     contract_event::ContractEvent,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
-    transaction::{SignedTransaction, TransactionWithProof},
+    state_proof::StateProof,
+    transaction::{SignedTransaction, Transaction, TransactionWithProof},
 };
 use storage_interface::{DbReader, Order};
 
 use anyhow::{ensure, format_err, Result};
 use aptos_state_view::StateView;
 use aptos_types::{
-    state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix},
+    state_store::{
+        state_key::StateKey,
+        state_key_prefix::StateKeyPrefix,
+        state_value::{StateValue, StateValueWithProof},
+    },
     transaction::Version,
 };
 use aptos_vm::data_cache::{IntoMoveResolver, RemoteStorageOwned};
 use futures::{channel::oneshot, SinkExt};
-use std::{convert::Infallible, sync::Arc};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
 use storage_interface::state_view::{
     DbStateView, DbStateViewAtVersion, LatestDbStateCheckpointView,
 };
+use tokio::sync::watch;
 use warp::{filters::BoxedFilter, Filter, Reply};
 
 // Context holds application scope context
@@ -37,6 +46,10 @@ pub struct Context {
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
     api_config: ApiConfig,
+    commit_sender: Arc<watch::Sender<Version>>,
+    // Kept alive so that notifying commits never fails for lack of receivers.
+    commit_receiver: watch::Receiver<Version>,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl Context {
@@ -46,11 +59,19 @@ impl Context {
         mp_sender: MempoolClientSender,
         api_config: ApiConfig,
     ) -> Self {
+        let (commit_sender, commit_receiver) = watch::channel(0);
+        let rate_limiter = api_config
+            .rate_limit
+            .as_ref()
+            .map(|config| Arc::new(RateLimiter::new(config)));
         Self {
             chain_id,
             db,
             mp_sender,
             api_config,
+            commit_sender: Arc::new(commit_sender),
+            commit_receiver,
+            rate_limiter,
         }
     }
 
@@ -60,6 +81,10 @@ impl Context {
             .map(|state_view| state_view.into_move_resolver())
     }
 
+    pub fn latest_state_view(&self) -> Result<DbStateView> {
+        self.db.latest_state_checkpoint_view()
+    }
+
     pub fn state_view_at_version(&self, version: Version) -> Result<DbStateView> {
         self.db.state_view_at_version(Some(version))
     }
@@ -72,6 +97,42 @@ impl Context {
         self.api_config.content_length_limit()
     }
 
+    pub fn max_view_function_gas(&self) -> u64 {
+        self.api_config.max_view_function_gas()
+    }
+
+    pub fn gas_estimation_block_size(&self) -> u64 {
+        self.api_config.gas_estimation_block_size()
+    }
+
+    pub fn max_submit_transaction_batch_size(&self) -> usize {
+        self.api_config.max_submit_transaction_batch_size()
+    }
+
+    /// Takes a request token from the rate limit quota of the client, or returns how long the
+    /// client has to wait before retrying. Always succeeds when rate limiting is not configured.
+    pub fn acquire_rate_limit_token(
+        &self,
+        remote: Option<SocketAddr>,
+        api_key: Option<String>,
+    ) -> Result<(), Duration> {
+        match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter.acquire(remote, api_key),
+            None => Ok(()),
+        }
+    }
+
+    /// Wakes up all commit subscribers, e.g. open transaction streams, after the ledger has been
+    /// extended up to the given version.
+    pub fn notify_commit(&self, version: Version) {
+        // Can not fail: `self.commit_receiver` keeps the channel open.
+        let _ = self.commit_sender.send(version);
+    }
+
+    pub fn subscribe_to_commits(&self) -> watch::Receiver<Version> {
+        self.commit_receiver.clone()
+    }
+
     pub fn filter(self) -> impl Filter<Extract = (Context,), Error = Infallible> + Clone {
         warp::any().map(move || self.clone())
     }
@@ -103,6 +164,25 @@ impl Context {
             .get_state_value(state_key)
     }
 
+    pub fn get_state_value_with_proof(
+        &self,
+        state_key: StateKey,
+        version: u64,
+        ledger_version: u64,
+    ) -> Result<StateValueWithProof> {
+        self.db
+            .get_state_value_with_proof(state_key, version, ledger_version)
+    }
+
+    pub fn get_state_proof_with_ledger_info(
+        &self,
+        known_version: u64,
+        ledger_info: LedgerInfoWithSignatures,
+    ) -> Result<StateProof> {
+        self.db
+            .get_state_proof_with_ledger_info(known_version, ledger_info)
+    }
+
     pub fn get_account_state(
         &self,
         address: AccountAddress,
@@ -115,10 +195,38 @@ impl Context {
         )
     }
 
+    /// Returns an iterator over the state values of an account, ordered by state key and
+    /// starting at `cursor` if provided.
+    pub fn get_account_state_values(
+        &self,
+        address: AccountAddress,
+        cursor: Option<&StateKey>,
+        version: u64,
+    ) -> Result<impl Iterator<Item = Result<(StateKey, StateValue)>> + '_> {
+        self.db
+            .get_prefixed_state_value_iterator(&StateKeyPrefix::from(address), cursor, version)
+    }
+
     pub fn get_block_timestamp(&self, version: u64) -> Result<u64> {
         self.db.get_block_timestamp(version)
     }
 
+    pub fn get_block_height_by_version(
+        &self,
+        version: u64,
+        ledger_version: u64,
+    ) -> Result<Option<u64>> {
+        self.db.get_block_height_by_version(version, ledger_version)
+    }
+
+    pub fn get_block_info_by_height(
+        &self,
+        height: u64,
+        ledger_version: u64,
+    ) -> Result<(Version, Version, NewBlockEvent)> {
+        self.db.get_block_info_by_height(height, ledger_version)
+    }
+
     pub fn get_transactions(
         &self,
         start_version: u64,
@@ -162,6 +270,20 @@ impl Context {
             .collect()
     }
 
+    /// Returns the transactions without their outputs, which is much cheaper than
+    /// `get_transactions` when only the transactions themselves are needed.
+    pub fn get_raw_transactions(
+        &self,
+        start_version: u64,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<Transaction>> {
+        Ok(self
+            .db
+            .get_transactions(start_version, limit as u64, ledger_version, false)?
+            .transactions)
+    }
+
     pub fn get_account_transactions(
         &self,
         address: AccountAddress,
@@ -208,6 +330,18 @@ impl Context {
         callback.await.map_err(anyhow::Error::from)
     }
 
+    pub async fn get_gas_price_at_rank(&self, rank: usize) -> Result<Option<u64>> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetGasPriceAtRank(rank, req_sender))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
     pub fn get_transaction_by_version(
         &self,
         version: u64,
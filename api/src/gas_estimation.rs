@@ -0,0 +1,108 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{context::Context, failpoint::fail_point, metrics::metrics};
+
+use aptos_api_types::{Error, GasEstimation, LedgerInfo, Response};
+use aptos_types::{
+    on_chain_config::{OnChainConfig, VMConfig},
+    transaction::Transaction,
+};
+use move_deps::move_core_types::gas_schedule::GasAlgebra;
+
+use anyhow::{format_err, Result};
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+/// Number of most recently committed transactions sampled for their gas unit prices.
+const RECENT_TRANSACTIONS_SAMPLE_SIZE: u64 = 1000;
+
+// GET /estimate_gas_price
+pub fn estimate_gas_price(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("estimate_gas_price")
+        .and(warp::get())
+        .and(context.filter())
+        .and_then(handle_estimate_gas_price)
+        .with(metrics("estimate_gas_price"))
+        .boxed()
+}
+
+async fn handle_estimate_gas_price(context: Context) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_estimate_gas_price")?;
+    Ok(GasEstimator::new(context)?.estimate().await?)
+}
+
+struct GasEstimator {
+    ledger_info: LedgerInfo,
+    context: Context,
+}
+
+impl GasEstimator {
+    fn new(context: Context) -> Result<Self, Error> {
+        let ledger_info = context.get_latest_ledger_info()?;
+        Ok(Self {
+            ledger_info,
+            context,
+        })
+    }
+
+    /// The estimate is the highest of:
+    /// 1. the minimum gas unit price accepted by the VM.
+    /// 2. the median gas unit price of the recently committed user transactions.
+    /// 3. when mempool holds more transactions than fit in the next block, the price outbidding
+    ///    the last of them in the mempool priority queue.
+    pub async fn estimate(self) -> Result<impl Reply, Error> {
+        let min_gas_price = self.min_gas_price()?;
+        let committed_gas_price = self.recent_committed_gas_price()?;
+        let rank = self.context.gas_estimation_block_size().saturating_sub(1) as usize;
+        let mempool_gas_price = self
+            .context
+            .get_gas_price_at_rank(rank)
+            .await?
+            .map(|price| price.saturating_add(1));
+
+        let gas_estimate = [committed_gas_price, mempool_gas_price]
+            .iter()
+            .flatten()
+            .fold(min_gas_price, |estimate, price| estimate.max(*price));
+        Response::new(
+            self.ledger_info,
+            &GasEstimation {
+                gas_estimate: gas_estimate.into(),
+            },
+        )
+    }
+
+    fn min_gas_price(&self) -> Result<u64> {
+        let resolver = self.context.move_resolver()?;
+        let config = VMConfig::fetch_config(&resolver)
+            .ok_or_else(|| format_err!("failed to fetch on-chain VM config"))?;
+        Ok(config
+            .gas_schedule
+            .gas_constants
+            .min_price_per_gas_unit
+            .get())
+    }
+
+    /// Returns the median gas unit price of the user transactions among the most recently
+    /// committed transactions, or `None` if there is no such transaction.
+    fn recent_committed_gas_price(&self) -> Result<Option<u64>> {
+        let ledger_version = self.ledger_info.version();
+        let start_version = (ledger_version + 1).saturating_sub(RECENT_TRANSACTIONS_SAMPLE_SIZE);
+        let limit = (ledger_version - start_version + 1) as u16;
+
+        let mut gas_prices: Vec<u64> = self
+            .context
+            .get_raw_transactions(start_version, limit, ledger_version)?
+            .into_iter()
+            .filter_map(|txn| match txn {
+                Transaction::UserTransaction(txn) => Some(txn.gas_unit_price()),
+                _ => None,
+            })
+            .collect();
+        if gas_prices.is_empty() {
+            return Ok(None);
+        }
+        gas_prices.sort_unstable();
+        Ok(Some(gas_prices[gas_prices.len() / 2]))
+    }
+}
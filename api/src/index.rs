@@ -2,13 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    accounts,
+    accounts, blocks,
     context::Context,
     events,
     failpoint::fail_point,
-    log,
+    gas_estimation, log,
     metrics::{metrics, status_metrics},
-    state, transactions,
+    rate_limit::{rate_limit, RateLimited, X_APTOS_API_KEY},
+    state, stream, transactions, view_function,
 };
 use aptos_api_types::{Error, Response};
 
@@ -26,28 +27,40 @@ const OPEN_API_HTML: &str = include_str!("../doc/spec.html");
 const OPEN_API_SPEC: &str = include_str!("../doc/openapi.yaml");
 
 pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone {
-    index(context.clone())
-        .or(openapi_spec())
-        .or(accounts::get_account(context.clone()))
-        .or(accounts::get_account_resources(context.clone()))
-        .or(accounts::get_account_modules(context.clone()))
-        .or(transactions::get_transaction(context.clone()))
-        .or(transactions::get_transactions(context.clone()))
-        .or(transactions::get_account_transactions(context.clone()))
-        .or(transactions::submit_bcs_transactions(context.clone()))
-        .or(transactions::submit_json_transactions(context.clone()))
-        .or(transactions::create_signing_message(context.clone()))
-        .or(events::get_events_by_event_key(context.clone()))
-        .or(events::get_events_by_event_handle(context.clone()))
-        .or(state::get_account_resource(context.clone()))
-        .or(state::get_account_module(context.clone()))
-        .or(state::get_table_item(context.clone()))
+    rate_limit(context.clone())
+        .and(
+            index(context.clone())
+                .or(openapi_spec())
+                .or(accounts::get_account(context.clone()))
+                .or(accounts::get_account_resources(context.clone()))
+                .or(accounts::get_account_modules(context.clone()))
+                .or(blocks::get_block_by_height(context.clone()))
+                .or(blocks::get_block_by_version(context.clone()))
+                .or(stream::stream_transactions(context.clone()))
+                .or(transactions::get_transaction(context.clone()))
+                .or(transactions::get_transactions(context.clone()))
+                .or(transactions::get_account_transactions(context.clone()))
+                .or(transactions::submit_bcs_transactions(context.clone()))
+                .or(transactions::submit_bcs_transaction_batch(context.clone()))
+                .or(transactions::submit_json_transactions(context.clone()))
+                .or(transactions::simulate_bcs_transactions(context.clone()))
+                .or(transactions::simulate_json_transactions(context.clone()))
+                .or(transactions::create_signing_message(context.clone()))
+                .or(events::get_events_by_event_key(context.clone()))
+                .or(events::get_events_by_event_handle(context.clone()))
+                .or(gas_estimation::estimate_gas_price(context.clone()))
+                .or(state::get_account_resource(context.clone()))
+                .or(state::get_account_module(context.clone()))
+                .or(state::get_table_item(context.clone()))
+                .or(view_function::view_bcs_function(context.clone()))
+                .or(view_function::view_json_function(context.clone())),
+        )
         .or(context.health_check_route().with(metrics("health_check")))
         .with(
             warp::cors()
                 .allow_any_origin()
                 .allow_methods(vec!["POST", "GET"])
-                .allow_headers(vec![header::CONTENT_TYPE]),
+                .allow_headers(vec![header::CONTENT_TYPE.as_str(), X_APTOS_API_KEY]),
         )
         .recover(handle_rejection)
         .with(log::logger())
@@ -89,8 +102,15 @@ pub async fn handle_index(context: Context) -> Result<impl Reply, Rejection> {
 async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
     let code;
     let body;
+    let mut retry_after = None;
 
-    if err.is_not_found() {
+    if let Some(cause) = err.find::<RateLimited>() {
+        code = StatusCode::TOO_MANY_REQUESTS;
+        body = reply::json(&Error::new(code, "Too Many Requests".to_owned()));
+        // Round up, so that the client does not retry before its quota is refilled.
+        let secs = cause.retry_after.as_secs() + (cause.retry_after.subsec_nanos() > 0) as u64;
+        retry_after = Some(secs.max(1));
+    } else if err.is_not_found() {
         code = StatusCode::NOT_FOUND;
         body = reply::json(&Error::new(code, "Not Found".to_owned()));
     } else if let Some(error) = err.find::<Error>() {
@@ -121,6 +141,10 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
     let mut rep = reply::with_status(body, code).into_response();
     rep.headers_mut()
         .insert("access-control-allow-origin", HeaderValue::from_static("*"));
+    if let Some(retry_after) = retry_after {
+        rep.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    }
     Ok(rep)
 }
 
@@ -2,18 +2,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts;
+mod blocks;
 mod context;
 mod events;
+mod gas_estimation;
 mod health_check;
 mod index;
 pub(crate) mod log;
 mod metrics;
 mod page;
 pub(crate) mod param;
+mod rate_limit;
 pub mod runtime;
 mod state;
+mod stream;
 mod transactions;
 pub(crate) mod version;
+mod view_function;
 
 mod failpoint;
 #[cfg(any(test))]
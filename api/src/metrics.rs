@@ -1,7 +1,9 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_metrics::{register_histogram_vec, HistogramVec};
+use aptos_metrics::{
+    register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec,
+};
 
 use once_cell::sync::Lazy;
 use warp::log::{custom, Info, Log};
@@ -24,6 +26,26 @@ static RESPONSE_STATUS: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of requests allowed and throttled per client of the rate limiters, observed on every
+/// refill of the client bucket.
+pub static RATE_LIMIT_METRICS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_api_rate_limit",
+        "API rate limiting metrics grouped by rate limiter and metric",
+        &["limiter", "metric"]
+    )
+    .unwrap()
+});
+
+static THROTTLED_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_throttled_requests",
+        "Number of API requests rejected for exceeding the client rate limit, grouped by rate limiter",
+        &["limiter"]
+    )
+    .unwrap()
+});
+
 // Record metrics by method, operation_id and status.
 // The operation_id is the id for the request handler.
 // Should use same `operationId` defined in `openapi.yaml` whenever possible.
@@ -51,3 +73,7 @@ pub fn status_metrics() -> Log<impl Fn(Info) + Copy> {
     };
     custom(func)
 }
+
+pub fn throttled_requests(limiter: &str) {
+    THROTTLED_REQUESTS.with_label_values(&[limiter]).inc();
+}
@@ -0,0 +1,141 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    metrics::{throttled_requests, RATE_LIMIT_METRICS},
+};
+
+use aptos_config::config::ApiRateLimitConfig;
+use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
+
+use std::{
+    collections::HashSet,
+    net::{IpAddr, SocketAddr},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use warp::{filters::BoxedFilter, reject, Filter, Rejection};
+
+pub const X_APTOS_API_KEY: &str = "X-Aptos-Api-Key";
+
+const IP_LABEL: &str = "ip";
+const API_KEY_LABEL: &str = "api_key";
+
+/// How often the buckets of idle clients are garbage collected.
+const GARBAGE_COLLECTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Rejection of a request exceeding the rate limit of its client.
+#[derive(Debug)]
+pub struct RateLimited {
+    pub retry_after: Duration,
+}
+
+impl reject::Reject for RateLimited {}
+
+/// Rate limits the requests of every client with a token bucket per API key, for the clients
+/// presenting a configured API key, or per IP address otherwise. The buckets of the clients idle
+/// long enough for their bucket to refill are garbage collected every
+/// `GARBAGE_COLLECTION_INTERVAL`, as the requests come.
+pub struct RateLimiter {
+    ip_limiter: TokenBucketRateLimiter<IpAddr>,
+    api_key_limiter: TokenBucketRateLimiter<String>,
+    api_keys: HashSet<String>,
+    last_garbage_collection: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &ApiRateLimitConfig) -> Self {
+        if !config.enabled {
+            return Self {
+                ip_limiter: TokenBucketRateLimiter::open(IP_LABEL),
+                api_key_limiter: TokenBucketRateLimiter::open(API_KEY_LABEL),
+                api_keys: HashSet::new(),
+                last_garbage_collection: Mutex::new(Instant::now()),
+            };
+        }
+        Self {
+            ip_limiter: TokenBucketRateLimiter::new(
+                IP_LABEL,
+                "api".to_owned(),
+                config.initial_bucket_fill_percentage,
+                config.ip_request_bucket_size,
+                config.ip_request_rate,
+                Some(RATE_LIMIT_METRICS.clone()),
+            ),
+            api_key_limiter: TokenBucketRateLimiter::new(
+                API_KEY_LABEL,
+                "api".to_owned(),
+                config.initial_bucket_fill_percentage,
+                config.api_key_request_bucket_size,
+                config.api_key_request_rate,
+                Some(RATE_LIMIT_METRICS.clone()),
+            ),
+            api_keys: config.api_keys.iter().cloned().collect(),
+            last_garbage_collection: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Takes a request token from the bucket of the client, or returns how long the client has
+    /// to wait for the next one. Requests of unknown API keys are limited by IP address, and
+    /// requests without a remote address are not limited.
+    pub fn acquire(
+        &self,
+        remote: Option<SocketAddr>,
+        api_key: Option<String>,
+    ) -> Result<(), Duration> {
+        self.maybe_garbage_collect();
+        let (label, bucket) = match (api_key.filter(|key| self.api_keys.contains(key)), remote) {
+            (Some(api_key), _) => (API_KEY_LABEL, self.api_key_limiter.bucket(api_key)),
+            (None, Some(remote)) => (IP_LABEL, self.ip_limiter.bucket(remote.ip())),
+            (None, None) => return Ok(()),
+        };
+        let result = bucket.lock().acquire_all_tokens(1);
+        result.map_err(|next_token_time| {
+            throttled_requests(label);
+            next_token_time
+                .map(|time| time.saturating_duration_since(Instant::now()))
+                .unwrap_or_else(|| Duration::from_secs(1))
+        })
+    }
+
+    /// Garbage collects the buckets of the idle clients if `GARBAGE_COLLECTION_INTERVAL` elapsed
+    /// since the last time. Concurrent requests don't wait for it.
+    fn maybe_garbage_collect(&self) {
+        let mut last_garbage_collection = match self.last_garbage_collection.try_lock() {
+            Ok(last_garbage_collection) => last_garbage_collection,
+            Err(_) => return,
+        };
+        if last_garbage_collection.elapsed() >= GARBAGE_COLLECTION_INTERVAL {
+            *last_garbage_collection = Instant::now();
+            self.garbage_collect();
+        }
+    }
+
+    /// Garbage collects the buckets of the clients idle long enough for their bucket to refill,
+    /// returning how many were removed.
+    pub(crate) fn garbage_collect(&self) -> usize {
+        self.ip_limiter.garbage_collect_full_buckets()
+            + self.api_key_limiter.garbage_collect_full_buckets()
+    }
+}
+
+/// Rejects the requests of the clients exceeding their rate limit with `RateLimited`.
+pub fn rate_limit(context: Context) -> BoxedFilter<()> {
+    warp::addr::remote()
+        .and(warp::header::optional::<String>(X_APTOS_API_KEY))
+        .and(context.filter())
+        .and_then(handle_rate_limit)
+        .untuple_one()
+        .boxed()
+}
+
+async fn handle_rate_limit(
+    remote: Option<SocketAddr>,
+    api_key: Option<String>,
+    context: Context,
+) -> Result<(), Rejection> {
+    context
+        .acquire_rate_limit_token(remote, api_key)
+        .map_err(|retry_after| reject::custom(RateLimited { retry_after }))
+}
@@ -6,6 +6,8 @@ use crate::{context::Context, index};
 use aptos_config::config::{ApiConfig, NodeConfig};
 use aptos_mempool::MempoolClientSender;
 use aptos_types::chain_id::ChainId;
+use event_notifications::EventNotificationListener;
+use futures::StreamExt;
 use storage_interface::DbReader;
 use warp::{Filter, Reply};
 
@@ -16,12 +18,15 @@ use tokio::runtime::{Builder, Runtime};
 /// When api and json-rpc are configured with same port, both API will be served for the port.
 /// When api and json-rpc are configured with different port, both API will be served for
 /// both ports.
+/// `commit_listener` should be subscribed to the new block event, and is used to notify
+/// transaction streams of newly committed transactions.
 /// Returns corresponding Tokio runtime
 pub fn bootstrap(
     config: &NodeConfig,
     chain_id: ChainId,
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+    mut commit_listener: EventNotificationListener,
 ) -> anyhow::Result<Runtime> {
     let runtime = Builder::new_multi_thread()
         .thread_name("api")
@@ -32,8 +37,16 @@ pub fn bootstrap(
     let api_config = config.api.clone();
     let api = WebServer::from(api_config.clone());
 
+    let context = Context::new(chain_id, db, mp_sender, api_config);
+
+    let commit_context = context.clone();
+    runtime.spawn(async move {
+        while let Some(notification) = commit_listener.next().await {
+            commit_context.notify_commit(notification.version);
+        }
+    });
+
     runtime.spawn(async move {
-        let context = Context::new(chain_id, db, mp_sender, api_config);
         let routes = index::routes(context);
         api.serve(routes).await;
     });
@@ -87,10 +100,13 @@ impl WebServer {
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::{sync::Arc, time::Duration};
 
     use aptos_config::config::NodeConfig;
-    use aptos_types::chain_id::ChainId;
+    use aptos_infallible::RwLock;
+    use aptos_types::{block_metadata::new_block_event_key, chain_id::ChainId};
+    use event_notifications::EventSubscriptionService;
+    use storage_interface::DbReaderWriter;
 
     use crate::{
         runtime::bootstrap,
@@ -109,11 +125,19 @@ mod tests {
         let context = runtime.block_on(new_test_context_async(
             "test_bootstrap_jsonprc_and_api_configured_at_different_port",
         ));
+        let mut event_subscription_service = EventSubscriptionService::new(
+            &[],
+            Arc::new(RwLock::new(DbReaderWriter::from_arc(context.db.clone()))),
+        );
+        let commit_listener = event_subscription_service
+            .subscribe_to_events(vec![new_block_event_key()])
+            .unwrap();
         let ret = bootstrap(
             &cfg,
             ChainId::test(),
             context.db.clone(),
             context.mempool.ac_client.clone(),
+            commit_listener,
         );
         assert!(ret.is_ok());
 
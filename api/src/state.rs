@@ -6,34 +6,65 @@ use crate::{
     failpoint::fail_point,
     metrics::metrics,
     param::{
-        AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam, TableHandleParam,
+        AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam, Param,
+        TableHandleParam,
     },
     version::Version,
 };
 use anyhow::anyhow;
 use aptos_api_types::{
     AsConverter, Error, LedgerInfo, MoveModuleBytecode, Response, TableItemRequest, TransactionId,
+    ValueWithProof,
 };
 use aptos_state_view::StateView;
-use aptos_types::{access_path::AccessPath, state_store::state_key::StateKey};
+use aptos_types::{
+    access_path::AccessPath, ledger_info::LedgerInfoWithSignatures,
+    state_store::state_key::StateKey,
+};
 use aptos_vm::data_cache::AsMoveResolver;
 use move_deps::move_core_types::{
     account_address::AccountAddress,
     identifier::Identifier,
     language_storage::{ModuleId, ResourceKey, StructTag},
 };
+use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 use storage_interface::state_view::DbStateView;
 use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
 
+#[derive(Clone, Debug, Deserialize)]
+struct ProofQuery {
+    with_proof: Option<Param<bool>>,
+    known_version: Option<LedgerVersionParam>,
+}
+
+impl ProofQuery {
+    /// Returns the ledger version already trusted by the client if proofs are requested,
+    /// defaulting to genesis.
+    fn known_version(self) -> Result<Option<u64>, Error> {
+        let with_proof = self
+            .with_proof
+            .map(|v| v.parse("with_proof"))
+            .unwrap_or(Ok(false))?;
+        if !with_proof {
+            return Ok(None);
+        }
+        self.known_version
+            .map(|v| v.parse("known_version"))
+            .unwrap_or(Ok(0))
+            .map(Some)
+    }
+}
+
 // GET /accounts/<address>/resource/<resource_type>
 pub fn get_account_resource(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "resource" / MoveStructTagParam)
         .and(warp::get())
         .and(context.filter())
         .and(warp::query::<Version>())
-        .map(|address, struct_tag, ctx, version: Version| {
-            (version.version, address, struct_tag, ctx)
+        .and(warp::query::<ProofQuery>())
+        .map(|address, struct_tag, ctx, version: Version, proof| {
+            (version.version, proof, address, struct_tag, ctx)
         })
         .untuple_one()
         .and_then(handle_get_account_resource)
@@ -47,7 +78,10 @@ pub fn get_account_module(context: Context) -> BoxedFilter<(impl Reply,)> {
         .and(warp::get())
         .and(context.filter())
         .and(warp::query::<Version>())
-        .map(|address, name, ctx, version: Version| (version.version, address, name, ctx))
+        .and(warp::query::<ProofQuery>())
+        .map(|address, name, ctx, version: Version, proof| {
+            (version.version, proof, address, name, ctx)
+        })
         .untuple_one()
         .and_then(handle_get_account_module)
         .with(metrics("get_account_module"))
@@ -64,7 +98,10 @@ pub fn get_table_item(context: Context) -> BoxedFilter<(impl Reply,)> {
         .and(warp::body::json::<TableItemRequest>())
         .and(context.filter())
         .and(warp::query::<Version>())
-        .map(|handle, body, ctx, version: Version| (version.version, handle, body, ctx))
+        .and(warp::query::<ProofQuery>())
+        .map(|handle, body, ctx, version: Version, proof| {
+            (version.version, proof, handle, body, ctx)
+        })
         .untuple_one()
         .and_then(handle_get_table_item)
         .with(metrics("get_table_item"))
@@ -73,48 +110,62 @@ pub fn get_table_item(context: Context) -> BoxedFilter<(impl Reply,)> {
 
 async fn handle_get_account_resource(
     ledger_version: Option<LedgerVersionParam>,
+    proof: ProofQuery,
     address: AddressParam,
     struct_tag: MoveStructTagParam,
     context: Context,
 ) -> anyhow::Result<impl Reply, Rejection> {
     fail_point("endpoint_query_resource")?;
     let struct_tag = struct_tag.parse("struct tag")?;
-    Ok(State::new(ledger_version, context)?.resource(
-        address.parse("account address")?.into(),
-        struct_tag
-            .clone()
-            .try_into()
-            .map_err(|_| Error::invalid_param("resource_type", struct_tag))?,
-    )?)
+    Ok(State::new(ledger_version, context)?
+        .with_proof(proof.known_version()?)
+        .resource(
+            address.parse("account address")?.into(),
+            struct_tag
+                .clone()
+                .try_into()
+                .map_err(|_| Error::invalid_param("resource_type", struct_tag))?,
+        )?)
 }
 
 async fn handle_get_account_module(
     ledger_version: Option<LedgerVersionParam>,
+    proof: ProofQuery,
     address: AddressParam,
     name: MoveIdentifierParam,
     context: Context,
 ) -> anyhow::Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account_module")?;
-    Ok(State::new(ledger_version, context)?.module(
-        address.parse("account address")?.into(),
-        name.parse("module name")?,
-    )?)
+    Ok(State::new(ledger_version, context)?
+        .with_proof(proof.known_version()?)
+        .module(
+            address.parse("account address")?.into(),
+            name.parse("module name")?,
+        )?)
 }
 
 async fn handle_get_table_item(
     ledger_version: Option<LedgerVersionParam>,
+    proof: ProofQuery,
     handle: TableHandleParam,
     body: TableItemRequest,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_table_item")?;
-    Ok(State::new(ledger_version, context)?.table_item(handle.parse("table handle")?, body)?)
+    Ok(State::new(ledger_version, context)?
+        .with_proof(proof.known_version()?)
+        .table_item(handle.parse("table handle")?, body)?)
 }
 
 pub(crate) struct State {
     state_view: DbStateView,
     ledger_version: aptos_types::transaction::Version,
     latest_ledger_info: LedgerInfo,
+    // the signed ledger info `latest_ledger_info` is built from, which the proofs are against
+    latest_ledger_info_with_signatures: LedgerInfoWithSignatures,
+    // the version known to the client, set when values must be returned with proofs
+    known_version: Option<aptos_types::transaction::Version>,
+    context: Context,
 }
 
 impl State {
@@ -122,7 +173,10 @@ impl State {
         ledger_version: Option<LedgerVersionParam>,
         context: Context,
     ) -> Result<Self, Error> {
-        let latest_ledger_info = context.get_latest_ledger_info()?;
+        let latest_ledger_info_with_signatures =
+            context.get_latest_ledger_info_with_signatures()?;
+        let latest_ledger_info =
+            LedgerInfo::new(&context.chain_id(), &latest_ledger_info_with_signatures);
         let ledger_version = ledger_version
             .map(|v| v.parse("ledger version"))
             .unwrap_or_else(|| Ok(latest_ledger_info.version()))?;
@@ -141,9 +195,17 @@ impl State {
             state_view,
             ledger_version,
             latest_ledger_info,
+            latest_ledger_info_with_signatures,
+            known_version: None,
+            context,
         })
     }
 
+    pub fn with_proof(mut self, known_version: Option<aptos_types::transaction::Version>) -> Self {
+        self.known_version = known_version;
+        self
+    }
+
     pub fn resource(
         self,
         address: AccountAddress,
@@ -162,7 +224,7 @@ impl State {
             .as_move_resolver()
             .as_converter()
             .try_into_resource(&struct_tag, &bytes)?;
-        Response::new(self.latest_ledger_info, &resource)
+        self.render(state_key, &resource)
     }
 
     pub fn module(self, address: AccountAddress, name: Identifier) -> Result<impl Reply, Error> {
@@ -177,7 +239,7 @@ impl State {
         let module = MoveModuleBytecode::new(bytes)
             .try_parse_abi()
             .map_err(Error::internal)?;
-        Response::new(self.latest_ledger_info, &module)
+        self.render(state_key, &module)
     }
 
     pub fn table_item(self, handle: u128, body: TableItemRequest) -> Result<impl Reply, Error> {
@@ -207,6 +269,41 @@ impl State {
             .ok_or_else(|| Error::not_found("table handle or item", key, self.ledger_version))?;
 
         let move_value = converter.try_into_move_value(&value_type, &bytes)?;
-        Response::new(self.latest_ledger_info, &move_value)
+        self.render(state_key, &move_value)
+    }
+
+    fn render<T: Serialize>(&self, state_key: StateKey, data: &T) -> Result<Response, Error> {
+        let known_version = match self.known_version {
+            Some(known_version) => known_version,
+            None => return Response::new(self.latest_ledger_info.clone(), data),
+        };
+
+        // Prove the value against the signed ledger info the queried version was resolved from,
+        // so that the proof matches the ledger version of the response even if blocks were
+        // committed since.
+        let ledger_info = self.latest_ledger_info_with_signatures.clone();
+        let proof_ledger_version = ledger_info.ledger_info().version();
+        if known_version > proof_ledger_version {
+            return Err(Error::invalid_param("known_version", known_version));
+        }
+        let state_value_with_proof = self.context.get_state_value_with_proof(
+            state_key,
+            self.ledger_version,
+            proof_ledger_version,
+        )?;
+        let state_proof = self
+            .context
+            .get_state_proof_with_ledger_info(known_version, ledger_info)?;
+
+        let value = ValueWithProof {
+            data,
+            state_value_with_proof: bcs::to_bytes(&state_value_with_proof)
+                .map_err(|e| Error::internal(e.into()))?
+                .into(),
+            state_proof: bcs::to_bytes(&state_proof)
+                .map_err(|e| Error::internal(e.into()))?
+                .into(),
+        };
+        Response::new(self.latest_ledger_info.clone(), &value)
     }
 }
@@ -0,0 +1,169 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    param::{AddressParam, EventKeyParam, TransactionVersionParam},
+};
+
+use aptos_api_types::{AsConverter, Error, TransactionOnChainData};
+use aptos_types::{
+    account_address::AccountAddress,
+    event::EventKey,
+    transaction::{Transaction, Version},
+};
+
+use anyhow::{format_err, Result};
+use futures::{stream, Stream, StreamExt};
+use serde::Deserialize;
+use std::{cmp::min, convert::Infallible};
+use tokio::sync::watch;
+use warp::{filters::BoxedFilter, sse, Filter, Rejection, Reply};
+
+/// Maximum number of transactions read from the database at a time.
+const MAX_BATCH_SIZE: u64 = 100;
+
+#[derive(Clone, Debug, Deserialize)]
+struct StreamQuery {
+    start: Option<TransactionVersionParam>,
+    sender: Option<AddressParam>,
+    event_key: Option<EventKeyParam>,
+}
+
+// GET /transactions/stream?start={u64}&sender={address}&event_key={event_key}
+pub fn stream_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("transactions" / "stream")
+        .and(warp::get())
+        .and(warp::query::<StreamQuery>())
+        .and(context.filter())
+        .and_then(handle_stream_transactions)
+        .with(metrics("stream_transactions"))
+        .boxed()
+}
+
+async fn handle_stream_transactions(
+    query: StreamQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_stream_transactions")?;
+    let stream = TransactionStream::new(query, context)?;
+    Ok(sse::reply(sse::keep_alive().stream(stream.into_events())))
+}
+
+/// Pushes committed transactions as server-sent events, in version order. Every event carries the
+/// transaction version as its id, so a client can resume with `start` set to the last id + 1.
+struct TransactionStream {
+    context: Context,
+    commits: watch::Receiver<Version>,
+    next_version: Version,
+    sender: Option<AccountAddress>,
+    event_key: Option<EventKey>,
+}
+
+impl TransactionStream {
+    fn new(query: StreamQuery, context: Context) -> Result<Self, Error> {
+        // Subscribe before reading the ledger version, so that no commit can be missed in between.
+        let commits = context.subscribe_to_commits();
+        let ledger_version = context.get_latest_ledger_info()?.version();
+        let next_version = query
+            .start
+            .map(|v| v.parse("start"))
+            .unwrap_or_else(|| Ok(ledger_version + 1))?;
+        let sender = query
+            .sender
+            .map(|v| v.parse("sender"))
+            .transpose()?
+            .map(Into::into);
+        let event_key = query
+            .event_key
+            .map(|v| v.parse("event key"))
+            .transpose()?
+            .map(Into::into);
+
+        Ok(Self {
+            context,
+            commits,
+            next_version,
+            sender,
+            event_key,
+        })
+    }
+
+    fn into_events(self) -> impl Stream<Item = Result<sse::Event, Infallible>> {
+        stream::unfold(Some(self), |state| async move {
+            let mut state = state?;
+            match state.next_batch().await {
+                Ok(events) => Some((events, Some(state))),
+                // Report the error to the client and end the stream.
+                Err(err) => Some((error_event(err).into_iter().collect(), None)),
+            }
+        })
+        .flat_map(|events: Vec<sse::Event>| stream::iter(events.into_iter().map(Ok)))
+    }
+
+    /// Waits until transactions are committed at or after `next_version`, and renders the ones
+    /// matching the filters. The batch is empty if none of the read transactions matches.
+    async fn next_batch(&mut self) -> Result<Vec<sse::Event>, Error> {
+        let ledger_version = loop {
+            let ledger_version = self.context.get_latest_ledger_info()?.version();
+            if self.next_version <= ledger_version {
+                break ledger_version;
+            }
+            self.commits
+                .changed()
+                .await
+                .map_err(|_| format_err!("commit notifications are closed"))?;
+        };
+
+        let limit = min(ledger_version - self.next_version + 1, MAX_BATCH_SIZE) as u16;
+        let data = self
+            .context
+            .get_transactions(self.next_version, limit, ledger_version)?;
+
+        let mut timestamp = self.context.get_block_timestamp(self.next_version)?;
+        let resolver = self.context.move_resolver()?;
+        let converter = resolver.as_converter();
+        let mut events = vec![];
+        for txn in data {
+            self.next_version = txn.version + 1;
+            // The timestamp of a block metadata transaction applies to the following
+            // transactions, whether they match the filters or not.
+            if let Transaction::BlockMetadata(metadata) = &txn.transaction {
+                timestamp = metadata.timestamp_usecs();
+            }
+            if !self.matches(&txn) {
+                continue;
+            }
+            let version = txn.version;
+            let txn = converter.try_into_onchain_transaction(timestamp, txn)?;
+            let event = sse::Event::default()
+                .event("transaction")
+                .id(version.to_string())
+                .json_data(&txn)
+                .map_err(|err| Error::internal(err.into()))?;
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    fn matches(&self, txn: &TransactionOnChainData) -> bool {
+        if let Some(sender) = &self.sender {
+            match &txn.transaction {
+                Transaction::UserTransaction(signed_txn) if &signed_txn.sender() == sender => (),
+                _ => return false,
+            }
+        }
+        if let Some(event_key) = &self.event_key {
+            if !txn.events.iter().any(|event| event.key() == event_key) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn error_event(err: Error) -> Option<sse::Event> {
+    sse::Event::default().event("error").json_data(&err).ok()
+}
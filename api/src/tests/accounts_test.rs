@@ -3,9 +3,10 @@
 
 use crate::{
     current_function_name,
-    tests::{find_value, new_test_context},
+    tests::{find_value, new_test_context, TestContext},
 };
-use serde_json::json;
+use aptos_api_types::X_APTOS_CURSOR;
+use serde_json::{json, Value};
 
 #[tokio::test]
 async fn test_get_account_resources_returns_empty_array_for_account_has_no_resources() {
@@ -204,6 +205,94 @@ async fn test_get_core_account_data_not_found() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test]
+async fn test_get_account_resources_paginated() {
+    let context = new_test_context(current_function_name!());
+    let address = "0xA550C18";
+    let all = context.get(&account_resources(address)).await;
+
+    let mut resources = vec![];
+    let mut cursor = None;
+    loop {
+        let path = match &cursor {
+            Some(cursor) => format!("{}?limit=2&cursor={}", account_resources(address), cursor),
+            None => format!("{}?limit=2", account_resources(address)),
+        };
+        let (page, next_cursor) = get_page(&context, &path).await;
+        let page = page.as_array().unwrap();
+        assert!(page.len() <= 2);
+        resources.extend(page.iter().cloned());
+        cursor = match next_cursor {
+            Some(next_cursor) => Some(next_cursor),
+            None => break,
+        };
+    }
+    assert_eq!(Value::Array(resources), all);
+}
+
+#[tokio::test]
+async fn test_get_account_resources_filtered_by_prefix() {
+    let context = new_test_context(current_function_name!());
+    let resources = context
+        .get(&format!(
+            "{}?prefix=0x1::Account",
+            account_resources("0xA550C18")
+        ))
+        .await;
+    let resources = resources.as_array().unwrap();
+    assert!(!resources.is_empty());
+    for resource in resources {
+        assert!(resource["type"]
+            .as_str()
+            .unwrap()
+            .starts_with("0x1::Account::"));
+    }
+}
+
+#[tokio::test]
+async fn test_get_account_modules_paginated_and_filtered_by_prefix() {
+    let context = new_test_context(current_function_name!());
+    let (modules, cursor) =
+        get_page(&context, &format!("{}?limit=5", account_modules("0x1"))).await;
+    assert_eq!(modules.as_array().unwrap().len(), 5);
+    assert!(cursor.is_some());
+
+    let (modules, cursor) = get_page(
+        &context,
+        &format!("{}?prefix=0x1::Coin", account_modules("0x1")),
+    )
+    .await;
+    let modules = modules.as_array().unwrap();
+    assert_eq!(modules.len(), 1);
+    assert_eq!(modules[0]["abi"]["name"], "Coin");
+    assert_eq!(cursor, None);
+}
+
+#[tokio::test]
+async fn test_get_account_resources_with_invalid_cursor() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path(&format!("{}?cursor=0xff", account_resources("0xA550C18"))),
+        )
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+async fn get_page(context: &TestContext, path: &str) -> (Value, Option<String>) {
+    let resp = context
+        .reply(warp::test::request().method("GET").path(path))
+        .await;
+    assert_eq!(resp.status(), 200);
+    let cursor = resp
+        .headers()
+        .get(X_APTOS_CURSOR)
+        .map(|cursor| cursor.to_str().unwrap().to_owned());
+    (serde_json::from_slice(resp.body()).unwrap(), cursor)
+}
+
 fn account_resources(address: &str) -> String {
     format!("/accounts/{}/resources", address)
 }
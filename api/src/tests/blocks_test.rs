@@ -0,0 +1,91 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{current_function_name, tests::new_test_context};
+
+#[tokio::test]
+async fn test_get_block_by_version_and_height() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let ledger_version = context.get_latest_ledger_info().version();
+    let block = context
+        .get(&format!("/blocks/by_version/{}", ledger_version))
+        .await;
+    let first_version: u64 = block["first_version"].as_str().unwrap().parse().unwrap();
+    let last_version: u64 = block["last_version"].as_str().unwrap().parse().unwrap();
+    assert_eq!(last_version, ledger_version);
+    assert!(first_version < last_version);
+    assert!(block.get("transactions").is_none());
+
+    let metadata_txn = context
+        .get(&format!("/transactions/{}", first_version))
+        .await;
+    assert_eq!(metadata_txn["type"], "block_metadata_transaction");
+    assert_eq!(block["block_hash"], metadata_txn["id"]);
+    assert_eq!(block["block_timestamp"], metadata_txn["timestamp"]);
+    assert_eq!(block["proposer"], metadata_txn["proposer"]);
+
+    let height = block["block_height"].as_str().unwrap();
+    let resp = context.get(&format!("/blocks/by_height/{}", height)).await;
+    assert_eq!(resp, block);
+
+    let resp = context
+        .get(&format!("/blocks/by_version/{}", first_version))
+        .await;
+    assert_eq!(resp, block);
+}
+
+#[tokio::test]
+async fn test_get_block_with_transactions() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account();
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let txn1 = context.create_user_account_by(&mut root_account, &account1);
+    let txn2 = context.create_user_account_by(&mut root_account, &account2);
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let ledger_version = context.get_latest_ledger_info().version();
+    let block = context
+        .get(&format!(
+            "/blocks/by_version/{}?with_transactions=true",
+            ledger_version
+        ))
+        .await;
+    let first_version: u64 = block["first_version"].as_str().unwrap().parse().unwrap();
+    let txns = block["transactions"].as_array().unwrap();
+    assert_eq!(txns.len() as u64, ledger_version - first_version + 1);
+    assert_eq!(txns[0]["type"], "block_metadata_transaction");
+    assert_eq!(txns[1]["type"], "user_transaction");
+    assert_eq!(txns[2]["type"], "user_transaction");
+}
+
+#[tokio::test]
+async fn test_get_block_not_found() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let ledger_version = context.get_latest_ledger_info().version();
+    let block = context
+        .get(&format!("/blocks/by_version/{}", ledger_version))
+        .await;
+    let height: u64 = block["block_height"].as_str().unwrap().parse().unwrap();
+
+    context
+        .expect_status_code(404)
+        .get(&format!("/blocks/by_height/{}", height + 1))
+        .await;
+    context
+        .expect_status_code(404)
+        .get(&format!("/blocks/by_version/{}", ledger_version + 1))
+        .await;
+    context
+        .expect_status_code(400)
+        .get("/blocks/by_height/abc")
+        .await;
+}
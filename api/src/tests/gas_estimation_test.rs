@@ -0,0 +1,36 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{current_function_name, tests::new_test_context};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_estimate_gas_price_without_user_transactions() {
+    let context = new_test_context(current_function_name!());
+    let resp = context.get("/estimate_gas_price").await;
+    // The test genesis sets the minimum gas unit price to 0.
+    assert_eq!(resp, json!({"gas_estimate": "0"}));
+}
+
+#[tokio::test]
+async fn test_estimate_gas_price_from_committed_transactions() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account();
+    let mut txns = vec![];
+    for gas_unit_price in [1, 9, 5] {
+        let account = context.gen_account();
+        let factory = context.transaction_factory();
+        txns.push(
+            root_account.sign_with_transaction_builder(
+                factory
+                    .create_user_account(account.public_key())
+                    .gas_unit_price(gas_unit_price)
+                    .expiration_timestamp_secs(u64::MAX),
+            ),
+        );
+    }
+    context.commit_block(&txns).await;
+
+    let resp = context.get("/estimate_gas_price").await;
+    assert_eq!(resp, json!({"gas_estimate": "5"}));
+}
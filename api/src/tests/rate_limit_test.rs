@@ -0,0 +1,131 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    current_function_name,
+    rate_limit::{RateLimiter, X_APTOS_API_KEY},
+    tests::{new_test_context_with_config, TestContext},
+};
+use aptos_config::config::{ApiConfig, ApiRateLimitConfig};
+use std::{net::SocketAddr, time::Duration};
+
+const API_KEY: &str = "test-api-key";
+
+fn rate_limit_config() -> ApiRateLimitConfig {
+    ApiRateLimitConfig {
+        ip_request_rate: 1,
+        ip_request_bucket_size: 2,
+        api_key_request_rate: 1,
+        api_key_request_bucket_size: 3,
+        api_keys: vec![API_KEY.to_owned()],
+        ..ApiRateLimitConfig::default()
+    }
+}
+
+fn rate_limited_context(test_name: &'static str) -> TestContext {
+    new_test_context_with_config(
+        test_name,
+        ApiConfig {
+            rate_limit: Some(rate_limit_config()),
+            ..ApiConfig::default()
+        },
+    )
+}
+
+async fn get_index(context: &TestContext, remote: &str, api_key: Option<&str>) -> u16 {
+    let mut req = warp::test::request()
+        .method("GET")
+        .path("/")
+        .remote_addr(remote.parse::<SocketAddr>().unwrap());
+    if let Some(api_key) = api_key {
+        req = req.header(X_APTOS_API_KEY, api_key);
+    }
+    context.reply(req).await.status().as_u16()
+}
+
+#[tokio::test]
+async fn test_rate_limit_by_ip() {
+    let context = rate_limited_context(current_function_name!());
+    assert_eq!(get_index(&context, "1.1.1.1:1000", None).await, 200);
+    assert_eq!(get_index(&context, "1.1.1.1:1001", None).await, 200);
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/")
+                .remote_addr("1.1.1.1:1002".parse().unwrap()),
+        )
+        .await;
+    assert_eq!(resp.status(), 429);
+    assert_eq!(resp.headers()["retry-after"], "1");
+
+    // Other clients have their own quota.
+    assert_eq!(get_index(&context, "2.2.2.2:1000", None).await, 200);
+}
+
+#[tokio::test]
+async fn test_rate_limit_by_api_key() {
+    let context = rate_limited_context(current_function_name!());
+    assert_eq!(get_index(&context, "1.1.1.1:1000", None).await, 200);
+    assert_eq!(get_index(&context, "1.1.1.1:1000", None).await, 200);
+    assert_eq!(get_index(&context, "1.1.1.1:1000", None).await, 429);
+
+    // A known API key is limited by its own quota, shared by all its IP addresses.
+    for remote in ["1.1.1.1:1000", "2.2.2.2:1000", "3.3.3.3:1000"] {
+        assert_eq!(get_index(&context, remote, Some(API_KEY)).await, 200);
+    }
+    assert_eq!(
+        get_index(&context, "4.4.4.4:1000", Some(API_KEY)).await,
+        429
+    );
+
+    // Unknown API keys are limited by IP address.
+    assert_eq!(
+        get_index(&context, "1.1.1.1:1000", Some("unknown")).await,
+        429
+    );
+}
+
+#[tokio::test]
+async fn test_health_check_is_not_rate_limited() {
+    let context = rate_limited_context(current_function_name!());
+    for _ in 0..5 {
+        let resp = context
+            .reply(
+                warp::test::request()
+                    .method("GET")
+                    .path("/-/healthy")
+                    .remote_addr("1.1.1.1:1000".parse().unwrap()),
+            )
+            .await;
+        assert_eq!(resp.status(), 200);
+    }
+}
+
+#[tokio::test]
+async fn test_idle_ip_bucket_is_garbage_collected() {
+    let rate_limiter = RateLimiter::new(&rate_limit_config());
+    rate_limiter
+        .acquire(Some("1.1.1.1:1000".parse().unwrap()), None)
+        .unwrap();
+    rate_limiter
+        .acquire(
+            Some("2.2.2.2:1000".parse().unwrap()),
+            Some(API_KEY.to_owned()),
+        )
+        .unwrap();
+
+    // The buckets of the clients which just made requests are not full yet.
+    assert_eq!(rate_limiter.garbage_collect(), 0);
+
+    // Once the clients are idle for long enough, their refilled buckets are dropped.
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(rate_limiter.garbage_collect(), 2);
+    assert_eq!(rate_limiter.garbage_collect(), 0);
+
+    // A returning client starts over with a new bucket.
+    rate_limiter
+        .acquire(Some("1.1.1.1:1000".parse().unwrap()), None)
+        .unwrap();
+}
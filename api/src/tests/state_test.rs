@@ -3,13 +3,27 @@
 
 use crate::{
     current_function_name,
+    state::State,
     tests::{new_test_context, TestContext},
 };
+use aptos_api_types::HexEncodedBytes;
 use aptos_sdk::types::LocalAccount;
-use move_deps::{move_core_types::account_address::AccountAddress, move_package::BuildConfig};
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::aptos_root_address,
+    state_proof::StateProof,
+    state_store::{state_key::StateKey, state_value::StateValueWithProof},
+};
+use move_deps::{
+    move_core_types::{
+        account_address::AccountAddress, language_storage::ResourceKey, parser::parse_struct_tag,
+    },
+    move_package::BuildConfig,
+};
 use serde::Serialize;
 use serde_json::{json, Value};
 use std::{convert::TryInto, path::PathBuf};
+use warp::Reply;
 
 #[tokio::test]
 async fn test_get_account_resource() {
@@ -93,6 +107,91 @@ async fn test_get_account_module_not_found() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test]
+async fn test_get_account_resource_with_proof() {
+    let context = new_test_context(current_function_name!());
+    let resource = context
+        .get(&get_account_resource("0xA550C18", "0x1::GUID::Generator"))
+        .await;
+    let resp = context
+        .get(&format!(
+            "{}?with_proof=true",
+            get_account_resource("0xA550C18", "0x1::GUID::Generator")
+        ))
+        .await;
+    assert_eq!(resp["data"], resource);
+
+    let state_value_with_proof: StateValueWithProof =
+        bcs::from_bytes(&decode_hex(&resp["state_value_with_proof"])).unwrap();
+    let state_proof: StateProof = bcs::from_bytes(&decode_hex(&resp["state_proof"])).unwrap();
+    let ledger_info = state_proof.latest_ledger_info();
+    assert_eq!(
+        ledger_info.version(),
+        context.get_latest_ledger_info().version()
+    );
+    // The client knows the genesis version by default, so only the genesis epoch change is
+    // proven.
+    assert_eq!(state_proof.epoch_changes().ledger_info_with_sigs.len(), 1);
+
+    let state_key = StateKey::AccessPath(AccessPath::resource_access_path(ResourceKey::new(
+        aptos_root_address(),
+        parse_struct_tag("0x1::GUID::Generator").unwrap(),
+    )));
+    state_value_with_proof
+        .verify(ledger_info, ledger_info.version(), state_key)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_get_account_resource_with_proof_after_commit() {
+    let mut context = new_test_context(current_function_name!());
+    let state = State::new(None, context.context.clone()).unwrap();
+    let version = context.get_latest_ledger_info().version();
+
+    // Commit a block between resolving the queried version and proving the value.
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&[txn]).await;
+    assert!(context.get_latest_ledger_info().version() > version);
+
+    let struct_tag = parse_struct_tag("0x1::GUID::Generator").unwrap();
+    let reply = state
+        .with_proof(Some(0))
+        .resource(aptos_root_address(), struct_tag.clone())
+        .unwrap()
+        .into_response();
+    let body = hyper::body::to_bytes(reply.into_body()).await.unwrap();
+    let resp: Value = serde_json::from_slice(&body).unwrap();
+
+    let state_value_with_proof: StateValueWithProof =
+        bcs::from_bytes(&decode_hex(&resp["state_value_with_proof"])).unwrap();
+    let state_proof: StateProof = bcs::from_bytes(&decode_hex(&resp["state_proof"])).unwrap();
+    let ledger_info = state_proof.latest_ledger_info();
+    assert_eq!(ledger_info.version(), version);
+
+    let state_key = StateKey::AccessPath(AccessPath::resource_access_path(ResourceKey::new(
+        aptos_root_address(),
+        struct_tag,
+    )));
+    state_value_with_proof
+        .verify(ledger_info, ledger_info.version(), state_key)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_get_account_resource_with_proof_and_invalid_known_version() {
+    let context = new_test_context(current_function_name!());
+    let known_version = context.get_latest_ledger_info().version() + 1;
+    context
+        .expect_status_code(400)
+        .get(&format!(
+            "{}?with_proof=true&known_version={}",
+            get_account_resource("0xA550C18", "0x1::GUID::Generator"),
+            known_version
+        ))
+        .await;
+}
+
 #[tokio::test]
 async fn test_get_table_item() {
     let mut context = new_test_context(current_function_name!());
@@ -258,3 +357,12 @@ async fn assert_table_item<T: Serialize, U: Serialize>(
     let response = api_get_table_item(ctx, table, key_type, value_type, key).await;
     assert_eq!(response, json!(value));
 }
+
+fn decode_hex(value: &Value) -> Vec<u8> {
+    value
+        .as_str()
+        .unwrap()
+        .parse::<HexEncodedBytes>()
+        .unwrap()
+        .into()
+}
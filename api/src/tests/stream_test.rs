@@ -0,0 +1,119 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    current_function_name, index,
+    tests::{new_test_context, TestContext},
+};
+use aptos_types::block_metadata::new_block_event_key;
+use hyper::{body::HttpBody, Body};
+use serde_json::Value;
+use std::time::Duration;
+use warp::Reply;
+
+#[tokio::test]
+async fn test_stream_transactions_from_start() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let mut body = open_stream(&context, "/transactions/stream?start=0").await;
+    let ledger_version = context.get_latest_ledger_info().version();
+    for version in 0..=ledger_version {
+        let (id, txn) = next_transaction(&mut body).await;
+        assert_eq!(id, version.to_string());
+        assert_eq!(txn["version"], version.to_string());
+    }
+}
+
+#[tokio::test]
+async fn test_stream_new_transactions_filtered_by_sender() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account();
+    let sender = root_account.address().to_hex_literal();
+
+    // The stream starts after the latest ledger version by default.
+    let mut body = open_stream(&context, &format!("/transactions/stream?sender={}", sender)).await;
+
+    let account = context.gen_account();
+    let txn = context.create_user_account_by(&mut root_account, &account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let (_, streamed) = next_transaction(&mut body).await;
+    assert_eq!(streamed["type"], "user_transaction");
+    assert_eq!(streamed["sender"], sender);
+    assert_eq!(streamed["hash"], txn.committed_hash().to_hex_literal());
+}
+
+#[tokio::test]
+async fn test_stream_transactions_filtered_by_event_key() {
+    let mut context = new_test_context(current_function_name!());
+    let event_key = aptos_api_types::EventKey::from(new_block_event_key());
+
+    let mut body = open_stream(
+        &context,
+        &format!("/transactions/stream?event_key={}", event_key),
+    )
+    .await;
+
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    // Only the block metadata transaction emits a new block event.
+    let (_, streamed) = next_transaction(&mut body).await;
+    assert_eq!(streamed["type"], "block_metadata_transaction");
+}
+
+#[tokio::test]
+async fn test_stream_transactions_with_invalid_sender() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/transactions/stream?sender=invalid"),
+        )
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+async fn open_stream(context: &TestContext, path: &str) -> Body {
+    let resp = warp::test::request()
+        .method("GET")
+        .path(path)
+        .filter(&index::routes(context.context.clone()))
+        .await
+        .unwrap()
+        .into_response();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+    resp.into_body()
+}
+
+/// Reads the next server-sent event from the stream, and returns its id and decoded transaction.
+async fn next_transaction(body: &mut Body) -> (String, Value) {
+    let mut buf = String::new();
+    while !buf.ends_with("\n\n") {
+        let chunk = tokio::time::timeout(Duration::from_secs(10), body.data())
+            .await
+            .expect("timed out waiting for streamed transaction")
+            .expect("stream ended")
+            .unwrap();
+        buf.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+
+    let mut id = None;
+    let mut data = None;
+    for line in buf.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            assert_eq!(value, "transaction", "unexpected event: {}", buf);
+        } else if let Some(value) = line.strip_prefix("id:") {
+            id = Some(value.to_owned());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data = Some(serde_json::from_str(value).unwrap());
+        }
+    }
+    (id.unwrap(), data.unwrap())
+}
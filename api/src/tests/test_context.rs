@@ -47,6 +47,10 @@ use vm_validator::vm_validator::VMValidator;
 use warp::http::header::CONTENT_TYPE;
 
 pub fn new_test_context(test_name: &'static str) -> TestContext {
+    new_test_context_with_config(test_name, ApiConfig::default())
+}
+
+pub fn new_test_context_with_config(test_name: &'static str, api_config: ApiConfig) -> TestContext {
     let tmp_dir = TempPath::new();
     tmp_dir.create_as_dir().unwrap();
 
@@ -71,7 +75,7 @@ pub fn new_test_context(test_name: &'static str) -> TestContext {
             ChainId::test(),
             db.clone(),
             mempool.ac_client.clone(),
-            ApiConfig::default(),
+            api_config,
         ),
         rng,
         root_keys,
@@ -248,12 +252,12 @@ impl TestContext {
             }
         }
 
+        let ledger_info = self.new_ledger_info(&metadata, result.root_hash(), txns.len());
+        let version = ledger_info.ledger_info().version();
         self.executor
-            .commit_blocks(
-                vec![metadata.id()],
-                self.new_ledger_info(&metadata, result.root_hash(), txns.len()),
-            )
+            .commit_blocks(vec![metadata.id()], ledger_info)
             .unwrap();
+        self.context.notify_commit(version);
 
         self.mempool
             .mempool_notifier
@@ -363,6 +367,17 @@ impl TestContext {
         .await
     }
 
+    pub async fn post_bcs_txn_batch(&self, path: &str, body: impl AsRef<[u8]>) -> Value {
+        self.execute(
+            warp::test::request()
+                .method("POST")
+                .path(path)
+                .header(CONTENT_TYPE, mime_types::BCS_SIGNED_TRANSACTION_BATCH)
+                .body(body),
+        )
+        .await
+    }
+
     pub async fn reply(&self, req: warp::test::RequestBuilder) -> Response<Bytes> {
         req.reply(&index::routes(self.context.clone())).await
     }
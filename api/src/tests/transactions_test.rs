@@ -6,7 +6,7 @@ use crate::{
     tests::{assert_json, new_test_context, pretty, TestContext},
 };
 
-use aptos_api_types::HexEncodedBytes;
+use aptos_api_types::{AsConverter, HexEncodedBytes};
 use aptos_crypto::{
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
     PrivateKey, SigningKey, Uniform,
@@ -25,6 +25,7 @@ use aptos_types::{
 
 use aptos_crypto::ed25519::Ed25519PrivateKey;
 use aptos_types::state_store::state_key::StateKey;
+use aptos_vm::data_cache::AsMoveResolver;
 use move_deps::move_core_types::{
     identifier::Identifier,
     language_storage::{ModuleId, StructTag, TypeTag},
@@ -283,6 +284,95 @@ async fn test_post_transaction_rejected_by_mempool() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test]
+async fn test_post_bcs_format_transaction_batch() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    let invalid_signature_txn = context.create_invalid_signature_transaction();
+    let body = bcs::to_bytes(&vec![txn.clone(), invalid_signature_txn]).unwrap();
+    let resp = context
+        .post_bcs_txn_batch("/transactions/batch", body)
+        .await;
+
+    // A rejected transaction does not fail the other ones.
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["hash"], txn.committed_hash().to_hex_literal());
+    assert_eq!(results[0]["status"], "accepted");
+    assert!(results[0].get("message").is_none());
+    assert_eq!(results[1]["status"], "invalid_transaction");
+    assert_eq!(results[1]["message"], "INVALID_SIGNATURE");
+}
+
+#[tokio::test]
+async fn test_post_transaction_batch_exceeding_limit() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    let txns = vec![txn; context.context.max_submit_transaction_batch_size() + 1];
+    let resp = context
+        .expect_status_code(400)
+        .post_bcs_txn_batch("/transactions/batch", bcs::to_bytes(&txns).unwrap())
+        .await;
+    assert_eq!(resp["code"], 400);
+}
+
+#[tokio::test]
+async fn test_simulate_transaction_with_dummy_signature() {
+    let mut context = new_test_context(current_function_name!());
+    let txn = context.create_invalid_signature_transaction();
+    let resp = context
+        .post_bcs_txn("/transactions/simulate", bcs::to_bytes(&txn).unwrap())
+        .await;
+
+    assert!(resp["success"].as_bool().unwrap(), "{}", pretty(&resp));
+    assert_eq!(resp["type"], "user_transaction");
+    assert_ne!(resp["gas_used"].as_str().unwrap(), "0");
+
+    // simulation must not submit the transaction to mempool
+    let hash = resp["hash"].as_str().unwrap();
+    context
+        .expect_status_code(404)
+        .get(&format!("/transactions/{}", hash))
+        .await;
+}
+
+#[tokio::test]
+async fn test_simulate_transaction_with_valid_signature() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    let resp = context
+        .expect_status_code(400)
+        .post_bcs_txn("/transactions/simulate", bcs::to_bytes(&txn).unwrap())
+        .await;
+    assert_eq!(
+        resp["message"],
+        "simulated transaction must not have a valid signature"
+    );
+}
+
+#[tokio::test]
+async fn test_simulate_json_transaction() {
+    let mut context = new_test_context(current_function_name!());
+    let txn = context.create_invalid_signature_transaction();
+    let state_view = context.latest_state_view();
+    let request = state_view
+        .as_move_resolver()
+        .as_converter()
+        .try_into_pending_transaction(txn)
+        .unwrap();
+
+    let resp = context
+        .post(
+            "/transactions/simulate",
+            serde_json::to_value(request).unwrap(),
+        )
+        .await;
+    assert!(resp["success"].as_bool().unwrap(), "{}", pretty(&resp));
+}
+
 #[ignore]
 #[tokio::test]
 async fn test_multi_agent_signed_transaction() {
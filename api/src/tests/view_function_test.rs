@@ -0,0 +1,120 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{current_function_name, tests::new_test_context};
+use aptos_api_types::mime_types;
+use aptos_types::{transaction::ScriptFunction, utility_coin::TEST_COIN_TYPE};
+use move_deps::move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use serde_json::json;
+use warp::http::header::CONTENT_TYPE;
+
+#[tokio::test]
+async fn test_view_function() {
+    let context = new_test_context(current_function_name!());
+    let root = context.root_account().address();
+    let resp = context
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::Account::exists_at",
+                "type_arguments": [],
+                "arguments": [root.to_hex_literal()],
+            }),
+        )
+        .await;
+    assert_eq!(resp, json!([true]));
+
+    let resp = context
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::Account::get_sequence_number",
+                "type_arguments": [],
+                "arguments": [root.to_hex_literal()],
+            }),
+        )
+        .await;
+    assert_eq!(resp, json!(["0"]));
+}
+
+#[tokio::test]
+async fn test_view_generic_function() {
+    let context = new_test_context(current_function_name!());
+    let root = context.root_account().address();
+    let resp = context
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::Coin::balance",
+                "type_arguments": [TEST_COIN_TYPE.to_string()],
+                "arguments": [root.to_hex_literal()],
+            }),
+        )
+        .await;
+    let balances = resp.as_array().unwrap();
+    assert_eq!(balances.len(), 1);
+    assert!(balances[0].as_str().unwrap().parse::<u64>().is_ok());
+}
+
+#[tokio::test]
+async fn test_view_function_with_ledger_version() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let version = context.get_latest_ledger_info().version();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let request = json!({
+        "function": "0x1::Account::exists_at",
+        "type_arguments": [],
+        "arguments": [account.address().to_hex_literal()],
+    });
+    let resp = context.post("/view", request.clone()).await;
+    assert_eq!(resp, json!([true]));
+    let resp = context
+        .post(&format!("/view?version={}", version), request)
+        .await;
+    assert_eq!(resp, json!([false]));
+}
+
+#[tokio::test]
+async fn test_view_non_public_function() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::Account::create_account",
+                "type_arguments": [],
+                "arguments": ["0x1"],
+            }),
+        )
+        .await;
+    assert!(resp["message"].as_str().unwrap().contains("is not public"));
+}
+
+#[tokio::test]
+async fn test_view_bcs_function() {
+    let context = new_test_context(current_function_name!());
+    let root = context.root_account().address();
+    let function = ScriptFunction::new(
+        ModuleId::new(
+            aptos_types::account_config::CORE_CODE_ADDRESS,
+            Identifier::new("Account").unwrap(),
+        ),
+        Identifier::new("exists_at").unwrap(),
+        vec![],
+        vec![bcs::to_bytes(&root).unwrap()],
+    );
+    let resp = context
+        .execute(
+            warp::test::request()
+                .method("POST")
+                .path("/view")
+                .header(CONTENT_TYPE, mime_types::BCS_VIEW_FUNCTION)
+                .body(bcs::to_bytes(&function).unwrap()),
+        )
+        .await;
+    assert_eq!(resp, json!([true]));
+}
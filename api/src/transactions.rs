@@ -10,16 +10,19 @@ use crate::{
 };
 
 use aptos_api_types::{
-    mime_types::BCS_SIGNED_TRANSACTION, AsConverter, Error, LedgerInfo, Response, Transaction,
-    TransactionData, TransactionId, TransactionOnChainData, TransactionSigningMessage,
+    mime_types::{BCS_SIGNED_TRANSACTION, BCS_SIGNED_TRANSACTION_BATCH},
+    AsConverter, Error, LedgerInfo, Response, Transaction, TransactionData, TransactionId,
+    TransactionOnChainData, TransactionSigningMessage, TransactionSubmissionResult,
     UserTransactionRequest,
 };
 use aptos_types::{
     mempool_status::MempoolStatusCode,
-    transaction::{RawTransaction, SignedTransaction},
+    transaction::{ExecutionStatus, RawTransaction, SignedTransaction, TransactionStatus},
 };
+use aptos_vm::AptosVM;
 
 use anyhow::Result;
+use futures::future::try_join_all;
 use warp::{
     filters::BoxedFilter,
     http::{header::CONTENT_TYPE, StatusCode},
@@ -95,6 +98,57 @@ pub fn submit_bcs_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+// POST /transactions/batch with BCS encoded `Vec<SignedTransaction>`
+pub fn submit_bcs_transaction_batch(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("transactions" / "batch")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::header::exact(
+            CONTENT_TYPE.as_str(),
+            BCS_SIGNED_TRANSACTION_BATCH,
+        ))
+        .and(warp::body::bytes())
+        .and(context.filter())
+        .and_then(handle_submit_bcs_transaction_batch)
+        .with(metrics("submit_bcs_transaction_batch"))
+        .boxed()
+}
+
+// POST /transactions/simulate with JSON
+pub fn simulate_json_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("transactions" / "simulate")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::json::<UserTransactionRequest>())
+        .and(context.filter())
+        .and_then(handle_simulate_json_transactions)
+        .with(metrics("simulate_json_transactions"))
+        .boxed()
+}
+
+// POST /transactions/simulate with BCS
+pub fn simulate_bcs_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    // See `submit_bcs_transactions` for why the content-type header is matched exactly.
+    warp::path!("transactions" / "simulate")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::header::exact(
+            CONTENT_TYPE.as_str(),
+            BCS_SIGNED_TRANSACTION,
+        ))
+        .and(warp::body::bytes())
+        .and(context.filter())
+        .and_then(handle_simulate_bcs_transactions)
+        .with(metrics("simulate_bcs_transactions"))
+        .boxed()
+}
+
 // POST /transactions/signing_message
 pub fn create_signing_message(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("transactions" / "signing_message")
@@ -153,6 +207,34 @@ async fn handle_submit_bcs_transactions(
     Ok(Transactions::new(context)?.create(txn).await?)
 }
 
+async fn handle_submit_bcs_transaction_batch(
+    body: bytes::Bytes,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_submit_bcs_transaction_batch")?;
+    let txns = bcs::from_bytes(&body)
+        .map_err(|err| Error::invalid_request_body(format!("deserialize error: {}", err)))?;
+    Ok(Transactions::new(context)?.create_batch(txns).await?)
+}
+
+async fn handle_simulate_json_transactions(
+    body: UserTransactionRequest,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_simulate_json_transactions")?;
+    Ok(Transactions::new(context)?.simulate_from_request(body)?)
+}
+
+async fn handle_simulate_bcs_transactions(
+    body: bytes::Bytes,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_simulate_bcs_transactions")?;
+    let txn = bcs::from_bytes(&body)
+        .map_err(|err| Error::invalid_request_body(format!("deserialize error: {}", err)))?;
+    Ok(Transactions::new(context)?.simulate(txn)?)
+}
+
 async fn handle_create_signing_message(
     body: UserTransactionRequest,
     context: Context,
@@ -215,6 +297,100 @@ impl Transactions {
         }
     }
 
+    /// Submits all the transactions to mempool, and returns their submission results in order.
+    /// Unlike `create`, a rejected transaction does not fail the request.
+    pub async fn create_batch(self, txns: Vec<SignedTransaction>) -> Result<impl Reply, Error> {
+        let max_batch_size = self.context.max_submit_transaction_batch_size();
+        if txns.len() > max_batch_size {
+            return Err(Error::invalid_request_body(format!(
+                "batch of {} transactions exceeds limit {}",
+                txns.len(),
+                max_batch_size
+            )));
+        }
+
+        let statuses = try_join_all(
+            txns.iter()
+                .map(|txn| self.context.submit_transaction(txn.clone())),
+        )
+        .await?;
+        let results: Vec<_> = txns
+            .into_iter()
+            .zip(statuses)
+            .map(|(txn, (mempool_status, vm_status_opt))| {
+                TransactionSubmissionResult::new(
+                    txn.committed_hash().into(),
+                    &mempool_status,
+                    vm_status_opt,
+                )
+            })
+            .collect();
+        Response::new(self.ledger_info, &results)
+    }
+
+    pub fn simulate_from_request(self, req: UserTransactionRequest) -> Result<impl Reply, Error> {
+        let txn = self
+            .context
+            .move_resolver()?
+            .as_converter()
+            .try_into_signed_transaction(req, self.context.chain_id())
+            .map_err(|e| {
+                Error::invalid_request_body(format!(
+                    "failed to create SignedTransaction from UserTransactionRequest: {}",
+                    e
+                ))
+            })?;
+        self.simulate(txn)
+    }
+
+    pub fn simulate(self, txn: SignedTransaction) -> Result<impl Reply, Error> {
+        // A correctly signed transaction could be replayed by anyone who observes the simulation
+        // request, so only unsigned (dummy-signed) transactions are accepted here.
+        if txn.clone().check_signature().is_ok() {
+            return Err(Error::bad_request(
+                "simulated transaction must not have a valid signature",
+            ));
+        }
+
+        let state_view = self.context.latest_state_view()?;
+        let (vm_status, output) = AptosVM::simulate_signed_transaction(&txn, &state_view);
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            TransactionStatus::Discard(code) => ExecutionStatus::MiscellaneousError(Some(*code)),
+            TransactionStatus::Retry => {
+                return Err(Error::internal(anyhow::format_err!(
+                    "unexpected simulation result: {:?}",
+                    vm_status
+                )))
+            }
+        };
+
+        let version = self.ledger_info.version();
+        let zero_hash = aptos_crypto::HashValue::zero();
+        let info = aptos_types::transaction::TransactionInfo::new(
+            txn.clone().committed_hash(),
+            zero_hash,
+            zero_hash,
+            None,
+            output.gas_used(),
+            status,
+        );
+        let simulated_txn = TransactionOnChainData {
+            version,
+            transaction: aptos_types::transaction::Transaction::UserTransaction(txn),
+            info,
+            events: output.events().to_vec(),
+            accumulator_root_hash: zero_hash,
+            changes: output.write_set().clone(),
+        };
+
+        let resolver = self.context.move_resolver()?;
+        let txn = resolver
+            .as_converter()
+            .try_into_onchain_transaction(self.ledger_info.timestamp(), simulated_txn)?;
+        Response::new(self.ledger_info, &txn)
+    }
+
     pub fn list(self, page: Page) -> Result<impl Reply, Error> {
         let ledger_version = self.ledger_info.version();
         let limit = page.limit()?;
@@ -0,0 +1,135 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context, failpoint::fail_point, metrics::metrics, param::LedgerVersionParam,
+    version::Version,
+};
+
+use aptos_api_types::{
+    mime_types::BCS_VIEW_FUNCTION, AsConverter, Error, LedgerInfo, Response, TransactionId,
+    ViewRequest,
+};
+use aptos_types::transaction::ScriptFunction;
+use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
+
+use storage_interface::state_view::DbStateView;
+use warp::{filters::BoxedFilter, http::header::CONTENT_TYPE, Filter, Rejection, Reply};
+
+// POST /view with JSON
+pub fn view_json_function(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("view")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::json::<ViewRequest>())
+        .and(context.filter())
+        .and(warp::query::<Version>())
+        .map(|body, ctx, version: Version| (version.version, body, ctx))
+        .untuple_one()
+        .and_then(handle_view_json_function)
+        .with(metrics("view_json_function"))
+        .boxed()
+}
+
+// POST /view with BCS encoded `ScriptFunction`
+pub fn view_bcs_function(context: Context) -> BoxedFilter<(impl Reply,)> {
+    // See `transactions::submit_bcs_transactions` for why the content-type header is matched
+    // exactly.
+    warp::path!("view")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::header::exact(
+            CONTENT_TYPE.as_str(),
+            BCS_VIEW_FUNCTION,
+        ))
+        .and(warp::body::bytes())
+        .and(context.filter())
+        .and(warp::query::<Version>())
+        .map(|body, ctx, version: Version| (version.version, body, ctx))
+        .untuple_one()
+        .and_then(handle_view_bcs_function)
+        .with(metrics("view_bcs_function"))
+        .boxed()
+}
+
+async fn handle_view_json_function(
+    ledger_version: Option<LedgerVersionParam>,
+    body: ViewRequest,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_view_json_function")?;
+    Ok(ViewFunction::new(ledger_version, context)?.view_from_request(body)?)
+}
+
+async fn handle_view_bcs_function(
+    ledger_version: Option<LedgerVersionParam>,
+    body: bytes::Bytes,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_view_bcs_function")?;
+    let function = bcs::from_bytes(&body)
+        .map_err(|err| Error::invalid_request_body(format!("deserialize error: {}", err)))?;
+    Ok(ViewFunction::new(ledger_version, context)?.view(function)?)
+}
+
+struct ViewFunction {
+    state_view: DbStateView,
+    latest_ledger_info: LedgerInfo,
+    max_gas_amount: u64,
+}
+
+impl ViewFunction {
+    fn new(ledger_version: Option<LedgerVersionParam>, context: Context) -> Result<Self, Error> {
+        let latest_ledger_info = context.get_latest_ledger_info()?;
+        let ledger_version = ledger_version
+            .map(|v| v.parse("ledger version"))
+            .unwrap_or_else(|| Ok(latest_ledger_info.version()))?;
+
+        if ledger_version > latest_ledger_info.version() {
+            return Err(Error::not_found(
+                "ledger",
+                TransactionId::Version(ledger_version),
+                latest_ledger_info.version(),
+            ));
+        }
+
+        Ok(Self {
+            state_view: context.state_view_at_version(ledger_version)?,
+            latest_ledger_info,
+            max_gas_amount: context.max_view_function_gas(),
+        })
+    }
+
+    pub fn view_from_request(self, req: ViewRequest) -> Result<impl Reply, Error> {
+        let function = self
+            .state_view
+            .as_move_resolver()
+            .as_converter()
+            .try_into_view_function(req)
+            .map_err(Error::invalid_request_body)?;
+        self.view(function)
+    }
+
+    pub fn view(self, function: ScriptFunction) -> Result<impl Reply, Error> {
+        let resolver = self.state_view.as_move_resolver();
+        let converter = resolver.as_converter();
+
+        // The VM bypasses function visibility, so make sure the function can be viewed first.
+        converter
+            .find_view_function(function.module(), function.function())
+            .map_err(Error::bad_request)?;
+        let return_values =
+            AptosVM::execute_view_function(&function, &self.state_view, self.max_gas_amount)
+                .map_err(|status| {
+                    Error::bad_request(format!("view function execution failed: {:?}", status))
+                })?;
+        let values = converter
+            .try_into_view_function_return_values(&function, return_values)
+            .map_err(Error::bad_request)?;
+        Response::new(self.latest_ledger_info, &values)
+    }
+}
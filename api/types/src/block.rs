@@ -0,0 +1,17 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, HashValue, Transaction, U64};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    pub block_height: U64,
+    pub block_hash: HashValue,
+    pub block_timestamp: U64,
+    pub first_version: U64,
+    pub last_version: U64,
+    pub proposer: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<Vec<Transaction>>,
+}
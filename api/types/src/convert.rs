@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    move_types::MoveFunctionVisibility,
     transaction::{ModuleBundlePayload, StateCheckpointTransaction},
     Bytecode, DirectWriteSet, Event, HexEncodedBytes, MoveFunction, MoveModuleBytecode,
     MoveResource, MoveScriptBytecode, MoveValue, ScriptFunctionId, ScriptFunctionPayload,
     ScriptPayload, ScriptWriteSet, Transaction, TransactionInfo, TransactionOnChainData,
-    TransactionPayload, UserTransactionRequest, WriteSet, WriteSetChange, WriteSetPayload,
+    TransactionPayload, UserTransactionRequest, ViewRequest, WriteSet, WriteSetChange,
+    WriteSetPayload,
 };
 use anyhow::{bail, ensure, format_err, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -27,7 +29,7 @@ use move_deps::{
     move_binary_format::file_format::FunctionHandleIndex,
     move_core_types,
     move_core_types::{
-        identifier::Identifier,
+        identifier::{IdentStr, Identifier},
         language_storage::{ModuleId, StructTag, TypeTag},
         value::{MoveStructLayout, MoveTypeLayout},
     },
@@ -421,6 +423,85 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
         Ok(ret)
     }
 
+    pub fn try_into_view_function(&self, request: ViewRequest) -> Result<ScriptFunction> {
+        let ViewRequest {
+            function,
+            type_arguments,
+            arguments,
+        } = request;
+
+        let module = function.module.clone().into();
+        let func = self.find_view_function(&module, function.name.as_ident_str())?;
+        ensure!(
+            func.generic_type_params.len() == type_arguments.len(),
+            "expect {} type arguments for function {}, but got {}",
+            func.generic_type_params.len(),
+            function,
+            type_arguments.len()
+        );
+        let args = self
+            .try_into_vm_values(func, arguments)?
+            .iter()
+            .map(bcs::to_bytes)
+            .collect::<Result<_, bcs::Error>>()?;
+
+        Ok(ScriptFunction::new(
+            module,
+            function.name,
+            type_arguments
+                .into_iter()
+                .map(|v| v.try_into())
+                .collect::<Result<_>>()?,
+            args,
+        ))
+    }
+
+    pub fn try_into_view_function_return_values(
+        &self,
+        function: &ScriptFunction,
+        return_values: Vec<Vec<u8>>,
+    ) -> Result<Vec<MoveValue>> {
+        let func = self.find_view_function(function.module(), function.function())?;
+        ensure!(
+            func.return_.len() == return_values.len(),
+            "expect {} return values from function {}::{}, but got {}",
+            func.return_.len(),
+            function.module(),
+            function.function(),
+            return_values.len()
+        );
+        func.return_
+            .into_iter()
+            .zip(return_values.into_iter())
+            .map(|(typ, bytes)| {
+                let type_tag = typ.try_into_type_tag_with_type_args(function.ty_args())?;
+                self.try_into_move_value(&type_tag, &bytes)
+            })
+            .collect()
+    }
+
+    // Only public functions that do not take a signer can be viewed, because there is no
+    // transaction sender to provide one.
+    pub fn find_view_function(&self, module: &ModuleId, name: &IdentStr) -> Result<MoveFunction> {
+        let code = self.inner.get_module(module)? as Rc<dyn Bytecode>;
+        let func = code
+            .find_function(name)
+            .ok_or_else(|| format_err!("could not find function by {}::{}", module, name))?;
+        ensure!(
+            matches!(func.visibility, MoveFunctionVisibility::Public),
+            "function {}::{} is not public",
+            module,
+            name
+        );
+        ensure!(
+            !func.params.iter().any(|p| p.is_signer()),
+            "function {}::{} takes a signer argument and can not be viewed",
+            module,
+            name
+        );
+        Ok(func)
+    }
+
     pub fn try_into_vm_values(
         &self,
         func: MoveFunction,
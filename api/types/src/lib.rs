@@ -3,24 +3,31 @@
 
 mod account;
 mod address;
+mod block;
 mod bytecode;
 mod convert;
 mod error;
 mod event_key;
+mod gas_estimation;
 mod hash;
 mod ledger_info;
 pub mod mime_types;
 mod move_types;
 mod response;
+mod state_proof;
+mod submission;
 mod table;
 mod transaction;
+mod view;
 
 pub use account::AccountData;
 pub use address::Address;
+pub use block::Block;
 pub use bytecode::Bytecode;
 pub use convert::{new_vm_ascii_string, AsConverter, MoveConverter};
 pub use error::Error;
 pub use event_key::EventKey;
+pub use gas_estimation::GasEstimation;
 pub use hash::HashValue;
 pub use ledger_info::LedgerInfo;
 pub use move_types::{
@@ -29,8 +36,11 @@ pub use move_types::{
     U128, U64,
 };
 pub use response::{
-    Response, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    Response, X_APTOS_CHAIN_ID, X_APTOS_CURSOR, X_APTOS_EPOCH, X_APTOS_LEDGER_TIMESTAMP,
+    X_APTOS_LEDGER_VERSION,
 };
+pub use state_proof::ValueWithProof;
+pub use submission::{SubmissionStatus, TransactionSubmissionResult};
 pub use table::TableItemRequest;
 pub use transaction::{
     BlockMetadataTransaction, DirectWriteSet, Event, GenesisTransaction, PendingTransaction,
@@ -39,3 +49,4 @@ pub use transaction::{
     TransactionSigningMessage, UserTransaction, UserTransactionRequest, WriteSet, WriteSetChange,
     WriteSetPayload,
 };
+pub use view::ViewRequest;
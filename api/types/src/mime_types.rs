@@ -2,4 +2,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub const BCS_SIGNED_TRANSACTION: &str = "application/x.aptos.signed_transaction+bcs";
+pub const BCS_SIGNED_TRANSACTION_BATCH: &str = "application/x.aptos.signed_transaction_batch+bcs";
+pub const BCS_VIEW_FUNCTION: &str = "application/x.aptos.view_function+bcs";
 pub const JSON: &str = "application/json";
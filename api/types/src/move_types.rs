@@ -541,6 +541,35 @@ impl MoveType {
             _ => false,
         }
     }
+
+    /// Converts into `TypeTag`, replacing generic type parameters by the given type arguments.
+    pub fn try_into_type_tag_with_type_args(
+        self,
+        type_args: &[TypeTag],
+    ) -> anyhow::Result<TypeTag> {
+        let ret = match self {
+            MoveType::GenericTypeParam { index } => {
+                type_args.get(index as usize).cloned().ok_or_else(|| {
+                    format_err!("missing type argument for generic type parameter {}", index)
+                })?
+            }
+            MoveType::Vector { items } => {
+                TypeTag::Vector(Box::new(items.try_into_type_tag_with_type_args(type_args)?))
+            }
+            MoveType::Struct(tag) => TypeTag::Struct(StructTag {
+                address: tag.address.into(),
+                module: tag.module,
+                name: tag.name,
+                type_params: tag
+                    .generic_type_params
+                    .into_iter()
+                    .map(|p| p.try_into_type_tag_with_type_args(type_args))
+                    .collect::<anyhow::Result<Vec<TypeTag>>>()?,
+            }),
+            typ => typ.try_into()?,
+        };
+        Ok(ret)
+    }
 }
 
 impl From<TypeTag> for MoveType {
@@ -0,0 +1,20 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::HexEncodedBytes;
+use serde::{Deserialize, Serialize};
+
+/// A state value along with the proofs authenticating it, returned by the state endpoints when
+/// `with_proof=true` is set.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValueWithProof<T> {
+    /// The value rendered in JSON, for convenience only: light clients should decode the
+    /// authenticated raw value carried by `state_value_with_proof` instead.
+    pub data: T,
+    /// BCS serialized `StateValueWithProof`, proving the raw value against the ledger info of
+    /// `state_proof`.
+    pub state_value_with_proof: HexEncodedBytes,
+    /// BCS serialized `StateProof`: the signed latest ledger info, and the epoch change proof from
+    /// the epoch of the version known to the client.
+    pub state_proof: HexEncodedBytes,
+}
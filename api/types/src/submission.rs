@@ -0,0 +1,92 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::HashValue;
+use aptos_types::{
+    mempool_status::{MempoolStatus, MempoolStatusCode},
+    vm_status::{DiscardedVMStatus, StatusCode},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    /// The transaction was accepted by mempool.
+    Accepted,
+    /// The transaction sequence number is older than the sender account sequence number.
+    SequenceNumberTooOld,
+    /// The transaction sequence number is too far ahead of the sender account sequence number.
+    SequenceNumberTooNew,
+    /// The sender cannot pay for the transaction max gas amount.
+    InsufficientBalance,
+    /// The transaction expiration timestamp has passed.
+    TransactionExpired,
+    /// The transaction failed any other validation, e.g. its signature is invalid.
+    InvalidTransaction,
+    /// Mempool rejected the sequence number, e.g. it is already committed.
+    InvalidSequenceNumber,
+    /// Mempool reached its capacity.
+    MempoolIsFull,
+    /// The sender reached its capacity of transactions in mempool.
+    TooManyTransactions,
+    /// A transaction with the same sender and sequence number is in mempool, and can only be
+    /// replaced by one with a higher gas price.
+    InvalidUpdate,
+    Unknown,
+}
+
+/// The outcome of submitting one transaction of a batch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSubmissionResult {
+    pub hash: HashValue,
+    pub status: SubmissionStatus,
+    /// Why the transaction was rejected, not set when it was accepted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl TransactionSubmissionResult {
+    pub fn new(
+        hash: HashValue,
+        mempool_status: &MempoolStatus,
+        vm_status: Option<DiscardedVMStatus>,
+    ) -> Self {
+        let (status, message) = match mempool_status.code {
+            MempoolStatusCode::Accepted => (SubmissionStatus::Accepted, None),
+            MempoolStatusCode::VmError => {
+                let status = match vm_status {
+                    Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD) => {
+                        SubmissionStatus::SequenceNumberTooOld
+                    }
+                    Some(StatusCode::SEQUENCE_NUMBER_TOO_NEW) => {
+                        SubmissionStatus::SequenceNumberTooNew
+                    }
+                    Some(StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE) => {
+                        SubmissionStatus::InsufficientBalance
+                    }
+                    Some(StatusCode::TRANSACTION_EXPIRED) => SubmissionStatus::TransactionExpired,
+                    _ => SubmissionStatus::InvalidTransaction,
+                };
+                let message = vm_status
+                    .map(|s| format!("{:?}", s))
+                    .unwrap_or_else(|| "UNKNOWN".to_owned());
+                (status, Some(message))
+            }
+            code => {
+                let status = match code {
+                    MempoolStatusCode::InvalidSeqNumber => SubmissionStatus::InvalidSequenceNumber,
+                    MempoolStatusCode::MempoolIsFull => SubmissionStatus::MempoolIsFull,
+                    MempoolStatusCode::TooManyTransactions => SubmissionStatus::TooManyTransactions,
+                    MempoolStatusCode::InvalidUpdate => SubmissionStatus::InvalidUpdate,
+                    _ => SubmissionStatus::Unknown,
+                };
+                (status, Some(mempool_status.to_string()))
+            }
+        };
+        Self {
+            hash,
+            status,
+            message,
+        }
+    }
+}
@@ -11,7 +11,7 @@ use crate::{
         charge_global_write_gas_usage, get_transaction_output, AptosVMImpl, AptosVMInternals,
     },
     counters::*,
-    data_cache::StateViewCache,
+    data_cache::{AsMoveResolver, StateViewCache},
     errors::expect_only_successful_execution,
     logging::AdapterLogSchema,
     move_vm_ext::{MoveResolverExt, SessionExt, SessionId},
@@ -29,9 +29,9 @@ use aptos_types::{
     block_metadata::BlockMetadata,
     on_chain_config::{VMConfig, VMPublishingOption, Version},
     transaction::{
-        ChangeSet, ExecutionStatus, ModuleBundle, SignatureCheckedTransaction, SignedTransaction,
-        Transaction, TransactionOutput, TransactionPayload, TransactionStatus, VMValidatorResult,
-        WriteSetPayload,
+        ChangeSet, ExecutionStatus, ModuleBundle, ScriptFunction, SignatureCheckedTransaction,
+        SignedTransaction, Transaction, TransactionOutput, TransactionPayload, TransactionStatus,
+        VMValidatorResult, WriteSetPayload,
     },
     vm_status::{StatusCode, VMStatus},
     write_set::{WriteSet, WriteSetMut},
@@ -789,6 +789,53 @@ impl AptosVM {
         ))
     }
 
+    /// Executes a user transaction against `state_view` without checking its signature. The
+    /// output is returned to the caller and never committed, which lets clients estimate gas
+    /// usage and detect aborts before signing a transaction.
+    pub fn simulate_signed_transaction(
+        txn: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        let vm = AptosVM::new(state_view);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        if let Err(err) = vm.check_transaction_format(txn) {
+            return discard_error_vm_status(err);
+        }
+        let txn = SignatureCheckedTransaction::new_unchecked(txn.clone());
+        vm.execute_user_transaction(&state_view.as_move_resolver(), &txn, &log_context)
+    }
+
+    /// Executes a function against `state_view` and returns its BCS serialized return values.
+    /// Nothing is committed; `max_gas_amount` bounds the work a single call may do. Callers are
+    /// expected to check the function visibility, as it is bypassed here.
+    pub fn execute_view_function(
+        function: &ScriptFunction,
+        state_view: &impl StateView,
+        max_gas_amount: u64,
+    ) -> Result<Vec<Vec<u8>>, VMStatus> {
+        let vm = AptosVM::new(state_view);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let gas_schedule = vm.0.get_gas_schedule(&log_context)?;
+        let mut gas_status = GasStatus::new(gas_schedule, GasUnits::new(max_gas_amount));
+
+        let resolver = state_view.as_move_resolver();
+        let mut session = vm.0.new_session(&resolver, SessionId::void());
+        let return_values = session
+            .execute_function_bypass_visibility(
+                function.module(),
+                function.function(),
+                function.ty_args().to_vec(),
+                function.args().to_vec(),
+                &mut gas_status,
+            )
+            .map_err(|e| e.into_vm_status())?
+            .return_values;
+        Ok(return_values
+            .into_iter()
+            .map(|(bytes, _layout)| bytes)
+            .collect())
+    }
+
     /// Alternate form of 'execute_block' that keeps the vm_status before it goes into the
     /// `TransactionOutput`
     pub fn execute_block_and_keep_vm_status(
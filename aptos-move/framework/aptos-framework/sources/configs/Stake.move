@@ -99,7 +99,7 @@ module AptosFramework::Stake {
     }
 
     /// Any user can delegate a stake.
-    public(friend) fun delegate_stake(account: &signer, to: address, amount: u64, locked_until_secs: u64) acquires StakePool, ValidatorSet {
+    public(script) fun delegate_stake(account: &signer, to: address, amount: u64, locked_until_secs: u64) acquires StakePool, ValidatorSet {
         let coins = Coin::withdraw<TestCoin>(account, amount);
         let current_time = Timestamp::now_seconds();
         assert!(current_time + MINIMUM_LOCK_PERIOD < locked_until_secs, Errors::invalid_argument(ELOCK_TIME_TOO_SHORT));
@@ -182,6 +182,20 @@ module AptosFramework::Stake {
         validator_info.consensus_pubkey = consensus_pubkey;
     }
 
+    /// Update the network addresses of the validator and its full nodes, e.g. to rotate its network
+    /// keys, it'll take effect in next epoch.
+    public(script) fun update_network_and_fullnode_addresses(
+        account: &signer,
+        network_address: vector<u8>,
+        fullnode_address: vector<u8>
+    ) acquires ValidatorConfig {
+        let addr = Signer::address_of(account);
+        assert!(exists<ValidatorConfig>(addr), Errors::not_published(EVALIDATOR_CONFIG));
+        let validator_info = borrow_global_mut<ValidatorConfig>(addr);
+        validator_info.network_address = network_address;
+        validator_info.fullnode_address = fullnode_address;
+    }
+
     /// Initialize validator set to the core resource account.
     public fun initialize_validator_set(account: &signer, minimum_stake: u64, maximum_stake: u64) {
         SystemAddresses::assert_core_resource(account);
@@ -196,7 +210,7 @@ module AptosFramework::Stake {
     }
 
     /// Initiate by the validator info owner
-    public(friend) fun join_validator_set(account: &signer) acquires StakePool, ValidatorConfig, ValidatorSet {
+    public(script) fun join_validator_set(account: &signer) acquires StakePool, ValidatorConfig, ValidatorSet {
         let addr = Signer::address_of(account);
         let stake_pool = borrow_global<StakePool>(addr);
         let validator_set = borrow_global_mut<ValidatorSet>(@CoreResources);
@@ -211,7 +225,7 @@ module AptosFramework::Stake {
     }
 
     /// Initiate by the validator info owner.
-    public(friend) fun leave_validator_set(account: &signer) acquires ValidatorSet {
+    public(script) fun leave_validator_set(account: &signer) acquires ValidatorSet {
         let addr = Signer::address_of(account);
         let validator_set = borrow_global_mut<ValidatorSet>(@CoreResources);
 
@@ -423,6 +437,7 @@ module AptosFramework::Stake {
         leave_validator_set(&account_2);
         join_validator_set(&account_3);
         rotate_consensus_key(&account_1, x"1234");
+        update_network_and_fullnode_addresses(&account_1, x"5678", x"9abc");
         assert!(is_current_validator(addr2), 0);
         assert!(Vector::borrow(&borrow_global<ValidatorSet>(@CoreResources).pending_inactive, 0).addr == addr2, 0);
         assert!(!is_current_validator(addr3), 0);
@@ -433,10 +448,112 @@ module AptosFramework::Stake {
         assert!(!is_current_validator(addr2), 0);
         assert!(is_current_validator(addr3), 0);
         assert!(Vector::borrow(&borrow_global<ValidatorSet>(@CoreResources).active_validators, 0).config.consensus_pubkey == x"1234", 0);
+        assert!(Vector::borrow(&borrow_global<ValidatorSet>(@CoreResources).active_validators, 0).config.network_address == x"5678", 0);
+        assert!(Vector::borrow(&borrow_global<ValidatorSet>(@CoreResources).active_validators, 0).config.fullnode_address == x"9abc", 0);
         // validators without enough stake will be removed
         Timestamp::update_global_time_for_test(100001000000);
         unlock(&account_1, addr1);
         on_new_epoch();
         assert!(!is_current_validator(addr1), 0);
     }
+
+    #[test(core_resources = @CoreResources, account = @0x123)]
+    #[expected_failure(abort_code = 519)]
+    public(script) fun test_delegate_stake_with_short_lock(
+        core_resources: signer,
+        account: signer
+    ) acquires StakePool, ValidatorSet {
+        initialize_validator_set(&core_resources, 100, 10000);
+        Timestamp::set_time_has_started_for_testing(&core_resources);
+        Coin::mint_for_test<TestCoin>(&account, 10000);
+        register_validator_candidate(&account, Vector::empty(), Vector::empty(), Vector::empty());
+        delegate_stake(&account, Signer::address_of(&account), 100, MINIMUM_LOCK_PERIOD);
+    }
+
+    #[test(core_resources = @CoreResources, account = @0x123)]
+    #[expected_failure(abort_code = 2823)]
+    public(script) fun test_delegate_stake_above_maximum(
+        core_resources: signer,
+        account: signer
+    ) acquires StakePool, ValidatorSet {
+        initialize_validator_set(&core_resources, 100, 10000);
+        Timestamp::set_time_has_started_for_testing(&core_resources);
+        Coin::mint_for_test<TestCoin>(&account, 20000);
+        register_validator_candidate(&account, Vector::empty(), Vector::empty(), Vector::empty());
+        delegate_stake(&account, Signer::address_of(&account), 10001, 100000);
+    }
+
+    #[test(core_resources = @CoreResources, account = @0x123)]
+    #[expected_failure]
+    public(script) fun test_join_validator_set_without_registering(
+        core_resources: signer,
+        account: signer
+    ) acquires StakePool, ValidatorConfig, ValidatorSet {
+        initialize_validator_set(&core_resources, 100, 10000);
+        join_validator_set(&account);
+    }
+
+    #[test(core_resources = @CoreResources, account = @0x123)]
+    #[expected_failure(abort_code = 1287)]
+    public(script) fun test_join_validator_set_with_too_little_stake(
+        core_resources: signer,
+        account: signer
+    ) acquires StakePool, ValidatorConfig, ValidatorSet {
+        initialize_validator_set(&core_resources, 100, 10000);
+        Timestamp::set_time_has_started_for_testing(&core_resources);
+        Coin::mint_for_test<TestCoin>(&account, 10000);
+        register_validator_candidate(&account, Vector::empty(), Vector::empty(), Vector::empty());
+        delegate_stake(&account, Signer::address_of(&account), 99, 100000);
+        join_validator_set(&account);
+    }
+
+    #[test(core_resources = @CoreResources, account = @0x123)]
+    #[expected_failure(abort_code = 1799)]
+    public(script) fun test_join_validator_set_twice(
+        core_resources: signer,
+        account: signer
+    ) acquires StakePool, ValidatorConfig, ValidatorSet {
+        initialize_validator_set(&core_resources, 100, 10000);
+        Timestamp::set_time_has_started_for_testing(&core_resources);
+        Coin::mint_for_test<TestCoin>(&account, 10000);
+        register_validator_candidate(&account, Vector::empty(), Vector::empty(), Vector::empty());
+        delegate_stake(&account, Signer::address_of(&account), 100, 100000);
+        join_validator_set(&account);
+        on_new_epoch();
+        join_validator_set(&account);
+    }
+
+    #[test(core_resources = @CoreResources, account_1 = @0x123, account_2 = @0x234)]
+    #[expected_failure(abort_code = 2055)]
+    public(script) fun test_leave_validator_set_as_non_validator(
+        core_resources: signer,
+        account_1: signer,
+        account_2: signer
+    ) acquires StakePool, ValidatorConfig, ValidatorSet {
+        initialize_validator_set(&core_resources, 100, 10000);
+        Timestamp::set_time_has_started_for_testing(&core_resources);
+        Coin::mint_for_test<TestCoin>(&account_1, 10000);
+        register_validator_candidate(&account_1, Vector::empty(), Vector::empty(), Vector::empty());
+        delegate_stake(&account_1, Signer::address_of(&account_1), 100, 100000);
+        join_validator_set(&account_1);
+        on_new_epoch();
+        // only the validator itself can take it out of the validator set
+        leave_validator_set(&account_2);
+    }
+
+    #[test(core_resources = @CoreResources, account = @0x123)]
+    #[expected_failure(abort_code = 2311)]
+    public(script) fun test_leave_validator_set_as_last_validator(
+        core_resources: signer,
+        account: signer
+    ) acquires StakePool, ValidatorConfig, ValidatorSet {
+        initialize_validator_set(&core_resources, 100, 10000);
+        Timestamp::set_time_has_started_for_testing(&core_resources);
+        Coin::mint_for_test<TestCoin>(&account, 10000);
+        register_validator_candidate(&account, Vector::empty(), Vector::empty(), Vector::empty());
+        delegate_stake(&account, Signer::address_of(&account), 100, 100000);
+        join_validator_set(&account);
+        on_new_epoch();
+        leave_validator_set(&account);
+    }
 }
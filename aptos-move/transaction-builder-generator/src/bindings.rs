@@ -0,0 +1,336 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Typed Rust bindings for the modules of a Move package, as published on-chain.
+//!
+//! For each module, a Rust module is generated with:
+//! * a function returning the `TransactionPayload` of each script function, taking the type
+//!   arguments as `TypeTag`s and the arguments as typed values, which are BCS encoded. As
+//!   parameter names aren't part of the bytecode, arguments are named after their position.
+//! * a struct for each struct type, which can be decoded with `serde` from the JSON
+//!   representation of resources returned by the REST API.
+//!
+//! The generated code depends on the `aptos-sdk`, `aptos-rest-client`, `bcs`, `serde` and
+//! `serde_json` crates.
+
+use heck::SnakeCase;
+use move_deps::{
+    move_binary_format::{
+        file_format::{Ability, Visibility},
+        normalized::{Function, Module, Struct, Type},
+        CompiledModule,
+    },
+    move_core_types::{account_address::AccountAddress, identifier::Identifier},
+};
+use serde_generate::indent::{IndentConfig, IndentedWriter};
+use std::{
+    collections::BTreeSet,
+    io::{Result, Write},
+};
+
+const ACCOUNT_ADDRESS: &str = "::aptos_sdk::move_types::account_address::AccountAddress";
+const IDENTIFIER: &str = "::aptos_sdk::move_types::identifier::Identifier";
+const MODULE_ID: &str = "::aptos_sdk::move_types::language_storage::ModuleId";
+const TYPE_TAG: &str = "::aptos_sdk::move_types::language_storage::TypeTag";
+const SCRIPT_FUNCTION: &str = "::aptos_sdk::types::transaction::ScriptFunction";
+const TRANSACTION_PAYLOAD: &str = "::aptos_sdk::types::transaction::TransactionPayload";
+const API_TYPES: &str = "::aptos_rest_client::aptos_api_types";
+
+/// Output typed Rust bindings for the given modules of a package.
+pub fn output(out: &mut dyn Write, modules: &[CompiledModule]) -> Result<()> {
+    let modules: Vec<_> = modules.iter().map(Module::new).collect();
+    let mut emitter = BindingsEmitter {
+        out: IndentedWriter::new(out, IndentConfig::Space(4)),
+        package_modules: modules
+            .iter()
+            .map(|module| (module.address, module.name.clone()))
+            .collect(),
+    };
+
+    emitter.output_preamble()?;
+    for module in &modules {
+        emitter.output_module(module)?;
+    }
+    Ok(())
+}
+
+/// Shared state for the bindings generator.
+struct BindingsEmitter<T> {
+    /// Writer.
+    out: IndentedWriter<T>,
+    /// Modules of the package, whose struct types can be referred to.
+    package_modules: BTreeSet<(AccountAddress, Identifier)>,
+}
+
+impl<T> BindingsEmitter<T>
+where
+    T: Write,
+{
+    fn output_preamble(&mut self) -> Result<()> {
+        writeln!(
+            self.out,
+            r#"// This file was generated by `aptos move generate-bindings`. Do not modify!
+//
+// Typed bindings for the script functions and struct types of a Move package."#
+        )
+    }
+
+    fn output_module(&mut self, module: &Module) -> Result<()> {
+        writeln!(
+            self.out,
+            "\n/// Bindings for `{}::{}`",
+            module.address.to_hex_literal(),
+            module.name
+        )?;
+        writeln!(self.out, "pub mod {} {{", module_name(&module.name))?;
+        self.out.indent();
+        // Paths are fully qualified, as the names of the module's items may be any Move name
+        writeln!(self.out, "#![allow(clippy::too_many_arguments, dead_code)]")?;
+
+        for (name, function) in &module.exposed_functions {
+            if function.visibility == Visibility::Script {
+                self.output_script_function(module, name, function)?;
+            }
+        }
+        for (name, struct_) in &module.structs {
+            self.output_struct(module, name, struct_)?;
+        }
+
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_script_function(
+        &mut self,
+        module: &Module,
+        name: &Identifier,
+        function: &Function,
+    ) -> Result<()> {
+        // The signers of the transaction are passed implicitly
+        let params: Vec<_> = function
+            .parameters
+            .iter()
+            .skip_while(|param| is_signer(param))
+            .collect();
+        let arg_types = match params
+            .iter()
+            .map(|param| argument_type(param))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(arg_types) => arg_types,
+            None => {
+                return writeln!(
+                    self.out,
+                    "\n// `{}` is skipped, as some of its arguments can't be passed to a script function",
+                    name
+                );
+            }
+        };
+
+        let type_args: Vec<_> = (0..function.type_parameters.len())
+            .map(|index| format!("type_arg{}", index))
+            .collect();
+        let args: Vec<_> = (0..arg_types.len())
+            .map(|index| format!("arg{}", index))
+            .collect();
+        let signature: Vec<_> = type_args
+            .iter()
+            .map(|type_arg| format!("{}: {}", type_arg, TYPE_TAG))
+            .chain(
+                args.iter()
+                    .zip(arg_types.iter())
+                    .map(|(arg, arg_type)| format!("{}: {}", arg, arg_type)),
+            )
+            .collect();
+
+        writeln!(
+            self.out,
+            "\n/// Calls `{}::{}::{}`",
+            module.address.to_hex_literal(),
+            module.name,
+            name
+        )?;
+        writeln!(
+            self.out,
+            "pub fn {}({}) -> {} {{",
+            rust_identifier(&name.as_str().to_snake_case()),
+            signature.join(", "),
+            TRANSACTION_PAYLOAD
+        )?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            "{}::ScriptFunction({}::new(",
+            TRANSACTION_PAYLOAD, SCRIPT_FUNCTION
+        )?;
+        self.out.indent();
+        writeln!(self.out, "{}::new(", MODULE_ID)?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            "{}::from_hex_literal(\"{}\").unwrap(),",
+            ACCOUNT_ADDRESS,
+            module.address.to_hex_literal()
+        )?;
+        writeln!(
+            self.out,
+            "{}::new(\"{}\").unwrap(),",
+            IDENTIFIER, module.name
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "),")?;
+        writeln!(self.out, "{}::new(\"{}\").unwrap(),", IDENTIFIER, name)?;
+        writeln!(self.out, "::std::vec![{}],", type_args.join(", "))?;
+        writeln!(self.out, "::std::vec![")?;
+        self.out.indent();
+        for arg in &args {
+            writeln!(self.out, "::bcs::to_bytes(&{}).unwrap(),", arg)?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "],")?;
+        self.out.unindent();
+        writeln!(self.out, "))")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_struct(
+        &mut self,
+        module: &Module,
+        name: &Identifier,
+        struct_: &Struct,
+    ) -> Result<()> {
+        let is_resource = struct_.abilities.has_ability(Ability::Key);
+        writeln!(
+            self.out,
+            "\n/// The `{}::{}::{}` {}",
+            module.address.to_hex_literal(),
+            module.name,
+            name,
+            if is_resource { "resource" } else { "struct" }
+        )?;
+        if !struct_.type_parameters.is_empty() {
+            writeln!(
+                self.out,
+                "///\n/// Fields depending on the type parameters are decoded as JSON values"
+            )?;
+        }
+        writeln!(
+            self.out,
+            "#[derive(Clone, Debug, ::serde::Deserialize, ::serde::Serialize)]"
+        )?;
+        writeln!(self.out, "pub struct {} {{", name)?;
+        self.out.indent();
+        for field in &struct_.fields {
+            let field_name = rust_identifier(field.name.as_str());
+            if field_name.trim_start_matches("r#") != field.name.as_str() {
+                writeln!(self.out, "#[serde(rename = \"{}\")]", field.name)?;
+            }
+            writeln!(
+                self.out,
+                "pub {}: {},",
+                field_name,
+                self.field_type(&field.type_)
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+
+        if is_resource && struct_.type_parameters.is_empty() {
+            writeln!(self.out, "\nimpl {} {{", name)?;
+            self.out.indent();
+            writeln!(
+                self.out,
+                "/// Type of the resource, to fetch it from an account\npub const RESOURCE_TYPE: &str = \"{}::{}::{}\";",
+                module.address.to_hex_literal(),
+                module.name,
+                name
+            )?;
+            self.out.unindent();
+            writeln!(self.out, "}}")?;
+        }
+        Ok(())
+    }
+
+    /// Rust type of a struct field, as represented in JSON by the REST API
+    fn field_type(&self, type_: &Type) -> String {
+        match type_ {
+            Type::Bool => "bool".to_string(),
+            Type::U8 => "u8".to_string(),
+            Type::U64 => format!("{}::U64", API_TYPES),
+            Type::U128 => format!("{}::U128", API_TYPES),
+            Type::Address | Type::Signer => format!("{}::Address", API_TYPES),
+            Type::Vector(items) => match items.as_ref() {
+                Type::U8 => format!("{}::HexEncodedBytes", API_TYPES),
+                items => format!("::std::vec::Vec<{}>", self.field_type(items)),
+            },
+            Type::Struct {
+                address,
+                module,
+                name,
+                ..
+            } if self.package_modules.contains(&(*address, module.clone())) => {
+                format!("super::{}::{}", module_name(module), name)
+            }
+            _ => "::serde_json::Value".to_string(),
+        }
+    }
+}
+
+fn is_signer(type_: &Type) -> bool {
+    match type_ {
+        Type::Signer => true,
+        Type::Reference(to) => matches!(to.as_ref(), Type::Signer),
+        _ => false,
+    }
+}
+
+/// Rust type of a script function argument, if it can be passed to a script function
+fn argument_type(type_: &Type) -> Option<String> {
+    Some(match type_ {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::U128 => "u128".to_string(),
+        Type::Address => ACCOUNT_ADDRESS.to_string(),
+        Type::Vector(items) => format!("::std::vec::Vec<{}>", argument_type(items)?),
+        // Encoded like a vector of bytes
+        Type::Struct {
+            address,
+            module,
+            name,
+            type_arguments,
+        } if *address == AccountAddress::ONE
+            && module.as_str() == "ASCII"
+            && name.as_str() == "String"
+            && type_arguments.is_empty() =>
+        {
+            "::std::string::String".to_string()
+        }
+        _ => return None,
+    })
+}
+
+fn module_name(name: &Identifier) -> String {
+    rust_identifier(&name.as_str().to_snake_case())
+}
+
+/// Escapes Rust keywords, which are valid Move identifiers. The keywords which can't be raw
+/// identifiers are suffixed with `_` instead
+fn rust_identifier(name: &str) -> String {
+    const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "super", "Self"];
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+        "where", "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
+        "try", "typeof", "unsized", "virtual", "yield",
+    ];
+    if NON_RAW_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
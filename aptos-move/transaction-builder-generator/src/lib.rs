@@ -5,6 +5,8 @@ use aptos_types::transaction::ScriptABI;
 use serde_generate::CustomCode;
 use std::{ffi::OsStr, fs, io::Read, path::Path};
 
+/// Support for code-generation of typed Rust bindings for a published Move package.
+pub mod bindings;
 /// Support for code-generation in C++17.
 pub mod cpp;
 /// Support for code-generation in C#
@@ -3,6 +3,10 @@
 
 use aptos_types::transaction::ScriptABI;
 use cached_framework_packages::abis;
+use move_deps::{
+    move_binary_format::{access::ModuleAccess, CompiledModule},
+    move_core_types::identifier::Identifier,
+};
 use serde_generate as serdegen;
 use serde_generate::SourceInstaller as _;
 use serde_reflection::Registry;
@@ -648,3 +652,129 @@ fn test_that_golang_code_compiles_and_demo_runs() {
     );
     assert!(output.status.success());
 }
+
+fn get_rust_bindings(modules: &[CompiledModule]) -> String {
+    let mut out = Vec::new();
+    buildgen::bindings::output(&mut out, modules).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+/// The `Coin` module, with some of its names changed to collide with Rust keywords and the names
+/// the generated code refers to
+fn get_module_with_colliding_names() -> CompiledModule {
+    let mut module = cached_framework_packages::modules()
+        .iter()
+        .find(|module| module.self_id().name().as_str() == "Coin")
+        .unwrap()
+        .clone();
+    for identifier in module.identifiers.iter_mut() {
+        let name = match identifier.as_str() {
+            // Script functions
+            "transfer" => "module_id",
+            "register" => "self",
+            // Structs
+            "DepositEvent" => "TransactionPayload",
+            "WithdrawEvent" => "String",
+            "CoinEvents" => "U64",
+            // Fields
+            "amount" => "crate",
+            "value" => "super",
+            "decimals" => "Self",
+            _ => continue,
+        };
+        *identifier = Identifier::new(name).unwrap();
+    }
+    module
+}
+
+#[test]
+fn test_rust_bindings_generation() {
+    let bindings = get_rust_bindings(cached_framework_packages::modules());
+
+    assert!(bindings.contains("pub mod coin {"));
+    assert!(bindings.contains(
+        "pub fn transfer(type_arg0: ::aptos_sdk::move_types::language_storage::TypeTag, arg0: ::aptos_sdk::move_types::account_address::AccountAddress, arg1: u64) -> ::aptos_sdk::types::transaction::TransactionPayload {"
+    ));
+    assert!(bindings.contains("pub coin: super::coin::Coin,"));
+    assert!(bindings.contains(
+        "pub authentication_key: ::aptos_rest_client::aptos_api_types::HexEncodedBytes,"
+    ));
+    assert!(bindings.contains("pub sequence_number: ::aptos_rest_client::aptos_api_types::U64,"));
+    assert!(bindings.contains("pub const RESOURCE_TYPE: &str = \"0x1::Account::Account\";"));
+}
+
+#[test]
+fn test_rust_bindings_generation_with_colliding_names() {
+    let bindings = get_rust_bindings(&[get_module_with_colliding_names()]);
+
+    assert!(bindings.contains(
+        "pub fn module_id(type_arg0: ::aptos_sdk::move_types::language_storage::TypeTag, arg0: ::aptos_sdk::move_types::account_address::AccountAddress, arg1: u64) -> ::aptos_sdk::types::transaction::TransactionPayload {"
+    ));
+    assert!(bindings.contains(
+        "pub fn self_(type_arg0: ::aptos_sdk::move_types::language_storage::TypeTag) -> ::aptos_sdk::types::transaction::TransactionPayload {"
+    ));
+    assert!(bindings.contains("Identifier::new(\"self\").unwrap(),"));
+    assert!(bindings.contains("pub struct TransactionPayload {"));
+    assert!(bindings.contains("pub struct String {"));
+    assert!(bindings.contains("pub struct U64 {"));
+    assert!(bindings.contains(
+        "#[serde(rename = \"crate\")]\n        pub crate_: ::aptos_rest_client::aptos_api_types::U64,"
+    ));
+    assert!(bindings.contains(
+        "#[serde(rename = \"super\")]\n        pub super_: ::aptos_rest_client::aptos_api_types::U64,"
+    ));
+    assert!(bindings.contains(
+        "#[serde(rename = \"Self\")]\n        pub Self_: ::aptos_rest_client::aptos_api_types::U64,"
+    ));
+    assert!(!bindings.contains("r#crate"));
+    assert!(!bindings.contains("r#self"));
+    assert!(!bindings.contains("r#super"));
+    assert!(!bindings.contains("r#Self"));
+}
+
+#[test]
+#[ignore]
+fn test_that_rust_bindings_compile() {
+    let dir = tempdir().unwrap();
+    let root = std::env::current_dir().unwrap().join("../..");
+
+    let mut cargo = std::fs::File::create(dir.path().join("Cargo.toml")).unwrap();
+    write!(
+        cargo,
+        r#"[package]
+name = "framework-bindings"
+version = "0.1.0"
+edition = "2018"
+
+[dependencies]
+aptos-rest-client = {{ path = "{}" }}
+aptos-sdk = {{ path = "{}" }}
+bcs = "0.1.3"
+serde = {{ version = "1.0.137", features = ["derive"] }}
+serde_json = "1.0.81"
+"#,
+        root.join("crates/aptos-rest-client").display(),
+        root.join("sdk").display(),
+    )
+    .unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    std::fs::write(
+        dir.path().join("src/lib.rs"),
+        format!(
+            "pub mod framework {{\n{}}}\n\npub mod colliding_names {{\n{}}}\n",
+            get_rust_bindings(cached_framework_packages::modules()),
+            get_rust_bindings(&[get_module_with_colliding_names()]),
+        ),
+    )
+    .unwrap();
+
+    // Use a stable `target` dir to avoid downloading and recompiling crates everytime.
+    let status = Command::new("cargo")
+        .current_dir(dir.path())
+        .arg("build")
+        .arg("--target-dir")
+        .arg(root.join("target"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
@@ -24,8 +24,9 @@ use aptos_telemetry::{
 };
 use aptos_time_service::TimeService;
 use aptos_types::{
-    account_config::aptos_root_address, account_view::AccountView, chain_id::ChainId,
-    move_resource::MoveStorage, on_chain_config::ON_CHAIN_CONFIG_REGISTRY, waypoint::Waypoint,
+    account_config::aptos_root_address, account_view::AccountView,
+    block_metadata::new_block_event_key, chain_id::ChainId, move_resource::MoveStorage,
+    on_chain_config::ON_CHAIN_CONFIG_REGISTRY, waypoint::Waypoint,
 };
 use aptos_vm::AptosVM;
 use aptosdb::AptosDB;
@@ -52,7 +53,7 @@ use std::{
     collections::{HashMap, HashSet},
     io::Write,
     net::ToSocketAddrs,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -133,67 +134,14 @@ pub fn load_test_environment<R>(
     R: ::rand::RngCore + ::rand::CryptoRng,
 {
     let config_temp_path = aptos_temppath::TempPath::new();
+    let config_path = config_path.unwrap_or_else(|| config_temp_path.as_ref().to_path_buf());
 
-    let (try_load, config_path) = if let Some(config_path) = config_path {
-        (
-            config_path.join("0").join("node.yaml").exists(),
-            config_path,
-        )
-    } else {
-        (false, config_temp_path.as_ref().to_path_buf())
-    };
-
-    std::fs::DirBuilder::new()
-        .recursive(true)
-        .create(&config_path)
-        .unwrap();
-
+    let config =
+        create_single_node_test_config(&config_path, random_ports, lazy, genesis_modules, rng)
+            .expect("Unable to create single validator testnet config");
     let config_path = config_path.canonicalize().unwrap();
-
-    let validator_config_path = config_path.join("0").join("node.yaml");
     let aptos_root_key_path = config_path.join("mint.key");
 
-    let config = if try_load {
-        NodeConfig::load(&validator_config_path).expect("Unable to load config:")
-    } else {
-        // Build a single validator network
-        let mut maybe_config = PathBuf::from(&config_path);
-        maybe_config.push("validator_node_template.yaml");
-        let mut template = NodeConfig::load_config(maybe_config)
-            .unwrap_or_else(|_| NodeConfig::default_for_validator());
-
-        // enable REST and JSON-RPC API
-        template.api.address = format!("0.0.0.0:{}", template.api.address.port())
-            .parse()
-            .unwrap();
-        if lazy {
-            template.consensus.mempool_poll_count = u64::MAX;
-        }
-
-        let builder = aptos_genesis_tool::validator_builder::ValidatorBuilder::new(
-            &config_path,
-            genesis_modules,
-        )
-        .template(template)
-        .randomize_first_validator_ports(random_ports);
-
-        let (root_keys, _genesis, genesis_waypoint, validators) = builder.build(rng).unwrap();
-
-        let serialized_keys = bcs::to_bytes(&root_keys.root_key).unwrap();
-        let mut key_file = std::fs::File::create(&aptos_root_key_path).unwrap();
-        key_file.write_all(&serialized_keys).unwrap();
-
-        // Build a waypoint file so that clients / docker can grab it easily
-        let waypoint_file_path = config_path.join("waypoint.txt");
-        std::io::Write::write_all(
-            &mut std::fs::File::create(&waypoint_file_path).unwrap(),
-            genesis_waypoint.to_string().as_bytes(),
-        )
-        .unwrap();
-
-        validators[0].config.clone()
-    };
-
     // Prepare log file since we cannot automatically route logs to stderr
     let log_file = config_path.join("validator.log");
 
@@ -217,6 +165,58 @@ pub fn load_test_environment<R>(
     start(&config, Some(log_file))
 }
 
+/// Loads the config of the single validator testnet in `config_path`, or creates the testnet in
+/// `config_path` if there is none yet: the validator config and storage in `config_path/0`, the
+/// aptos root key in `config_path/mint.key` and the genesis waypoint in
+/// `config_path/waypoint.txt`.
+pub fn create_single_node_test_config<R>(
+    config_path: &Path,
+    random_ports: bool,
+    lazy: bool,
+    genesis_modules: Vec<Vec<u8>>,
+    rng: R,
+) -> anyhow::Result<NodeConfig>
+where
+    R: ::rand::RngCore + ::rand::CryptoRng,
+{
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .create(config_path)?;
+    let config_path = config_path.canonicalize()?;
+
+    let validator_config_path = config_path.join("0").join("node.yaml");
+    if validator_config_path.exists() {
+        return Ok(NodeConfig::load(&validator_config_path)?);
+    }
+
+    // Build a single validator network
+    let mut template = NodeConfig::load_config(config_path.join("validator_node_template.yaml"))
+        .unwrap_or_else(|_| NodeConfig::default_for_validator());
+
+    // enable REST and JSON-RPC API
+    template.api.address = format!("0.0.0.0:{}", template.api.address.port()).parse()?;
+    if lazy {
+        template.consensus.mempool_poll_count = u64::MAX;
+    }
+
+    let builder =
+        aptos_genesis_tool::validator_builder::ValidatorBuilder::new(&config_path, genesis_modules)
+            .template(template)
+            .randomize_first_validator_ports(random_ports);
+
+    let (root_keys, _genesis, genesis_waypoint, validators) = builder.build(rng)?;
+
+    let serialized_keys = bcs::to_bytes(&root_keys.root_key)?;
+    let mut key_file = std::fs::File::create(config_path.join("mint.key"))?;
+    key_file.write_all(&serialized_keys)?;
+
+    // Build a waypoint file so that clients / docker can grab it easily
+    let mut waypoint_file = std::fs::File::create(config_path.join("waypoint.txt"))?;
+    waypoint_file.write_all(genesis_waypoint.to_string().as_bytes())?;
+
+    Ok(validators[0].config.clone())
+}
+
 // Fetch chain ID from on-chain resource
 fn fetch_chain_id(db: &DbReaderWriter) -> ChainId {
     let db_state_view = db
@@ -542,6 +542,12 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         None
     };
 
+    // Create an API subscription for new blocks, so that clients streaming transactions are
+    // notified of new commits.
+    let api_commit_subscription = event_subscription_service
+        .subscribe_to_events(vec![new_block_event_key()])
+        .unwrap();
+
     // Gather all network configs into a single vector.
     let mut network_configs: Vec<&NetworkConfig> = node_config.full_node_networks.iter().collect();
     if let Some(network_config) = node_config.validator_network.as_ref() {
@@ -661,7 +667,14 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
 
     let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
 
-    let api_runtime = bootstrap_api(node_config, chain_id, aptos_db, mp_client_sender).unwrap();
+    let api_runtime = bootstrap_api(
+        node_config,
+        chain_id,
+        aptos_db,
+        mp_client_sender,
+        api_commit_subscription,
+    )
+    .unwrap();
 
     let mut consensus_runtime = None;
     let (consensus_to_mempool_sender, consensus_requests) = channel(INTRA_NODE_CHANNEL_BUFFER_SIZE);
@@ -18,11 +18,27 @@ pub struct ApiConfig {
     // optional for compatible with old configuration
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content_length_limit: Option<u64>,
+    // maximum gas a single view function call may consume
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_view_function_gas: Option<u64>,
+    // number of transactions expected to fit in the next block, used by the gas price estimation
+    // to find the mempool transaction a new transaction has to outbid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_estimation_block_size: Option<u64>,
+    // maximum number of transactions accepted by a single batch submission request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_submit_transaction_batch_size: Option<usize>,
+    // per client request rate limits, requests are not rate limited if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<ApiRateLimitConfig>,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
 pub const DEFAULT_PORT: u16 = 8080;
 pub const DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT: u64 = 4 * 1024 * 1024; // 4mb
+pub const DEFAULT_MAX_VIEW_FUNCTION_GAS: u64 = 1_000_000;
+pub const DEFAULT_GAS_ESTIMATION_BLOCK_SIZE: u64 = 3000;
+pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 100;
 
 fn default_enabled() -> bool {
     true
@@ -38,6 +54,10 @@ impl Default for ApiConfig {
             tls_cert_path: None,
             tls_key_path: None,
             content_length_limit: None,
+            max_view_function_gas: None,
+            gas_estimation_block_size: None,
+            max_submit_transaction_batch_size: None,
+            rate_limit: None,
         }
     }
 }
@@ -53,4 +73,60 @@ impl ApiConfig {
             None => DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT,
         }
     }
+
+    pub fn max_view_function_gas(&self) -> u64 {
+        self.max_view_function_gas
+            .unwrap_or(DEFAULT_MAX_VIEW_FUNCTION_GAS)
+    }
+
+    pub fn gas_estimation_block_size(&self) -> u64 {
+        self.gas_estimation_block_size
+            .unwrap_or(DEFAULT_GAS_ESTIMATION_BLOCK_SIZE)
+    }
+
+    pub fn max_submit_transaction_batch_size(&self) -> usize {
+        self.max_submit_transaction_batch_size
+            .unwrap_or(DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE)
+    }
+}
+
+/// Token bucket rate limits of the requests of every client. Clients are identified by the API key
+/// they present in the `X-Aptos-Api-Key` request header if it is one of `api_keys`, and by their IP
+/// address otherwise.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiRateLimitConfig {
+    /// Number of requests/s allowed for an IP address
+    pub ip_request_rate: usize,
+    /// Maximum burst of requests for an IP address
+    pub ip_request_bucket_size: usize,
+    /// Number of requests/s allowed for an API key
+    pub api_key_request_rate: usize,
+    /// Maximum burst of requests for an API key
+    pub api_key_request_bucket_size: usize,
+    /// API keys granted the API key limits, each with its own quota
+    pub api_keys: Vec<String>,
+    /// Initial amount of tokens initially in the buckets
+    pub initial_bucket_fill_percentage: u8,
+    /// Allow for disabling the throttles
+    pub enabled: bool,
+}
+
+pub const DEFAULT_IP_REQUEST_RATE: usize = 100;
+pub const DEFAULT_IP_REQUEST_BUCKET_SIZE: usize = 200;
+pub const DEFAULT_API_KEY_REQUEST_RATE: usize = 1000;
+pub const DEFAULT_API_KEY_REQUEST_BUCKET_SIZE: usize = 2000;
+
+impl Default for ApiRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            ip_request_rate: DEFAULT_IP_REQUEST_RATE,
+            ip_request_bucket_size: DEFAULT_IP_REQUEST_BUCKET_SIZE,
+            api_key_request_rate: DEFAULT_API_KEY_REQUEST_RATE,
+            api_key_request_bucket_size: DEFAULT_API_KEY_REQUEST_BUCKET_SIZE,
+            api_keys: vec![],
+            initial_bucket_fill_percentage: 100,
+            enabled: true,
+        }
+    }
 }
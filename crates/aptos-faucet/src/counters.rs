@@ -0,0 +1,26 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_metrics::{register_int_gauge_vec, IntGaugeVec};
+use once_cell::sync::Lazy;
+
+/// Number of mint requests being processed by each funding account, including the ones waiting
+/// for its outstanding transactions to be committed.
+pub static FUNDING_ACCOUNT_QUEUE_DEPTH: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_faucet_funding_account_queue_depth",
+        "Number of mint requests being processed, grouped by funding account",
+        &["address"]
+    )
+    .unwrap()
+});
+
+/// Balance of each funding account, as of its last check for a refill.
+pub static FUNDING_ACCOUNT_BALANCE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_faucet_funding_account_balance",
+        "Balance of the funding accounts, grouped by funding account",
+        &["address"]
+    )
+    .unwrap()
+});
@@ -0,0 +1,304 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Funding accounts, which the mint requests are distributed across.
+//!
+//! Transactions from a single account are ordered by sequence number, which caps the throughput
+//! of the faucet account.  Instead, the faucet account can provision funding accounts, which serve
+//! the mint requests by transferring their own coins, and which the faucet account refills when
+//! their balance runs low.  The keys of the funding accounts are derived from a secret seed, so
+//! that restarts reuse the accounts already provisioned.
+
+use crate::{
+    counters::{FUNDING_ACCOUNT_BALANCE, FUNDING_ACCOUNT_QUEUE_DEPTH},
+    Service,
+};
+use anyhow::Result;
+use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey};
+use aptos_logger::{info, warn};
+use aptos_sdk::{
+    transaction_builder::aptos_stdlib,
+    types::{
+        account_address::AccountAddress,
+        transaction::{authenticator::AuthenticationKey, TransactionPayload},
+        LocalAccount,
+    },
+};
+use futures::lock::Mutex;
+use std::{
+    convert::TryFrom,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+/// An account sending the transactions of mint requests.
+pub struct FundingAccount {
+    account: Arc<Mutex<LocalAccount>>,
+    address: AccountAddress,
+    /// Whether the account mints the coins, rather than transferring its own.
+    mints: bool,
+    /// Number of requests being processed by the account.
+    requests: AtomicUsize,
+}
+
+impl FundingAccount {
+    fn new(account: Arc<Mutex<LocalAccount>>, address: AccountAddress, mints: bool) -> Self {
+        Self {
+            account,
+            address,
+            mints,
+            requests: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn account(&self) -> &Mutex<LocalAccount> {
+        &self.account
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    /// Payload of the transaction sending `amount` coins to `receiver` from this account.
+    pub fn fund_payload(&self, receiver: AccountAddress, amount: u64) -> TransactionPayload {
+        if self.mints {
+            aptos_stdlib::encode_test_coin_mint(receiver, amount)
+        } else {
+            aptos_stdlib::encode_test_coin_transfer(receiver, amount)
+        }
+    }
+}
+
+/// The funding accounts, which are only the faucet account until others are provisioned.
+pub struct FundingAccounts {
+    accounts: RwLock<Vec<Arc<FundingAccount>>>,
+    /// Account to start looking from for the next request, so that idle accounts take turns.
+    next: AtomicUsize,
+}
+
+impl FundingAccounts {
+    pub(crate) fn new(faucet_account: Arc<Mutex<LocalAccount>>, address: AccountAddress) -> Self {
+        Self {
+            accounts: RwLock::new(vec![Arc::new(FundingAccount::new(
+                faucet_account,
+                address,
+                true,
+            ))]),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Takes the account with the fewest requests in progress, for the duration of a request.
+    pub fn acquire(&self) -> FundingAccountGuard {
+        let accounts = self.accounts.read().unwrap();
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % accounts.len();
+        let account = accounts
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(accounts.len())
+            .min_by_key(|account| account.requests.load(Ordering::Relaxed))
+            .expect("There is always a funding account")
+            .clone();
+        drop(accounts);
+        account.requests.fetch_add(1, Ordering::Relaxed);
+        FUNDING_ACCOUNT_QUEUE_DEPTH
+            .with_label_values(&[&account.address.to_hex_literal()])
+            .inc();
+        FundingAccountGuard(account)
+    }
+
+    pub fn accounts(&self) -> Vec<Arc<FundingAccount>> {
+        self.accounts.read().unwrap().clone()
+    }
+
+    fn replace(&self, accounts: Vec<Arc<FundingAccount>>) {
+        *self.accounts.write().unwrap() = accounts;
+    }
+}
+
+/// A funding account taken for a request, which is released when dropped.
+pub struct FundingAccountGuard(Arc<FundingAccount>);
+
+impl Deref for FundingAccountGuard {
+    type Target = FundingAccount;
+
+    fn deref(&self) -> &FundingAccount {
+        &self.0
+    }
+}
+
+impl Drop for FundingAccountGuard {
+    fn drop(&mut self) {
+        self.0.requests.fetch_sub(1, Ordering::Relaxed);
+        FUNDING_ACCOUNT_QUEUE_DEPTH
+            .with_label_values(&[&self.0.address.to_hex_literal()])
+            .dec();
+    }
+}
+
+const FUNDING_KEY_DOMAIN: &[u8] = b"APTOS::FAUCET::FUNDING_ACCOUNT";
+
+/// Derives the key of the funding account `index` from `seed`.
+fn funding_account_key(seed: &[u8], index: usize) -> Ed25519PrivateKey {
+    let key_bytes =
+        HashValue::sha3_256_of(&[FUNDING_KEY_DOMAIN, seed, &(index as u64).to_le_bytes()].concat());
+    Ed25519PrivateKey::try_from(key_bytes.as_ref())
+        .expect("Any 32 bytes are a valid Ed25519 private key")
+}
+
+/// Provisions `count` funding accounts, which then serve the mint requests instead of the faucet
+/// account.  Their keys are derived from `seed`, which must be kept secret, e.g. the bytes of the
+/// mint key.  Accounts which don't exist yet are created with `balance` coins, and the existing
+/// ones are reused as is, the refills topping them up.
+pub async fn provision(service: &Service, seed: &[u8], count: usize, balance: u64) -> Result<()> {
+    if count == 0 {
+        return Ok(());
+    }
+
+    let accounts = futures::future::try_join_all((0..count).map(|index| async move {
+        let key = funding_account_key(seed, index);
+        let address = AuthenticationKey::ed25519(&key.public_key()).derived_address();
+        let sequence_number = match service.client.get_account(address).await {
+            Ok(account) => account.into_inner().sequence_number,
+            // Other errors, e.g. timeouts, don't tell whether the account exists, and an existing
+            // account must be reused with its sequence number
+            Err(err) if is_not_found(&err) => {
+                mint_from_faucet_account(service, address, true, balance).await?;
+                0
+            }
+            Err(err) => return Err(err),
+        };
+        Result::<_>::Ok(LocalAccount::new(address, key, sequence_number))
+    }))
+    .await?;
+
+    info!(
+        "[faucet]: provisioned {} funding accounts with a balance of {}",
+        count, balance
+    );
+    service.funding_accounts.replace(
+        accounts
+            .into_iter()
+            .map(|account| {
+                let address = account.address();
+                Arc::new(FundingAccount::new(
+                    Arc::new(Mutex::new(account)),
+                    address,
+                    false,
+                ))
+            })
+            .collect(),
+    );
+    Ok(())
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<aptos_rest_client::error::Error>()
+        .map_or(false, |err| err.is_not_found())
+}
+
+/// Checks the balance of the funding accounts every `interval`, refilling the ones below
+/// `threshold` with `amount` coins minted by the faucet account.
+pub async fn refill(service: Arc<Service>, threshold: u64, amount: u64, interval: Duration) {
+    loop {
+        for account in service.funding_accounts.accounts() {
+            if let Err(err) = refill_account(&service, &account, threshold, amount).await {
+                warn!(
+                    "[faucet]: unable to refill funding account {}: {}",
+                    account.address(),
+                    err
+                );
+            }
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn refill_account(
+    service: &Service,
+    account: &FundingAccount,
+    threshold: u64,
+    amount: u64,
+) -> Result<()> {
+    if account.mints {
+        return Ok(());
+    }
+
+    let balance = service
+        .client
+        .get_account_balance(account.address())
+        .await?
+        .into_inner()
+        .get();
+    FUNDING_ACCOUNT_BALANCE
+        .with_label_values(&[&account.address().to_hex_literal()])
+        .set(balance as i64);
+
+    if balance < threshold {
+        info!(
+            "[faucet]: refilling funding account {} with a balance of {}",
+            account.address(),
+            balance
+        );
+        mint_from_faucet_account(service, account.address(), false, amount).await?;
+    }
+    Ok(())
+}
+
+/// Mints coins to `receiver` from the faucet account, creating the account first if `create` is
+/// set, and waits for the transactions to be committed.
+async fn mint_from_faucet_account(
+    service: &Service,
+    receiver: AccountAddress,
+    create: bool,
+    amount: u64,
+) -> Result<()> {
+    let (faucet_seq, txns) = {
+        let mut faucet_account = service.faucet_account.lock().await;
+        let faucet_seq = service
+            .client
+            .get_account(faucet_account.address())
+            .await?
+            .inner()
+            .sequence_number;
+        if faucet_seq > faucet_account.sequence_number() {
+            *faucet_account.sequence_number_mut() = faucet_seq;
+        }
+
+        let mut txns = vec![];
+        if create {
+            txns.push(
+                faucet_account.sign_with_transaction_builder(
+                    service
+                        .transaction_factory
+                        .payload(aptos_stdlib::encode_account_create_account(receiver)),
+                ),
+            );
+        }
+        txns.push(
+            faucet_account.sign_with_transaction_builder(
+                service
+                    .transaction_factory
+                    .payload(aptos_stdlib::encode_test_coin_mint(receiver, amount)),
+            ),
+        );
+        (faucet_seq, txns)
+    };
+
+    for txn in &txns {
+        if let Err(err) = service.client.submit(txn).await {
+            // Reset the sequence number to what's on chain, as for mint requests
+            *service.faucet_account.lock().await.sequence_number_mut() = faucet_seq;
+            return Err(err);
+        }
+    }
+    for txn in &txns {
+        service.client.wait_for_signed_transaction(txn).await?;
+    }
+    Ok(())
+}
@@ -26,20 +26,27 @@ use aptos_sdk::{
     transaction_builder::{aptos_stdlib, TransactionFactory},
     types::{chain_id::ChainId, LocalAccount},
 };
+use funding::FundingAccounts;
 use futures::lock::Mutex;
+use limits::Limits;
 use reqwest::StatusCode;
 use std::{convert::Infallible, fmt, sync::Arc};
 use url::Url;
 use warp::{http, Filter, Rejection, Reply};
 
+mod counters;
+pub mod funding;
+pub mod limits;
 pub mod mint;
 
 pub struct Service {
-    pub faucet_account: Mutex<LocalAccount>,
+    pub faucet_account: Arc<Mutex<LocalAccount>>,
+    pub funding_accounts: FundingAccounts,
     transaction_factory: TransactionFactory,
     client: Client,
     endpoint: String,
     maximum_amount: Option<u64>,
+    limits: Option<Limits>,
 }
 
 impl Service {
@@ -50,20 +57,36 @@ impl Service {
         maximum_amount: Option<u64>,
     ) -> Self {
         let client = Client::new(Url::parse(&endpoint).expect("Invalid rest endpoint"));
+        let faucet_address = faucet_account.address();
+        let faucet_account = Arc::new(Mutex::new(faucet_account));
         Service {
-            faucet_account: Mutex::new(faucet_account),
+            funding_accounts: FundingAccounts::new(faucet_account.clone(), faucet_address),
+            faucet_account,
             transaction_factory: TransactionFactory::new(chain_id)
                 .with_gas_unit_price(1)
                 .with_transaction_expiration_time(30),
             client,
             endpoint,
             maximum_amount,
+            limits: None,
         }
     }
 
+    /// Protects the mint API with the given limits.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
     pub fn endpoint(&self) -> &String {
         &self.endpoint
     }
+
+    /// Amount of coins minted for a request of `requested` coins.
+    fn mint_amount(&self, requested: u64) -> u64 {
+        self.maximum_amount
+            .map_or(requested, |maximum_amount| maximum_amount.min(requested))
+    }
 }
 
 pub fn routes(
@@ -141,6 +164,7 @@ pub async fn delegate_mint_account(
     server_url: String,
     chain_id: ChainId,
     maximum_amount: Option<u64>,
+    limits: Option<Limits>,
 ) -> Arc<Service> {
     // Create a new random account, then delegate to it
     let mut delegated_account = LocalAccount::generate(&mut rand::rngs::OsRng);
@@ -160,6 +184,7 @@ pub async fn delegate_mint_account(
             ),
             pub_key: None,
             return_txns: Some(true),
+            pow_nonce: None,
         },
     )
     .await
@@ -207,10 +232,9 @@ pub async fn delegate_mint_account(
         .await
         .unwrap();
 
-    Arc::new(Service::new(
-        server_url,
-        chain_id,
-        delegated_account,
-        maximum_amount,
-    ))
+    let service = Service::new(server_url, chain_id, delegated_account, maximum_amount);
+    Arc::new(match limits {
+        Some(limits) => service.with_limits(limits),
+        None => service,
+    })
 }
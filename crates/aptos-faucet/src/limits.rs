@@ -0,0 +1,438 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protection of the faucet against abuse, e.g. scripts draining it.
+//!
+//! Mint requests can be limited by:
+//! * daily quotas of coins per requesting IP and per receiving address, which are persisted to a
+//!   file so that they survive restarts,
+//! * an allowlist of IPs and addresses exempt from the quotas, and a denylist of IPs and
+//!   addresses whose requests are rejected,
+//! * a shared secret token or a proof of work, one of which requests must then provide.  Requests
+//!   with the token are also exempt from the quotas, and each proof of work is accepted once.
+
+use anyhow::{format_err, Result};
+use aptos_crypto::HashValue;
+use aptos_logger::warn;
+use aptos_sdk::types::account_address::AccountAddress;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use structopt::StructOpt;
+use warp::{Filter, Rejection};
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, Default, StructOpt)]
+pub struct LimitsConfig {
+    /// Maximum amount of coins minted per day for the requests from an IP
+    #[structopt(long)]
+    pub ip_daily_limit: Option<u64>,
+    /// Maximum amount of coins minted per day to an address
+    #[structopt(long)]
+    pub address_daily_limit: Option<u64>,
+    /// File of IPs and addresses exempt from the daily limits, one per line
+    #[structopt(long, parse(from_os_str))]
+    pub allowlist_file: Option<PathBuf>,
+    /// File of IPs and addresses whose requests are rejected, one per line
+    #[structopt(long, parse(from_os_str))]
+    pub denylist_file: Option<PathBuf>,
+    /// Shared secret token, passed by requests as `Authorization: Bearer <token>`.
+    /// Requests must then provide either the token or a proof of work, and requests with the
+    /// token are exempt from the daily limits
+    #[structopt(long)]
+    pub auth_token: Option<String>,
+    /// Number of leading zero bits of the proof of work required from requests.
+    /// See the README for how the proof of work is computed
+    #[structopt(long)]
+    pub pow_difficulty: Option<u8>,
+    /// File the amounts minted today are persisted to, so that the daily limits survive restarts
+    #[structopt(long, parse(from_os_str))]
+    pub accounting_file: Option<PathBuf>,
+    /// Take the requesting IP from the `X-Forwarded-For` header, when the faucet is behind a
+    /// proxy appending it. The last IP of the header is used, as the ones before it are set by
+    /// the client
+    #[structopt(long)]
+    pub trust_forwarded_for: bool,
+}
+
+/// Why a request was rejected by the limits.
+#[derive(Debug)]
+pub enum LimitError {
+    Denied,
+    Unauthorized,
+    ProofOfWorkReused,
+    QuotaExceeded(String),
+}
+
+impl LimitError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            LimitError::Denied => StatusCode::FORBIDDEN,
+            LimitError::Unauthorized | LimitError::ProofOfWorkReused => StatusCode::UNAUTHORIZED,
+            LimitError::QuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitError::Denied => {
+                f.write_str("Requests from this IP or to this address are denied")
+            }
+            LimitError::Unauthorized => {
+                f.write_str("A valid 'Authorization' token or 'pow_nonce' is required")
+            }
+            LimitError::ProofOfWorkReused => {
+                f.write_str("This 'pow_nonce' was already used, a new one is required")
+            }
+            LimitError::QuotaExceeded(quota) => {
+                write!(f, "Daily limit of coins {} exceeded, retry tomorrow", quota)
+            }
+        }
+    }
+}
+
+/// The parts of a mint request the limits depend on.
+#[derive(Debug)]
+pub struct RequestInfo {
+    remote: Option<SocketAddr>,
+    forwarded_for: Option<String>,
+    authorization: Option<String>,
+}
+
+/// Extracts the `RequestInfo` of a request.
+pub fn request_info() -> impl Filter<Extract = (RequestInfo,), Error = Rejection> + Clone {
+    warp::addr::remote()
+        .and(warp::header::optional::<String>("x-forwarded-for"))
+        .and(warp::header::optional::<String>("authorization"))
+        .map(|remote, forwarded_for, authorization| RequestInfo {
+            remote,
+            forwarded_for,
+            authorization,
+        })
+}
+
+/// Coins counted against the daily quotas for a request, which are refunded if it fails.
+#[derive(Debug)]
+pub struct Reservation {
+    day: u64,
+    ip: Option<IpAddr>,
+    address: AccountAddress,
+    amount: u64,
+}
+
+pub struct Limits {
+    config: LimitsConfig,
+    allowlist: Entries,
+    denylist: Entries,
+    accounting: Arc<Mutex<Accounting>>,
+    /// Held while writing the accounting file, so that concurrent writes are not interleaved
+    persist_lock: Arc<Mutex<()>>,
+}
+
+impl Limits {
+    pub fn new(config: LimitsConfig) -> Result<Self> {
+        let allowlist = Entries::load(config.allowlist_file.as_deref())?;
+        let denylist = Entries::load(config.denylist_file.as_deref())?;
+        let accounting = match &config.accounting_file {
+            Some(path) if path.exists() => {
+                serde_json::from_slice(&std::fs::read(path)?).map_err(|err| {
+                    format_err!(
+                        "Unable to parse accounting file {}: {}",
+                        path.display(),
+                        err
+                    )
+                })?
+            }
+            _ => Accounting::default(),
+        };
+        Ok(Self {
+            config,
+            allowlist,
+            denylist,
+            accounting: Arc::new(Mutex::new(accounting)),
+            persist_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Checks whether a request minting `amount` coins to `receiver` is allowed, and counts the
+    /// coins against its quotas unless it's exempt from them. The proof of work of an allowed
+    /// request is spent, even if the request fails later on.
+    pub async fn check(
+        &self,
+        request: &RequestInfo,
+        receiver: AccountAddress,
+        amount: u64,
+        pow_nonce: Option<u64>,
+    ) -> Result<Option<Reservation>, LimitError> {
+        let (result, changed) = self.check_inner(request, receiver, amount, pow_nonce);
+        if changed {
+            self.persist().await;
+        }
+        result
+    }
+
+    /// Does the work of `check`, also telling whether the accounting changed.
+    fn check_inner(
+        &self,
+        request: &RequestInfo,
+        receiver: AccountAddress,
+        amount: u64,
+        pow_nonce: Option<u64>,
+    ) -> (Result<Option<Reservation>, LimitError>, bool) {
+        let ip = self.ip(request);
+        if self.denylist.contains(ip, receiver) {
+            return (Err(LimitError::Denied), false);
+        }
+
+        let day = today();
+        let has_token = match (&self.config.auth_token, &request.authorization) {
+            (Some(token), Some(authorization)) => {
+                authorization.strip_prefix("Bearer ") == Some(token.as_str())
+            }
+            _ => false,
+        };
+        let mut proof_of_work = None;
+        if !has_token {
+            if let (Some(difficulty), Some(nonce)) = (self.config.pow_difficulty, pow_nonce) {
+                // Proofs computed just before midnight are accepted
+                proof_of_work = [day, day - 1]
+                    .iter()
+                    .map(|&day| ProofOfWork {
+                        receiver,
+                        day,
+                        nonce,
+                    })
+                    .find(|proof| proof.verify(difficulty));
+            }
+            let needs_authorization =
+                self.config.auth_token.is_some() || self.config.pow_difficulty.is_some();
+            if needs_authorization && proof_of_work.is_none() {
+                return (Err(LimitError::Unauthorized), false);
+            }
+        }
+
+        let mut accounting = self.accounting.lock().unwrap();
+        accounting.roll(day);
+        if let Some(proof) = &proof_of_work {
+            if accounting.proofs_of_work.contains(proof) {
+                return (Err(LimitError::ProofOfWorkReused), false);
+            }
+        }
+
+        let reservation = if has_token || self.allowlist.contains(ip, receiver) {
+            None
+        } else {
+            if let (Some(limit), Some(ip)) = (self.config.ip_daily_limit, ip) {
+                let minted = accounting.ips.get(&ip).copied().unwrap_or(0);
+                if minted.saturating_add(amount) > limit {
+                    let err = LimitError::QuotaExceeded(format!("for IP {}", ip));
+                    return (Err(err), false);
+                }
+            }
+            if let Some(limit) = self.config.address_daily_limit {
+                let minted = accounting.addresses.get(&receiver).copied().unwrap_or(0);
+                if minted.saturating_add(amount) > limit {
+                    let err = LimitError::QuotaExceeded(format!("to address {}", receiver));
+                    return (Err(err), false);
+                }
+            }
+
+            if let Some(ip) = ip {
+                let minted = accounting.ips.entry(ip).or_insert(0);
+                *minted = minted.saturating_add(amount);
+            }
+            let minted = accounting.addresses.entry(receiver).or_insert(0);
+            *minted = minted.saturating_add(amount);
+            Some(Reservation {
+                day,
+                ip,
+                address: receiver,
+                amount,
+            })
+        };
+
+        let changed = reservation.is_some() || proof_of_work.is_some();
+        if let Some(proof) = proof_of_work {
+            accounting.proofs_of_work.insert(proof);
+        }
+        (Ok(reservation), changed)
+    }
+
+    /// Refunds the coins of a request which failed.
+    pub async fn refund(&self, reservation: Reservation) {
+        {
+            let mut accounting = self.accounting.lock().unwrap();
+            if accounting.day != reservation.day {
+                return;
+            }
+            if let Some(minted) = reservation.ip.and_then(|ip| accounting.ips.get_mut(&ip)) {
+                *minted = minted.saturating_sub(reservation.amount);
+            }
+            if let Some(minted) = accounting.addresses.get_mut(&reservation.address) {
+                *minted = minted.saturating_sub(reservation.amount);
+            }
+        }
+        self.persist().await;
+    }
+
+    fn ip(&self, request: &RequestInfo) -> Option<IpAddr> {
+        if self.config.trust_forwarded_for {
+            // The proxy appends the IP it received the request from to the IPs sent by the
+            // client, which can't be trusted
+            if let Some(ip) = request
+                .forwarded_for
+                .as_ref()
+                .and_then(|forwarded_for| forwarded_for.split(',').next_back())
+                .and_then(|ip| ip.trim().parse().ok())
+            {
+                return Some(ip);
+            }
+        }
+        request.remote.map(|remote| remote.ip())
+    }
+
+    /// Writes the accounting to the accounting file, on a blocking thread. The accounting is
+    /// serialized while holding `persist_lock`, so that the last write is of the latest state.
+    async fn persist(&self) {
+        let path = match &self.config.accounting_file {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        let accounting = self.accounting.clone();
+        let persist_lock = self.persist_lock.clone();
+        let temp_path = path.with_extension("tmp");
+        let rename_path = path.clone();
+        let result = tokio::task::spawn_blocking(move || -> Result<()> {
+            let _guard = persist_lock.lock().unwrap();
+            let bytes = serde_json::to_vec(&*accounting.lock().unwrap())?;
+            // Written to a temporary file first, so that a crash doesn't leave a truncated file
+            std::fs::write(&temp_path, bytes)?;
+            std::fs::rename(&temp_path, &rename_path)?;
+            Ok(())
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+        if let Err(err) = result {
+            warn!(
+                "[faucet]: unable to persist accounting file {}: {}",
+                path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// IPs and addresses of an allowlist or a denylist.
+#[derive(Default)]
+struct Entries {
+    ips: HashSet<IpAddr>,
+    addresses: HashSet<AccountAddress>,
+}
+
+impl Entries {
+    fn load(path: Option<&Path>) -> Result<Self> {
+        let mut entries = Entries::default();
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(entries),
+        };
+
+        for line in std::fs::read_to_string(path)?.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Ok(ip) = line.parse() {
+                entries.ips.insert(ip);
+            } else if let Ok(address) =
+                AccountAddress::from_hex_literal(line).or_else(|_| AccountAddress::from_hex(line))
+            {
+                entries.addresses.insert(address);
+            } else {
+                return Err(format_err!(
+                    "Invalid IP or address '{}' in {}",
+                    line,
+                    path.display()
+                ));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn contains(&self, ip: Option<IpAddr>, address: AccountAddress) -> bool {
+        ip.map_or(false, |ip| self.ips.contains(&ip)) || self.addresses.contains(&address)
+    }
+}
+
+/// Coins minted today per IP and per address, and the proofs of work spent.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Accounting {
+    day: u64,
+    ips: HashMap<IpAddr, u64>,
+    addresses: HashMap<AccountAddress, u64>,
+    /// Proofs of work of today and yesterday, the days of the proofs still accepted
+    #[serde(default)]
+    proofs_of_work: HashSet<ProofOfWork>,
+}
+
+impl Accounting {
+    /// Resets the quotas on a new day, and forgets the proofs of work no longer accepted.
+    fn roll(&mut self, day: u64) {
+        if self.day != day {
+            let proofs_of_work = std::mem::take(&mut self.proofs_of_work)
+                .into_iter()
+                .filter(|proof| proof.day + 1 >= day)
+                .collect();
+            *self = Accounting {
+                day,
+                proofs_of_work,
+                ..Accounting::default()
+            };
+        }
+    }
+}
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+        / SECS_PER_DAY
+}
+
+/// A proof of work for minting to `receiver` on `day`.
+#[derive(Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+struct ProofOfWork {
+    receiver: AccountAddress,
+    day: u64,
+    nonce: u64,
+}
+
+impl ProofOfWork {
+    /// The proof of work is a nonce such that the SHA3-256 hash of
+    /// `<receiver hex literal>:<days since the unix epoch>:<nonce>` starts with `difficulty` zero
+    /// bits.
+    fn verify(&self, difficulty: u8) -> bool {
+        let hash = HashValue::sha3_256_of(
+            format!(
+                "{}:{}:{}",
+                self.receiver.to_hex_literal(),
+                self.day,
+                self.nonce
+            )
+            .as_bytes(),
+        );
+        hash.common_prefix_bits_len(HashValue::zero()) >= difficulty as usize
+    }
+}
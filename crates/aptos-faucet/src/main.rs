@@ -1,15 +1,15 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos::common::types::EncodingType;
 use aptos_config::keys::ConfigKey;
 use aptos_crypto::ed25519::Ed25519PrivateKey;
+use aptos_faucet::limits::{Limits, LimitsConfig};
 use aptos_logger::info;
 use aptos_sdk::types::{
     account_address::AccountAddress, account_config::aptos_root_address, chain_id::ChainId,
     LocalAccount,
 };
-use std::{path::Path, sync::Arc};
+use std::{sync::Arc, time::Duration};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -53,6 +53,25 @@ struct Args {
     pub maximum_amount: Option<u64>,
     #[structopt(long)]
     pub do_not_delegate: bool,
+    /// Number of funding accounts to provision, which serve the mint requests concurrently.
+    /// If 0, the mint requests are served by the mint account
+    #[structopt(long, default_value = "0")]
+    pub funding_accounts: usize,
+    /// Balance the funding accounts are provisioned and refilled with
+    #[structopt(long, default_value = "100000000000")]
+    pub funding_account_balance: u64,
+    /// Balance below which a funding account is refilled
+    #[structopt(long, default_value = "10000000000")]
+    pub refill_threshold: u64,
+    /// Number of seconds between checks of the balance of the funding accounts
+    #[structopt(long, default_value = "30")]
+    pub refill_interval_secs: u64,
+    /// Port to serve the metrics on, e.g. the queue depth and balance of the funding accounts.
+    /// If not present, the metrics aren't served
+    #[structopt(long)]
+    pub metrics_port: Option<u16>,
+    #[structopt(flatten)]
+    pub limits: LimitsConfig,
 }
 
 #[tokio::main]
@@ -74,13 +93,14 @@ async fn main() {
     let key = if let Some(key) = args.mint_key {
         key.private_key()
     } else {
-        EncodingType::BCS
-            .load_key::<Ed25519PrivateKey>("mint key", Path::new(&args.mint_key_file_path))
-            .unwrap()
+        let key_bytes = std::fs::read(&args.mint_key_file_path).expect("Unable to read mint key");
+        bcs::from_bytes::<Ed25519PrivateKey>(&key_bytes).expect("Unable to decode mint key")
     };
 
     let faucet_address: AccountAddress =
         args.mint_account_address.unwrap_or_else(aptos_root_address);
+    // The funding accounts are derived from the mint key, to be reused across restarts
+    let funding_seed = key.to_bytes();
     let faucet_account = LocalAccount::new(faucet_address, key, 0);
 
     // Do not use maximum amount on delegation, this allows the new delegated faucet to
@@ -91,29 +111,54 @@ async fn main() {
         None
     };
 
-    let service = Arc::new(aptos_faucet::Service::new(
+    let limits = Limits::new(args.limits).expect("Unable to load the limits");
+
+    let service = aptos_faucet::Service::new(
         args.server_url.clone(),
         args.chain_id,
         faucet_account,
         maximum_amount,
-    ));
+    );
 
     let actual_service = if args.do_not_delegate {
-        service
+        Arc::new(service.with_limits(limits))
     } else {
         aptos_faucet::delegate_mint_account(
-            service,
+            Arc::new(service),
             args.server_url,
             args.chain_id,
             args.maximum_amount,
+            Some(limits),
         )
         .await
     };
 
+    if args.funding_accounts > 0 {
+        aptos_faucet::funding::provision(
+            &actual_service,
+            &funding_seed,
+            args.funding_accounts,
+            args.funding_account_balance,
+        )
+        .await
+        .expect("Unable to provision the funding accounts");
+        tokio::spawn(aptos_faucet::funding::refill(
+            actual_service.clone(),
+            args.refill_threshold,
+            args.funding_account_balance,
+            Duration::from_secs(args.refill_interval_secs),
+        ));
+    }
+
+    if let Some(metrics_port) = args.metrics_port {
+        aptos_metrics::metric_server::start_server(args.address.clone(), metrics_port, false);
+    }
+
     info!(
-        "[faucet]: running on: {}. Minting from {}",
+        "[faucet]: running on: {}. Minting from {} with {} funding accounts",
         address,
-        actual_service.faucet_account.lock().await.address()
+        actual_service.faucet_account.lock().await.address(),
+        actual_service.funding_accounts.accounts().len()
     );
     warp::serve(aptos_faucet::routes(actual_service))
         .run(address)
@@ -122,9 +167,15 @@ async fn main() {
 
 #[cfg(test)]
 mod tests {
-    use aptos::op::key::GenerateKey;
-    use aptos_crypto::{ed25519::Ed25519PublicKey, hash::HashValue, PrivateKey};
-    use aptos_faucet::{routes, Service};
+    use aptos_crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+        hash::HashValue,
+        PrivateKey, Uniform,
+    };
+    use aptos_faucet::{
+        limits::{Limits, LimitsConfig},
+        routes, Service,
+    };
     use aptos_infallible::RwLock;
     use aptos_rest_client::{
         aptos_api_types::{
@@ -145,6 +196,7 @@ mod tests {
             LocalAccount,
         },
     };
+    use rand::SeedableRng;
     use serde::Serialize;
     use std::{
         collections::HashMap,
@@ -173,7 +225,14 @@ mod tests {
     }
 
     fn setup(maximum_amount: Option<u64>) -> (AccountStates, Arc<Service>) {
-        let key = GenerateKey::generate_ed25519_in_memory();
+        setup_with_limits(maximum_amount, None)
+    }
+
+    fn setup_with_limits(
+        maximum_amount: Option<u64>,
+        limits: Option<LimitsConfig>,
+    ) -> (AccountStates, Arc<Service>) {
+        let key = Ed25519PrivateKey::generate(&mut rand::rngs::StdRng::from_entropy());
         let account_address = AuthenticationKey::ed25519(&key.public_key()).derived_address();
 
         let faucet_account = LocalAccount::new(account_address, key, 0);
@@ -211,6 +270,10 @@ mod tests {
             faucet_account,
             maximum_amount,
         );
+        let service = match limits {
+            Some(limits) => service.with_limits(Limits::new(limits).unwrap()),
+            None => service,
+        };
         (accounts, Arc::new(service))
     }
 
@@ -302,6 +365,15 @@ mod tests {
                         .expect("account should be created");
                     account.balance += amount;
                 }
+                ScriptFunctionCall::CoinTransfer { to, amount, .. } => {
+                    let mut writer = accounts.write();
+                    let sender = writer
+                        .get_mut(&txn.sender())
+                        .expect("sender should be created");
+                    sender.balance -= amount;
+                    let receiver = writer.get_mut(&to).expect("account should be created");
+                    receiver.balance += amount;
+                }
                 script => panic!("unexpected type of script function: {:?}", script),
             }
         }
@@ -459,6 +531,197 @@ mod tests {
         assert_eq!(account.balance, amount);
     }
 
+    #[tokio::test]
+    async fn test_mint_with_funding_accounts() {
+        let (accounts, service) = setup(None);
+        aptos_faucet::funding::provision(&service, b"seed", 2, 1000)
+            .await
+            .unwrap();
+        let funding_addresses: Vec<_> = service
+            .funding_accounts
+            .accounts()
+            .iter()
+            .map(|account| account.address())
+            .collect();
+        assert_eq!(funding_addresses.len(), 2);
+        let filter = routes(service);
+
+        let receivers = [
+            "459c77a38803bd53f3adee52703810e3a74fd7c46952c497e75afb0a7932586d",
+            "9ff98e82355eb13098f3b1157ac018a725c62c0e0820f422000814cdba407835",
+        ];
+        for receiver in receivers {
+            let resp = warp::test::request()
+                .method("POST")
+                .path(format!("/mint?address={}&amount=100", receiver).as_str())
+                .reply(&filter)
+                .await;
+            let values: Vec<HashValue> = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(values.len(), 2);
+        }
+
+        // Each request is served by a different funding account, transferring its own coins
+        let reader = accounts.read();
+        for receiver in receivers {
+            let addr = AccountAddress::from_hex(receiver).unwrap();
+            assert_eq!(reader.get(&addr).unwrap().balance, 100);
+        }
+        for address in funding_addresses {
+            assert_eq!(reader.get(&address).unwrap().balance, 900);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_funding_accounts_are_reused() {
+        let (accounts, service) = setup(None);
+        aptos_faucet::funding::provision(&service, b"seed", 2, 1000)
+            .await
+            .unwrap();
+        let funding_addresses = |service: &Service| -> Vec<AccountAddress> {
+            service
+                .funding_accounts
+                .accounts()
+                .iter()
+                .map(|account| account.address())
+                .collect()
+        };
+        let provisioned = funding_addresses(&service);
+
+        // Provisioning again from the same seed, e.g. on restart, reuses the funded accounts
+        aptos_faucet::funding::provision(&service, b"seed", 2, 1000)
+            .await
+            .unwrap();
+        assert_eq!(funding_addresses(&service), provisioned);
+        for address in &provisioned {
+            assert_eq!(accounts.read().get(address).unwrap().balance, 1000);
+        }
+
+        // Another seed provisions other accounts
+        aptos_faucet::funding::provision(&service, b"other seed", 2, 1000)
+            .await
+            .unwrap();
+        for address in funding_addresses(&service) {
+            assert!(!provisioned.contains(&address));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mint_address_daily_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let limits = LimitsConfig {
+            address_daily_limit: Some(1000),
+            accounting_file: Some(dir.path().join("accounting.json")),
+            ..LimitsConfig::default()
+        };
+        let (accounts, service) = setup_with_limits(None, Some(limits.clone()));
+        let filter = routes(service);
+
+        let address = "459c77a38803bd53f3adee52703810e3a74fd7c46952c497e75afb0a7932586d";
+        let mint = |amount: u64| {
+            warp::test::request()
+                .method("POST")
+                .path(format!("/mint?address={}&amount={}", address, amount).as_str())
+                .reply(&filter)
+        };
+        assert_eq!(mint(600).await.status(), 200);
+        assert_eq!(mint(600).await.status(), 429);
+        assert_eq!(mint(400).await.status(), 200);
+
+        let addr = AccountAddress::from_hex(address).unwrap();
+        assert_eq!(accounts.read().get(&addr).unwrap().balance, 1000);
+
+        // The quota survives a restart
+        let (_accounts, service) = setup_with_limits(None, Some(limits));
+        let resp = warp::test::request()
+            .method("POST")
+            .path(format!("/mint?address={}&amount=1", address).as_str())
+            .reply(&routes(service))
+            .await;
+        assert_eq!(resp.status(), 429);
+    }
+
+    #[tokio::test]
+    async fn test_mint_ip_daily_limit_ignores_spoofed_forwarded_for() {
+        let limits = LimitsConfig {
+            ip_daily_limit: Some(1000),
+            trust_forwarded_for: true,
+            ..LimitsConfig::default()
+        };
+        let (_accounts, service) = setup_with_limits(None, Some(limits));
+        let filter = routes(service);
+
+        // The client sets the first IPs, the proxy appends the IP of the client
+        let mint = |forwarded_for: &str| {
+            warp::test::request()
+                .method("POST")
+                .path("/mint?address=459c77a38803bd53f3adee52703810e3a74fd7c46952c497e75afb0a7932586d&amount=600")
+                .header("X-Forwarded-For", forwarded_for)
+                .reply(&filter)
+        };
+        assert_eq!(mint("1.1.1.1, 10.0.0.1").await.status(), 200);
+        assert_eq!(mint("2.2.2.2, 10.0.0.1").await.status(), 429);
+        assert_eq!(mint("10.0.0.2").await.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_mint_auth_token() {
+        let limits = LimitsConfig {
+            auth_token: Some("secret".to_string()),
+            ..LimitsConfig::default()
+        };
+        let (_accounts, service) = setup_with_limits(None, Some(limits));
+        let filter = routes(service);
+
+        let path = "/mint?address=459c77a38803bd53f3adee52703810e3a74fd7c46952c497e75afb0a7932586d&amount=10";
+        let resp = warp::test::request()
+            .method("POST")
+            .path(path)
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), 401);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path(path)
+            .header("Authorization", "Bearer secret")
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_mint_proof_of_work_is_not_replayable() {
+        let dir = tempfile::tempdir().unwrap();
+        let limits = LimitsConfig {
+            // Every nonce is a valid proof of work
+            pow_difficulty: Some(0),
+            accounting_file: Some(dir.path().join("accounting.json")),
+            ..LimitsConfig::default()
+        };
+        let (_accounts, service) = setup_with_limits(None, Some(limits.clone()));
+        let filter = routes(service);
+
+        let address = "459c77a38803bd53f3adee52703810e3a74fd7c46952c497e75afb0a7932586d";
+        let mint = |pow_nonce: u64| {
+            warp::test::request().method("POST").path(
+                format!(
+                    "/mint?address={}&amount=10&pow_nonce={}",
+                    address, pow_nonce
+                )
+                .as_str(),
+            )
+        };
+        assert_eq!(mint(1).reply(&filter).await.status(), 200);
+        assert_eq!(mint(1).reply(&filter).await.status(), 401);
+        assert_eq!(mint(2).reply(&filter).await.status(), 200);
+
+        // Spent proofs survive a restart
+        let (_accounts, service) = setup_with_limits(None, Some(limits));
+        let filter = routes(service);
+        assert_eq!(mint(2).reply(&filter).await.status(), 401);
+        assert_eq!(mint(3).reply(&filter).await.status(), 200);
+    }
+
     #[tokio::test]
     async fn test_health() {
         let (_accounts, service) = setup(None);
@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::Service;
+use crate::{
+    limits::{self, RequestInfo},
+    Service,
+};
 use anyhow::Result;
 use aptos_crypto::{ed25519::Ed25519PublicKey, hash::HashValue};
 use aptos_logger::{error, info, warn};
@@ -27,19 +30,44 @@ pub fn mint_routes(
         .and(warp::post())
         .and(warp::any().map(move || service.clone()))
         .and(warp::query().map(move |params: MintParams| params))
-        .and_then(|_, service, params| handle(service, params))
+        .and(limits::request_info())
+        .and_then(|_, service, params, request| handle(service, params, request))
 }
 
 async fn handle(
     service: Arc<Service>,
     params: MintParams,
+    request: RequestInfo,
 ) -> Result<Box<dyn warp::Reply>, Infallible> {
+    let reservation = match (&service.limits, params.receiver()) {
+        (Some(limits), Some(receiver)) => {
+            let amount = service.mint_amount(params.amount);
+            match limits
+                .check(&request, receiver, amount, params.pow_nonce)
+                .await
+            {
+                Ok(reservation) => reservation,
+                Err(err) => {
+                    info!("Rejected {}: {}", params, err);
+                    let status = err.status();
+                    return Ok(Box::new(warp::reply::with_status(err.to_string(), status)));
+                }
+            }
+        }
+        _ => None,
+    };
+
     match process(&service, params).await {
         Ok(body) => Ok(Box::new(body.to_string())),
-        Err(err) => Ok(Box::new(warp::reply::with_status(
-            err.to_string(),
-            StatusCode::INTERNAL_SERVER_ERROR,
-        ))),
+        Err(err) => {
+            if let (Some(limits), Some(reservation)) = (&service.limits, reservation) {
+                limits.refund(reservation).await;
+            }
+            Ok(Box::new(warp::reply::with_status(
+                err.to_string(),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )))
+        }
     }
 }
 
@@ -69,6 +97,8 @@ pub struct MintParams {
     pub address: Option<String>,
     pub pub_key: Option<Ed25519PublicKey>,
     pub return_txns: Option<bool>,
+    /// Proof of work, when required by the limits of the faucet.
+    pub pow_nonce: Option<u64>,
 }
 
 impl std::fmt::Display for MintParams {
@@ -105,16 +135,20 @@ impl MintParams {
 }
 
 pub async fn process(service: &Service, params: MintParams) -> Result<Response> {
-    let maybe_maximum_amount = service.maximum_amount.unwrap_or(params.amount);
-    let amount = std::cmp::min(params.amount, maybe_maximum_amount);
+    let amount = service.mint_amount(params.amount);
 
     let receiver_address = params.receiver().ok_or_else(|| {
         anyhow::format_err!("You must provide 'address' (preferred), 'pub_key', or 'auth_key'")
     })?;
 
-    let (mut faucet_seq, mut receiver_seq) = sequences(service, receiver_address).await?;
+    // Requests are distributed across the funding accounts, which track their own sequence numbers
+    let funding_account = service.funding_accounts.acquire();
+    let faucet_address = funding_account.address();
+
+    let (mut faucet_seq, mut receiver_seq) =
+        sequences(service, faucet_address, receiver_address).await?;
     let our_faucet_seq = {
-        let mut faucet_account = service.faucet_account.lock().await;
+        let mut faucet_account = funding_account.account().lock().await;
 
         // If the onchain sequence_number is greater than what we have, update our
         // sequence_numbers
@@ -135,7 +169,7 @@ pub async fn process(service: &Service, params: MintParams) -> Result<Response>
         );
 
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-        let (lhs, rhs) = sequences(service, receiver_address).await?;
+        let (lhs, rhs) = sequences(service, faucet_address, receiver_address).await?;
         faucet_seq = lhs;
         receiver_seq = rhs;
     }
@@ -143,7 +177,7 @@ pub async fn process(service: &Service, params: MintParams) -> Result<Response>
     // After 30 seconds, we still have not caught up, we are likely unhealthy
     if our_faucet_seq >= faucet_seq + 50 {
         error!("We are unhealthy, transactions have likely expired.");
-        let mut faucet_account = service.faucet_account.lock().await;
+        let mut faucet_account = funding_account.account().lock().await;
         if faucet_account.sequence_number() >= faucet_seq + 50 {
             info!("Resetting the sequence number counter.");
             *faucet_account.sequence_number_mut() = faucet_seq;
@@ -155,7 +189,7 @@ pub async fn process(service: &Service, params: MintParams) -> Result<Response>
     let mut txns = vec![];
 
     {
-        let mut faucet_account = service.faucet_account.lock().await;
+        let mut faucet_account = funding_account.account().lock().await;
 
         if receiver_seq.is_none() {
             let builder =
@@ -171,9 +205,11 @@ pub async fn process(service: &Service, params: MintParams) -> Result<Response>
 
         if amount != 0 {
             txns.push(
-                faucet_account.sign_with_transaction_builder(service.transaction_factory.payload(
-                    aptos_stdlib::encode_test_coin_mint(receiver_address, amount),
-                )),
+                faucet_account.sign_with_transaction_builder(
+                    service
+                        .transaction_factory
+                        .payload(funding_account.fund_payload(receiver_address, amount)),
+                ),
             );
         }
     }
@@ -184,7 +220,7 @@ pub async fn process(service: &Service, params: MintParams) -> Result<Response>
     // If there was an issue submitting a transaction we should just reset our sequence_numbers
     // to what was on chain
     if responses.iter().any(Result::is_err) {
-        *service.faucet_account.lock().await.sequence_number_mut() = faucet_seq;
+        *funding_account.account().lock().await.sequence_number_mut() = faucet_seq;
     }
 
     while !responses.is_empty() {
@@ -203,8 +239,11 @@ pub async fn process(service: &Service, params: MintParams) -> Result<Response>
     }
 }
 
-async fn sequences(service: &Service, receiver: AccountAddress) -> Result<(u64, Option<u64>)> {
-    let faucet_address = service.faucet_account.lock().await.address();
+async fn sequences(
+    service: &Service,
+    faucet_address: AccountAddress,
+    receiver: AccountAddress,
+) -> Result<(u64, Option<u64>)> {
     let f_request = service.client.get_account(faucet_address);
     let r_request = service.client.get_account(receiver);
     let mut responses = futures::future::join_all([f_request, r_request]).await;
@@ -171,6 +171,16 @@ impl<Key: Eq + Hash + Clone + Debug> TokenBucketRateLimiter<Key> {
         }
         remove
     }
+
+    /// Garbage collects the buckets not in use which are full, i.e. of the keys idle long enough
+    /// for their bucket to refill. A new bucket is never fuller, so no limit is lifted by this.
+    /// Returns the number of buckets removed.
+    pub fn garbage_collect_full_buckets(&self) -> usize {
+        let mut buckets = self.buckets.write();
+        let num_buckets = buckets.len();
+        buckets.retain(|_, bucket| Arc::strong_count(bucket) > 1 || !bucket.lock().is_full());
+        num_buckets - buckets.len()
+    }
 }
 
 /// A token bucket object that keeps track of everything related to a key
@@ -288,6 +298,12 @@ impl Bucket {
         }
     }
 
+    /// Refills the bucket, and tells whether it holds as many tokens as it can
+    pub fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.size
+    }
+
     /// Determine if an entire batch can be passed through
     /// This is important for message based rate limiting, where the whole message has
     /// to make it through, or else it must be rejected.  A result of `None` means it cannot
@@ -546,4 +562,30 @@ mod tests {
         assert!(!rate_limiter.try_garbage_collect_key(&key_to_keep));
         assert_num_keys(&rate_limiter, 1);
     }
+
+    #[test]
+    fn test_garbage_collect_full_buckets() {
+        let key_in_use = "in use";
+        let key_idle = "idle";
+        let rate_limiter = TokenBucketRateLimiter::test(2, 1);
+
+        let bucket_in_use = rate_limiter.bucket(key_in_use);
+        bucket_in_use.lock().acquire_all_tokens(1).unwrap();
+        rate_limiter
+            .bucket(key_idle)
+            .lock()
+            .acquire_all_tokens(1)
+            .unwrap();
+        assert_num_keys(&rate_limiter, 2);
+
+        // Buckets not refilled yet are kept
+        assert_eq!(rate_limiter.garbage_collect_full_buckets(), 0);
+        assert_num_keys(&rate_limiter, 2);
+
+        // Once refilled, only the bucket not in use is collected
+        sleep(Duration::from_secs(1));
+        assert_eq!(rate_limiter.garbage_collect_full_buckets(), 1);
+        assert_num_keys(&rate_limiter, 1);
+        assert!(rate_limiter.buckets.read().contains_key(&key_in_use));
+    }
 }
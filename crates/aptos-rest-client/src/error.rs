@@ -53,6 +53,10 @@ impl Error {
         matches!(self.inner.kind, Kind::NeedSync)
     }
 
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.inner.kind, Kind::HttpStatus(404))
+    }
+
     //
     // Private Constructors
     //
@@ -1,14 +1,26 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{anyhow, Result};
-use aptos_api_types::mime_types::BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE;
-pub use aptos_api_types::{self, MoveModuleBytecode, PendingTransaction, Transaction};
+use anyhow::{anyhow, ensure, Result};
+use aptos_api_types::mime_types::{
+    BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE,
+    BCS_SIGNED_TRANSACTION_BATCH as BCS_BATCH_CONTENT_TYPE,
+};
+pub use aptos_api_types::{
+    self, GasEstimation, MoveModuleBytecode, PendingTransaction, SubmissionStatus, Transaction,
+    TransactionSubmissionResult, ValueWithProof,
+};
 use aptos_crypto::HashValue;
 use aptos_types::{
-    account_address::AccountAddress, account_config::aptos_root_address,
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::aptos_root_address,
+    state_proof::StateProof,
+    state_store::{state_key::StateKey, state_value::StateValueWithProof},
     transaction::SignedTransaction,
+    trusted_state::{TrustedState, TrustedStateChange},
 };
+use move_deps::move_core_types::{language_storage::ResourceKey, parser::parse_struct_tag};
 use reqwest::{header::CONTENT_TYPE, Client as ReqwestClient, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -83,11 +95,18 @@ impl Client {
             epoch: r.epoch,
             version: r.ledger_version,
             timestamp_usecs: r.ledger_timestamp,
+            cursor: None,
         });
 
         Ok(response)
     }
 
+    pub async fn estimate_gas_price(&self) -> Result<Response<GasEstimation>> {
+        let url = self.base_url.join("estimate_gas_price")?;
+        let response = self.inner.get(url).send().await?;
+        self.json(response).await
+    }
+
     pub async fn submit(&self, txn: &SignedTransaction) -> Result<Response<PendingTransaction>> {
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.base_url.join("transactions")?;
@@ -103,6 +122,26 @@ impl Client {
         self.json(response).await
     }
 
+    /// Submits the transactions in a single request. A transaction rejected by the node is
+    /// reported in its result, instead of failing the whole batch.
+    pub async fn submit_batch(
+        &self,
+        txns: &[SignedTransaction],
+    ) -> Result<Response<Vec<TransactionSubmissionResult>>> {
+        let txns_payload = bcs::to_bytes(&txns)?;
+        let url = self.base_url.join("transactions/batch")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_BATCH_CONTENT_TYPE)
+            .body(txns_payload)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
     pub async fn submit_and_wait(&self, txn: &SignedTransaction) -> Result<Response<Transaction>> {
         self.submit(txn).await?;
         self.wait_for_signed_transaction(txn).await
@@ -256,6 +295,28 @@ impl Client {
         self.json(response).await
     }
 
+    /// Fetches a page of at most `limit` resources of the account, starting at `cursor`. The
+    /// cursor to the next page is returned in the response state, and is `None` after the last
+    /// page.
+    pub async fn get_account_resources_paginated(
+        &self,
+        address: AccountAddress,
+        cursor: Option<&str>,
+        limit: u16,
+    ) -> Result<Response<Vec<Resource>>> {
+        let url = self
+            .base_url
+            .join(&format!("accounts/{}/resources", address))?;
+
+        let mut request = self.inner.get(url).query(&[("limit", limit)]);
+        if let Some(cursor) = cursor {
+            request = request.query(&[("cursor", cursor)]);
+        }
+        let response = request.send().await?;
+
+        self.json(response).await
+    }
+
     pub async fn get_resource<T: DeserializeOwned>(
         &self,
         address: AccountAddress,
@@ -289,6 +350,36 @@ impl Client {
         self.json(response).await
     }
 
+    /// Fetches a resource along with its proofs, and verifies them against `trusted_state`, so
+    /// that the resource can be read from an untrusted node. Returns the resource BCS decoded from
+    /// the proven bytes, and the trusted state ratcheted to the ledger info the proofs were
+    /// verified against.
+    pub async fn get_resource_verified<T: DeserializeOwned>(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        trusted_state: &TrustedState,
+    ) -> Result<Response<(T, TrustedState)>> {
+        let struct_tag = parse_struct_tag(resource_type)?;
+        let state_key = StateKey::AccessPath(AccessPath::resource_access_path(ResourceKey::new(
+            address, struct_tag,
+        )));
+
+        let url = self
+            .base_url
+            .join(&format!("accounts/{}/resource/{}", address, resource_type))?;
+        let response = self
+            .inner
+            .get(url)
+            .query(&[("with_proof", "true")])
+            .query(&[("known_version", trusted_state.version())])
+            .send()
+            .await?;
+
+        let response: Response<ValueWithProof<Value>> = self.json(response).await?;
+        response.and_then(|value| verify_state_value(value, state_key, trusted_state))
+    }
+
     pub async fn get_account_modules(
         &self,
         address: AccountAddress,
@@ -331,9 +422,16 @@ impl Client {
         &self,
         response: reqwest::Response,
     ) -> Result<(reqwest::Response, State)> {
-        if !response.status().is_success() {
-            let error_response = response.json::<RestError>().await?;
-            return Err(anyhow::anyhow!("Request failed: {:?}", error_response));
+        let status = response.status();
+        if !status.is_success() {
+            // The status is kept as the source of the error, so that callers can tell it apart
+            let error = anyhow::Error::new(error::Error::status(status.as_u16()));
+            return Err(match response.json::<RestError>().await {
+                Ok(error_response) => {
+                    error.context(format!("Request failed: {:?}", error_response))
+                }
+                Err(err) => error.context(format!("Request failed: {}: {}", status, err)),
+            });
         }
         let state = State::from_headers(response.headers())?;
 
@@ -365,3 +463,108 @@ impl Client {
         Ok(())
     }
 }
+
+/// Verifies the proofs of a state value, and returns the value BCS decoded from the proven bytes
+/// along with the new trusted state. The value must be proven at the version of the latest ledger
+/// info: the version the server says the value is at is not trusted.
+fn verify_state_value<T: DeserializeOwned>(
+    value: ValueWithProof<Value>,
+    state_key: StateKey,
+    trusted_state: &TrustedState,
+) -> Result<(T, TrustedState)> {
+    let state_value_with_proof: StateValueWithProof =
+        bcs::from_bytes(&Vec::<u8>::from(value.state_value_with_proof))?;
+    let state_proof: StateProof = bcs::from_bytes(&Vec::<u8>::from(value.state_proof))?;
+
+    let ledger_info = state_proof.latest_ledger_info();
+    let new_trusted_state = match trusted_state.verify_and_ratchet(&state_proof)? {
+        TrustedStateChange::Version { new_state } => new_state,
+        TrustedStateChange::Epoch { new_state, .. } => new_state,
+        TrustedStateChange::NoChange => trusted_state.clone(),
+    };
+    // A trusted state too many epochs behind only ratchets up to an epoch change ledger info.
+    ensure!(
+        new_trusted_state.version() == ledger_info.version(),
+        "could not verify ledger info at version {}, trusted state only ratcheted to version {}",
+        ledger_info.version(),
+        new_trusted_state.version(),
+    );
+
+    state_value_with_proof.verify(ledger_info, ledger_info.version(), state_key)?;
+    let bytes = state_value_with_proof
+        .value
+        .and_then(|value| value.maybe_bytes)
+        .ok_or_else(|| anyhow!("state value does not exist"))?;
+    Ok((bcs::from_bytes(&bytes)?, new_trusted_state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::{
+        block_info::BlockInfo,
+        epoch_change::EpochChangeProof,
+        epoch_state::EpochState,
+        ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+        proof::{
+            SparseMerkleProof, StateStoreValueProof, TransactionAccumulatorProof,
+            TransactionInfoWithProof,
+        },
+        state_store::state_value::StateValue,
+        transaction::{ExecutionStatus, TransactionInfo},
+        waypoint::Waypoint,
+    };
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_reject_state_value_at_older_version() {
+        let ledger_info = LedgerInfo::new(
+            BlockInfo::new(
+                0,
+                0,
+                HashValue::zero(),
+                HashValue::zero(),
+                10,
+                0,
+                Some(EpochState::empty()),
+            ),
+            HashValue::zero(),
+        );
+        let trusted_state =
+            TrustedState::from_epoch_waypoint(Waypoint::new_epoch_boundary(&ledger_info).unwrap());
+        let ledger_info = LedgerInfoWithSignatures::new(ledger_info, BTreeMap::new());
+        let state_proof = StateProof::new(
+            ledger_info.clone(),
+            EpochChangeProof::new(vec![ledger_info], false),
+        );
+
+        // The server claims the value is at version 9 while the ledger info is at version 10.
+        let state_value_with_proof = StateValueWithProof::new(
+            9,
+            Some(StateValue::from(bcs::to_bytes(&1u64).unwrap())),
+            StateStoreValueProof::new(
+                TransactionInfoWithProof::new(
+                    TransactionAccumulatorProof::new(vec![]),
+                    TransactionInfo::new(
+                        HashValue::zero(),
+                        HashValue::zero(),
+                        HashValue::zero(),
+                        Some(HashValue::zero()),
+                        0,
+                        ExecutionStatus::Success,
+                    ),
+                ),
+                SparseMerkleProof::new(None, vec![]),
+            ),
+        );
+        let value = ValueWithProof {
+            data: Value::Null,
+            state_value_with_proof: bcs::to_bytes(&state_value_with_proof).unwrap().into(),
+            state_proof: bcs::to_bytes(&state_proof).unwrap().into(),
+        };
+        let state_key = StateKey::AccessPath(AccessPath::new(AccountAddress::ONE, vec![]));
+
+        let err = verify_state_value::<u64>(value, state_key, &trusted_state).unwrap_err();
+        assert!(err.to_string().contains("State version (9)"), "{}", err);
+    }
+}
@@ -7,6 +7,10 @@ use clap::Subcommand;
 pub mod create;
 pub mod fund;
 pub mod list;
+pub mod multisig;
+pub mod rotate_key;
+#[cfg(test)]
+mod tests;
 pub mod transfer;
 
 /// CLI tool for interacting with accounts
@@ -16,6 +20,9 @@ pub enum AccountTool {
     Create(create::CreateAccount),
     Fund(fund::FundAccount),
     List(list::ListAccount),
+    #[clap(subcommand)]
+    Multisig(multisig::MultisigTool),
+    RotateKey(rotate_key::RotateKey),
     Transfer(transfer::TransferCoins),
 }
 
@@ -25,6 +32,8 @@ impl AccountTool {
             AccountTool::Create(tool) => tool.execute_serialized().await,
             AccountTool::Fund(tool) => tool.execute_serialized().await,
             AccountTool::List(tool) => tool.execute_serialized().await,
+            AccountTool::Multisig(tool) => tool.execute().await,
+            AccountTool::RotateKey(tool) => tool.execute_serialized().await,
             AccountTool::Transfer(tool) => tool.execute_serialized().await,
         }
     }
@@ -0,0 +1,338 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            CliCommand, CliError, CliResult, CliTypedResult, EncodingOptions, EncodingType,
+            PrivateKeyInputOptions, ProfileOptions, RestOptions, SaveFile, TransactionSummary,
+        },
+        utils::{get_chain_id, get_sequence_number, read_from_file},
+    },
+    move_tool::FunctionCallOptions,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    multi_ed25519::MultiEd25519PublicKey,
+    PrivateKey,
+};
+use aptos_rest_client::Client;
+use aptos_sdk::{transaction_builder::TransactionFactory, types::MultiSigAccount};
+use aptos_types::{account_address::AccountAddress, transaction::RawTransaction};
+use async_trait::async_trait;
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Tool for creating and operating K-of-N multisig accounts
+///
+/// A multisig account is defined by the public keys of its N key holders and a threshold K.
+/// Transactions are built unsigned to a file, signed separately by at least K key holders
+/// (which can be done offline), and the signatures are then combined and submitted.
+#[derive(Debug, Subcommand)]
+pub enum MultisigTool {
+    Create(CreateMultisig),
+    BuildTransaction(BuildMultisigTransaction),
+    SignTransaction(SignMultisigTransaction),
+    SubmitTransaction(SubmitMultisigTransaction),
+}
+
+impl MultisigTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            MultisigTool::Create(tool) => tool.execute_serialized().await,
+            MultisigTool::BuildTransaction(tool) => tool.execute_serialized().await,
+            MultisigTool::SignTransaction(tool) => tool.execute_serialized().await,
+            MultisigTool::SubmitTransaction(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// Options for loading the multisig key created by `aptos account multisig create`
+#[derive(Debug, Parser)]
+pub struct MultisigKeyOptions {
+    /// Multisig key file, containing the public keys and threshold of the account
+    #[clap(long, parse(from_os_str))]
+    multisig_key_file: PathBuf,
+}
+
+impl MultisigKeyOptions {
+    fn load_account(&self, encoding: EncodingType) -> CliTypedResult<MultiSigAccount> {
+        let public_key: MultiEd25519PublicKey =
+            encoding.load_key("--multisig-key-file", self.multisig_key_file.as_path())?;
+        Ok(MultiSigAccount::new(public_key, 0))
+    }
+}
+
+/// A signature of a multisig transaction by one of the key holders
+#[derive(Debug, Deserialize, Serialize)]
+struct MultisigSignature {
+    public_key: Ed25519PublicKey,
+    signature: Ed25519Signature,
+}
+
+/// Create a K-of-N multisig key from the public keys of the key holders
+///
+/// The multisig key is saved to `output_file` for use by the other multisig commands, and the
+/// address of the account is returned.  The account can then be created on-chain with
+/// `aptos account create --account <ADDRESS>`.
+#[derive(Debug, Parser)]
+pub struct CreateMultisig {
+    /// Public key files of the key holders, in a fixed order
+    #[clap(long, required = true, multiple_values = true, parse(from_os_str))]
+    public_key_files: Vec<PathBuf>,
+    /// Number of signatures required to authorize a transaction
+    #[clap(long)]
+    threshold: u8,
+    #[clap(flatten)]
+    encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<MultisigAccountSummary> for CreateMultisig {
+    fn command_name(&self) -> &'static str {
+        "CreateMultisig"
+    }
+
+    async fn execute(self) -> CliTypedResult<MultisigAccountSummary> {
+        self.save_file.check_file()?;
+
+        let encoding = self.encoding_options.encoding;
+        let public_keys = self
+            .public_key_files
+            .iter()
+            .map(|file| encoding.load_key("--public-key-files", file.as_path()))
+            .collect::<CliTypedResult<Vec<Ed25519PublicKey>>>()?;
+        let public_key =
+            MultiEd25519PublicKey::new(public_keys, self.threshold).map_err(|err| {
+                CliError::CommandArgumentError(format!(
+                    "Invalid threshold {} for {} public keys: {:?}",
+                    self.threshold,
+                    self.public_key_files.len(),
+                    err
+                ))
+            })?;
+        self.save_file.save_to_file(
+            "Multisig key",
+            &encoding.encode_key("Multisig key", &public_key)?,
+        )?;
+
+        let account = MultiSigAccount::new(public_key, 0);
+        Ok(MultisigAccountSummary {
+            address: account.address(),
+            threshold: account.threshold(),
+            public_keys: account.public_key().public_keys().clone(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MultisigAccountSummary {
+    address: AccountAddress,
+    threshold: u8,
+    public_keys: Vec<Ed25519PublicKey>,
+}
+
+/// Build an unsigned transaction from a multisig account, calling a Move function
+///
+/// The BCS encoded raw transaction is saved to `output_file`, to be signed by the key holders
+/// with `aptos account multisig sign-transaction`.
+#[derive(Debug, Parser)]
+pub struct BuildMultisigTransaction {
+    #[clap(flatten)]
+    multisig_key_options: MultisigKeyOptions,
+    #[clap(flatten)]
+    function_options: FunctionCallOptions,
+    /// Sequence number of the transaction
+    ///
+    /// Defaults to the current sequence number of the account on-chain
+    #[clap(long)]
+    sequence_number: Option<u64>,
+    /// Maximum gas to be used by the transaction
+    #[clap(long, default_value_t = 1000)]
+    max_gas: u64,
+    /// Number of seconds until the transaction expires
+    ///
+    /// This must leave enough time to collect the signatures of the key holders
+    #[clap(long, default_value_t = 3600)]
+    expiration_secs: u64,
+    #[clap(flatten)]
+    encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    rest_options: RestOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+    #[clap(flatten)]
+    save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<RawTransaction> for BuildMultisigTransaction {
+    fn command_name(&self) -> &'static str {
+        "BuildMultisigTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<RawTransaction> {
+        self.save_file.check_file()?;
+
+        let mut account = self
+            .multisig_key_options
+            .load_account(self.encoding_options.encoding)?;
+        let client = Client::new(self.rest_options.url(&self.profile_options.profile)?);
+        *account.sequence_number_mut() = match self.sequence_number {
+            Some(sequence_number) => sequence_number,
+            None => get_sequence_number(&client, account.address()).await?,
+        };
+
+        let transaction_factory = TransactionFactory::new(get_chain_id(&client).await?)
+            .with_gas_unit_price(1)
+            .with_max_gas_amount(self.max_gas)
+            .with_transaction_expiration_time(self.expiration_secs);
+        let raw_txn = account
+            .build_transaction(transaction_factory.payload(self.function_options.payload()?));
+        self.save_file.save_to_file(
+            "Raw transaction",
+            &bcs::to_bytes(&raw_txn).map_err(|err| CliError::BCS("Raw transaction", err))?,
+        )?;
+        Ok(raw_txn)
+    }
+}
+
+/// Sign a multisig transaction with the key of one of the key holders
+///
+/// This doesn't need network access.  The signature is saved to `output_file`, to be combined
+/// with the others by `aptos account multisig submit-transaction`.  The transaction is
+/// returned so that it can be reviewed.
+#[derive(Debug, Parser)]
+pub struct SignMultisigTransaction {
+    #[clap(flatten)]
+    multisig_key_options: MultisigKeyOptions,
+    /// BCS encoded raw transaction file, from `aptos account multisig build-transaction`
+    #[clap(long, parse(from_os_str))]
+    raw_txn_file: PathBuf,
+    #[clap(flatten)]
+    private_key_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+    #[clap(flatten)]
+    save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<RawTransaction> for SignMultisigTransaction {
+    fn command_name(&self) -> &'static str {
+        "SignMultisigTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<RawTransaction> {
+        self.save_file.check_file()?;
+
+        let encoding = self.encoding_options.encoding;
+        let account = self.multisig_key_options.load_account(encoding)?;
+        let raw_txn = load_raw_transaction(&self.raw_txn_file)?;
+        if raw_txn.sender() != account.address() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Transaction is sent by {}, not by the multisig account {}",
+                raw_txn.sender(),
+                account.address()
+            )));
+        }
+
+        let private_key = self
+            .private_key_options
+            .extract_private_key(encoding, &self.profile_options.profile)?;
+        let (signature, _) = account
+            .sign_partial(&raw_txn, &private_key)
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(
+                    "Private key is not one of the keys of the multisig account".to_string(),
+                )
+            })?;
+        let signature = MultisigSignature {
+            public_key: private_key.public_key(),
+            signature,
+        };
+        self.save_file.save_to_file(
+            "Signature",
+            &bcs::to_bytes(&signature).map_err(|err| CliError::BCS("Signature", err))?,
+        )?;
+        Ok(raw_txn)
+    }
+}
+
+/// Combine the signatures of the key holders and submit a multisig transaction
+///
+/// At least as many signatures as the threshold of the multisig account are required.
+#[derive(Debug, Parser)]
+pub struct SubmitMultisigTransaction {
+    #[clap(flatten)]
+    multisig_key_options: MultisigKeyOptions,
+    /// BCS encoded raw transaction file, from `aptos account multisig build-transaction`
+    #[clap(long, parse(from_os_str))]
+    raw_txn_file: PathBuf,
+    /// Signature files, from `aptos account multisig sign-transaction`
+    #[clap(long, required = true, multiple_values = true, parse(from_os_str))]
+    signature_files: Vec<PathBuf>,
+    #[clap(flatten)]
+    encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    rest_options: RestOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for SubmitMultisigTransaction {
+    fn command_name(&self) -> &'static str {
+        "SubmitMultisigTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let account = self
+            .multisig_key_options
+            .load_account(self.encoding_options.encoding)?;
+        let raw_txn = load_raw_transaction(&self.raw_txn_file)?;
+
+        let mut signatures = Vec::new();
+        for file in self.signature_files.iter() {
+            let signature: MultisigSignature = bcs::from_bytes(&read_from_file(file)?)
+                .map_err(|err| CliError::BCS("Signature", err))?;
+            let index = account.key_index(&signature.public_key).ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "Signature in {} is not from a key of the multisig account",
+                    file.display()
+                ))
+            })?;
+            signatures.push((signature.signature, index));
+        }
+
+        let transaction = account
+            .combine_signatures(raw_txn, signatures)
+            .map_err(|err| {
+                CliError::CommandArgumentError(format!(
+                    "Unable to combine {} signatures with a threshold of {}: {:?}",
+                    self.signature_files.len(),
+                    account.threshold(),
+                    err
+                ))
+            })?;
+        transaction.clone().check_signature().map_err(|err| {
+            CliError::CommandArgumentError(format!("Invalid multisig signature: {}", err))
+        })?;
+
+        let client = Client::new(self.rest_options.url(&self.profile_options.profile)?);
+        client
+            .submit_and_wait(&transaction)
+            .await
+            .map(|response| TransactionSummary::from(response.into_inner()))
+            .map_err(|err| CliError::ApiError(err.to_string()))
+    }
+}
+
+fn load_raw_transaction(path: &Path) -> CliTypedResult<RawTransaction> {
+    bcs::from_bytes(&read_from_file(path)?).map_err(|err| CliError::BCS("Raw transaction", err))
+}
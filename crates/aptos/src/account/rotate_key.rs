@@ -0,0 +1,103 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{
+    CliCommand, CliConfig, CliError, CliTypedResult, TransactionOptions, TransactionSummary,
+};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
+use aptos_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
+use async_trait::async_trait;
+use cached_framework_packages::aptos_stdlib;
+use clap::Parser;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Rotate the authentication key of an account
+///
+/// Transactions from the account must then be signed with the new private key, and the address
+/// of the account doesn't change.  If the account is the one of the profile, the profile is
+/// updated to use the new private key.
+#[derive(Debug, Parser)]
+pub struct RotateKey {
+    #[clap(flatten)]
+    txn_options: TransactionOptions,
+    /// Private key file of the new key, e.g. from `aptos key generate`
+    #[clap(long, parse(from_os_str))]
+    new_private_key_file: PathBuf,
+}
+
+#[async_trait]
+impl CliCommand<RotateSummary> for RotateKey {
+    fn command_name(&self) -> &'static str {
+        "RotateKey"
+    }
+
+    async fn execute(self) -> CliTypedResult<RotateSummary> {
+        let new_private_key: Ed25519PrivateKey =
+            self.txn_options.encoding_options.encoding.load_key(
+                "--new-private-key-file",
+                self.new_private_key_file.as_path(),
+            )?;
+        let new_auth_key = AuthenticationKey::ed25519(&new_private_key.public_key());
+        let sender_address = self.txn_options.sender_address()?;
+
+        let transaction = self
+            .txn_options
+            .submit_transaction(aptos_stdlib::encode_account_rotate_authentication_key(
+                new_auth_key.to_vec(),
+            ))
+            .await?;
+        if !transaction.success() {
+            return Err(CliError::ApiError(format!(
+                "Key rotation failed: {}",
+                transaction.vm_status()
+            )));
+        }
+
+        let profile_updated = update_profile(
+            &self.txn_options.profile_options.profile,
+            sender_address,
+            new_private_key,
+        )?;
+        Ok(RotateSummary {
+            transaction: TransactionSummary::from(transaction),
+            profile_updated,
+        })
+    }
+}
+
+/// Replaces the key of the profile if it's for the rotated account, returning whether it was
+fn update_profile(
+    profile: &str,
+    address: AccountAddress,
+    new_private_key: Ed25519PrivateKey,
+) -> CliTypedResult<bool> {
+    if !CliConfig::config_exists() {
+        return Ok(false);
+    }
+
+    let mut config = CliConfig::load()?;
+    let profile_config = match config
+        .profiles
+        .as_mut()
+        .and_then(|profiles| profiles.get_mut(profile))
+    {
+        Some(profile_config) if profile_config.account_address() == Some(address) => profile_config,
+        _ => return Ok(false),
+    };
+    profile_config.rotate_key(address, new_private_key);
+    config.save().map_err(|err| {
+        CliError::UnexpectedError(format!(
+            "The key was rotated but the profile '{}' couldn't be updated, \
+            the private key must be replaced manually: {}",
+            profile, err
+        ))
+    })?;
+    Ok(true)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateSummary {
+    transaction: TransactionSummary,
+    profile_updated: bool,
+}
@@ -0,0 +1,46 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{account_address_from_public_key, sender_address_for_key, ProfileConfig},
+    op::key::GenerateKey,
+};
+use aptos_crypto::PrivateKey;
+
+/// Transactions signed with the key of a profile must still be sent from the account of the
+/// profile once its authentication key was rotated, as the address can't be derived from the key
+#[test]
+fn test_transfer_after_key_rotation_is_sent_from_original_account() {
+    let original_key = GenerateKey::generate_ed25519_in_memory();
+    let address = account_address_from_public_key(&original_key.public_key());
+    let mut profile = ProfileConfig {
+        public_key: Some(original_key.public_key()),
+        private_key: Some(original_key),
+        ..Default::default()
+    };
+    assert_eq!(profile.account_address(), Some(address));
+    assert_eq!(
+        sender_address_for_key(Some(&profile), profile.private_key.as_ref().unwrap()),
+        address
+    );
+
+    // What `aptos account rotate-key` saves to the profile
+    let new_key = GenerateKey::generate_ed25519_in_memory();
+    let new_key_address = account_address_from_public_key(&new_key.public_key());
+    profile.rotate_key(address, new_key);
+    assert_ne!(new_key_address, address);
+
+    // `account transfer` and the other commands signing with the profile's key
+    assert_eq!(
+        sender_address_for_key(Some(&profile), profile.private_key.as_ref().unwrap()),
+        address
+    );
+    // `--account <profile>` arguments
+    assert_eq!(profile.account_address(), Some(address));
+
+    // A key given on the command line isn't the one of a profile
+    assert_eq!(
+        sender_address_for_key(None, profile.private_key.as_ref().unwrap()),
+        new_key_address
+    );
+}
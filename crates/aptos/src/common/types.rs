@@ -5,8 +5,9 @@ use crate::{
     common::{
         init::{DEFAULT_FAUCET_URL, DEFAULT_REST_URL},
         utils::{
-            check_if_file_exists, read_from_file, to_common_result, to_common_success_result,
-            write_to_file, write_to_file_with_opts, write_to_user_only_file,
+            check_if_file_exists, get_chain_id, read_from_file, submit_transaction_from_account,
+            to_common_result, to_common_success_result, write_to_file, write_to_file_with_opts,
+            write_to_user_only_file,
         },
     },
     genesis::git::from_yaml,
@@ -17,7 +18,10 @@ use aptos_crypto::{
 };
 use aptos_logger::debug;
 use aptos_rest_client::{aptos_api_types::WriteSetChange, Client, Transaction};
-use aptos_types::{chain_id::ChainId, transaction::authenticator::AuthenticationKey};
+use aptos_types::{
+    chain_id::ChainId,
+    transaction::{authenticator::AuthenticationKey, TransactionPayload},
+};
 use async_trait::async_trait;
 use clap::{ArgEnum, Parser};
 use move_deps::move_core_types::account_address::AccountAddress;
@@ -127,6 +131,7 @@ pub struct CliConfig {
 
 const CONFIG_FILE: &str = "config.yaml";
 const LEGACY_CONFIG_FILE: &str = "config.yml";
+const TEMP_CONFIG_FILE: &str = "config.yaml.tmp";
 const CONFIG_FOLDER: &str = ".aptos";
 
 /// An individual profile
@@ -144,6 +149,31 @@ pub struct ProfileConfig {
     pub faucet_url: Option<String>,
 }
 
+impl ProfileConfig {
+    /// Address of the account of the profile
+    ///
+    /// This is the `account` if set, as the authentication key of the account may have been
+    /// rotated, otherwise it's derived from the key of the profile.
+    pub fn account_address(&self) -> Option<AccountAddress> {
+        self.account.or_else(|| {
+            self.public_key
+                .clone()
+                .or_else(|| self.private_key.as_ref().map(|key| key.public_key()))
+                .as_ref()
+                .map(account_address_from_public_key)
+        })
+    }
+
+    /// Replaces the key of the profile after the authentication key of its account at `address`
+    /// was rotated to `new_private_key`
+    pub fn rotate_key(&mut self, address: AccountAddress, new_private_key: Ed25519PrivateKey) {
+        self.public_key = Some(new_private_key.public_key());
+        self.private_key = Some(new_private_key);
+        // The address can no longer be derived from the key
+        self.account = Some(address);
+    }
+}
+
 impl Default for CliConfig {
     fn default() -> Self {
         CliConfig {
@@ -219,12 +249,15 @@ impl CliConfig {
             debug!("{} folder already initialized", aptos_folder.display());
         }
 
-        // Save over previous config file
+        // Save over previous config file, through a temporary file so that it's replaced atomically
         let config_file = aptos_folder.join(CONFIG_FILE);
+        let temp_config_file = aptos_folder.join(TEMP_CONFIG_FILE);
         let config_bytes = serde_yaml::to_string(&self).map_err(|err| {
             CliError::UnexpectedError(format!("Failed to serialize config {}", err))
         })?;
-        write_to_user_only_file(&config_file, CONFIG_FILE, config_bytes.as_bytes())?;
+        write_to_user_only_file(&temp_config_file, CONFIG_FILE, config_bytes.as_bytes())?;
+        std::fs::rename(&temp_config_file, &config_file)
+            .map_err(|err| CliError::IO(CONFIG_FILE.to_string(), err))?;
 
         // As a cleanup, delete the old if it exists
         let legacy_config_file = aptos_folder.join(LEGACY_CONFIG_FILE);
@@ -558,16 +591,91 @@ pub struct WriteTransactionOptions {
 impl WriteTransactionOptions {
     /// Retrieve the chain id from onchain via the Rest API
     pub async fn chain_id(&self, profile: &str) -> CliTypedResult<ChainId> {
-        let client = Client::new(self.rest_options.url(profile)?);
-        let state = client
-            .get_ledger_information()
-            .await
-            .map_err(|err| CliError::ApiError(err.to_string()))?
-            .into_inner();
-        Ok(ChainId::new(state.chain_id))
+        get_chain_id(&Client::new(self.rest_options.url(profile)?)).await
     }
 }
 
+/// Options for signing and submitting a transaction from the account of the private key
+#[derive(Debug, Parser)]
+pub struct TransactionOptions {
+    #[clap(flatten)]
+    pub write_options: WriteTransactionOptions,
+    #[clap(flatten)]
+    pub encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub profile_options: ProfileOptions,
+}
+
+impl TransactionOptions {
+    /// Retrieve the private key from the command line or the profile
+    pub fn private_key(&self) -> CliTypedResult<Ed25519PrivateKey> {
+        self.write_options.private_key_options.extract_private_key(
+            self.encoding_options.encoding,
+            &self.profile_options.profile,
+        )
+    }
+
+    /// Address of the account sending the transaction
+    ///
+    /// When the private key of the profile is used, this is the account of the profile, as its
+    /// authentication key may have been rotated.  Otherwise it's derived from the private key.
+    pub fn sender_address(&self) -> CliTypedResult<AccountAddress> {
+        let profile = if self
+            .write_options
+            .private_key_options
+            .extract_private_key_cli(self.encoding_options.encoding)?
+            .is_none()
+        {
+            CliConfig::load_profile(&self.profile_options.profile)?
+        } else {
+            None
+        };
+        Ok(sender_address_for_key(
+            profile.as_ref(),
+            &self.private_key()?,
+        ))
+    }
+
+    pub fn rest_client(&self) -> CliTypedResult<Client> {
+        Ok(Client::new(
+            self.write_options
+                .rest_options
+                .url(&self.profile_options.profile)?,
+        ))
+    }
+
+    /// Signs the payload with the private key, submits it and waits for its execution
+    pub async fn submit_transaction(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<Transaction> {
+        submit_transaction_from_account(
+            self.write_options
+                .rest_options
+                .url(&self.profile_options.profile)?,
+            self.write_options
+                .chain_id(&self.profile_options.profile)
+                .await?,
+            self.sender_address()?,
+            self.private_key()?,
+            payload,
+            self.write_options.max_gas,
+        )
+        .await
+    }
+}
+
+/// Address of the account sending transactions signed with `private_key`, taken from `profile` if
+/// the key is the one of the profile
+pub fn sender_address_for_key(
+    profile: Option<&ProfileConfig>,
+    private_key: &Ed25519PrivateKey,
+) -> AccountAddress {
+    profile
+        .and_then(|profile| profile.account)
+        .unwrap_or_else(|| account_address_from_public_key(&private_key.public_key()))
+}
+
 /// Options for compiling a move package dir
 #[derive(Debug, Parser)]
 pub struct MovePackageDir {
@@ -623,9 +731,10 @@ pub fn load_account_arg(str: &str) -> Result<AccountAddress, CliError> {
         })
     } else if let Ok(account_address) = AccountAddress::from_str(str) {
         Ok(account_address)
-    } else if let Some(Some(private_key)) = CliConfig::load_profile(str)?.map(|p| p.private_key) {
-        let public_key = private_key.public_key();
-        Ok(account_address_from_public_key(&public_key))
+    } else if let Some(Some(account_address)) =
+        CliConfig::load_profile(str)?.map(|p| p.account_address())
+    {
+        Ok(account_address)
     } else {
         Err(CliError::CommandArgumentError(
             "'--account-address' or '--profile' after using aptos init must be provided"
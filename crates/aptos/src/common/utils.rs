@@ -5,14 +5,11 @@ use crate::{
     common::types::{CliError, CliTypedResult, PromptOptions},
     CliResult,
 };
-use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
+use aptos_crypto::ed25519::Ed25519PrivateKey;
 use aptos_rest_client::{Client, Transaction};
 use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
 use aptos_telemetry::constants::APTOS_CLI_PUSH_METRICS;
-use aptos_types::{
-    chain_id::ChainId,
-    transaction::{authenticator::AuthenticationKey, TransactionPayload},
-};
+use aptos_types::{chain_id::ChainId, transaction::TransactionPayload};
 use itertools::Itertools;
 use move_deps::move_core_types::account_address::AccountAddress;
 use reqwest::Url;
@@ -252,6 +249,16 @@ pub async fn get_sequence_number(
     Ok(account.sequence_number)
 }
 
+/// Retrieve the chain id from onchain via the Rest API
+pub async fn get_chain_id(client: &aptos_rest_client::Client) -> CliTypedResult<ChainId> {
+    let state = client
+        .get_ledger_information()
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?
+        .into_inner();
+    Ok(ChainId::new(state.chain_id))
+}
+
 /// Error message for parsing a map
 const PARSE_MAP_SYNTAX_MSG: &str = "Invalid syntax for map. Example: Name=Value,Name2=Value";
 
@@ -286,20 +293,18 @@ where
     Ok(map)
 }
 
-/// Submits a [`TransactionPayload`] as signed by the `sender_key`
-pub async fn submit_transaction(
+/// Submits a transaction from an account whose authentication key may have been rotated, so that
+/// its address can't be derived from its key
+pub async fn submit_transaction_from_account(
     url: Url,
     chain_id: ChainId,
+    sender_address: AccountAddress,
     sender_key: Ed25519PrivateKey,
     payload: TransactionPayload,
     max_gas: u64,
 ) -> CliTypedResult<Transaction> {
     let client = Client::new(url);
 
-    // Get sender address
-    let sender_address = AuthenticationKey::ed25519(&sender_key.public_key()).derived_address();
-    let sender_address = AccountAddress::new(*sender_address);
-
     // Get sequence number for account
     let sequence_number = get_sequence_number(&client, sender_address).await?;
 
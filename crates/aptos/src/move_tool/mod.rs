@@ -6,19 +6,20 @@ mod aptos_debug_natives;
 use crate::{
     common::{
         types::{
-            load_account_arg, AccountAddressWrapper, CliError, CliTypedResult, EncodingOptions,
-            MovePackageDir, ProfileOptions, PromptOptions, TransactionSummary,
-            WriteTransactionOptions,
+            load_account_arg, AccountAddressWrapper, CliError, CliTypedResult, MovePackageDir,
+            ProfileOptions, PromptOptions, RestOptions, SaveFile, TransactionOptions,
+            TransactionSummary,
         },
-        utils::{check_if_file_exists, submit_transaction},
+        utils::check_if_file_exists,
     },
     CliCommand, CliResult,
 };
-use aptos_rest_client::aptos_api_types::MoveType;
+use aptos_rest_client::{aptos_api_types::MoveType, Client};
 use aptos_types::transaction::{ModuleBundle, ScriptFunction, TransactionPayload};
 use async_trait::async_trait;
 use clap::{Parser, Subcommand};
 use move_deps::{
+    move_binary_format::CompiledModule,
     move_cli,
     move_cli::package::cli::UnitTestResult,
     move_command_line_common::env::get_bytecode_version_from_env,
@@ -47,6 +48,7 @@ use std::{
 #[derive(Subcommand)]
 pub enum MoveTool {
     Compile(CompilePackage),
+    GenerateBindings(GenerateBindings),
     Init(InitPackage),
     Publish(PublishPackage),
     Run(RunFunction),
@@ -57,6 +59,7 @@ impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::Compile(tool) => tool.execute_serialized().await,
+            MoveTool::GenerateBindings(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
             MoveTool::Publish(tool) => tool.execute_serialized().await,
             MoveTool::Run(tool) => tool.execute_serialized().await,
@@ -178,6 +181,74 @@ impl CliCommand<Vec<String>> for CompilePackage {
     }
 }
 
+/// Generates typed Rust bindings for the script functions and struct types of a package
+///
+/// A function returning the transaction payload is generated for each script function, and a
+/// struct that can be decoded from the REST API is generated for each struct type.  The package
+/// is compiled from `package_dir`, unless `account` is given, in which case the modules published
+/// at the account are fetched instead.
+#[derive(Parser)]
+pub struct GenerateBindings {
+    #[clap(flatten)]
+    move_options: MovePackageDir,
+    /// Account to fetch the published modules from, instead of compiling the package
+    #[clap(long, parse(try_from_str = load_account_arg))]
+    account: Option<AccountAddress>,
+    #[clap(flatten)]
+    rest_options: RestOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+    #[clap(flatten)]
+    save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for GenerateBindings {
+    fn command_name(&self) -> &'static str {
+        "GenerateBindings"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        self.save_file.check_file()?;
+
+        let modules = if let Some(account) = self.account {
+            let client = Client::new(self.rest_options.url(&self.profile_options.profile)?);
+            client
+                .get_account_modules(account)
+                .await
+                .map_err(|err| CliError::ApiError(err.to_string()))?
+                .into_inner()
+                .iter()
+                .map(|module| {
+                    CompiledModule::deserialize(module.bytecode.inner())
+                        .map_err(|err| CliError::UnableToParse("Module", err.to_string()))
+                })
+                .collect::<CliTypedResult<Vec<_>>>()?
+        } else {
+            let build_config = BuildConfig {
+                additional_named_addresses: self.move_options.named_addresses(),
+                install_dir: self.move_options.output_dir.clone(),
+                ..Default::default()
+            };
+            compile_move(build_config, self.move_options.package_dir.as_path())?
+                .root_modules_map()
+                .iter_modules()
+                .into_iter()
+                .cloned()
+                .collect()
+        };
+
+        let mut bindings = Vec::new();
+        transaction_builder_generator::bindings::output(&mut bindings, &modules)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        self.save_file.save_to_file("Bindings", &bindings)?;
+        Ok(modules
+            .iter()
+            .map(|module| module.self_id().to_string())
+            .collect())
+    }
+}
+
 /// Run Move unit tests against a package path
 #[derive(Parser)]
 pub struct TestPackage {
@@ -233,14 +304,10 @@ fn compile_move(build_config: BuildConfig, package_dir: &Path) -> CliTypedResult
 /// Publishes the modules in a Move package
 #[derive(Parser)]
 pub struct PublishPackage {
-    #[clap(flatten)]
-    encoding_options: EncodingOptions,
     #[clap(flatten)]
     move_options: MovePackageDir,
     #[clap(flatten)]
-    write_options: WriteTransactionOptions,
-    #[clap(flatten)]
-    profile_options: ProfileOptions,
+    txn_options: TransactionOptions,
 }
 
 #[async_trait]
@@ -270,24 +337,10 @@ impl CliCommand<TransactionSummary> for PublishPackage {
         let compiled_payload = TransactionPayload::ModuleBundle(ModuleBundle::new(compiled_units));
 
         // Now that it's compiled, lets send it
-        let sender_key = self.write_options.private_key_options.extract_private_key(
-            self.encoding_options.encoding,
-            &self.profile_options.profile,
-        )?;
-
-        submit_transaction(
-            self.write_options
-                .rest_options
-                .url(&self.profile_options.profile)?,
-            self.write_options
-                .chain_id(&self.profile_options.profile)
-                .await?,
-            sender_key,
-            compiled_payload,
-            self.write_options.max_gas,
-        )
-        .await
-        .map(TransactionSummary::from)
+        self.txn_options
+            .submit_transaction(compiled_payload)
+            .await
+            .map(TransactionSummary::from)
     }
 }
 
@@ -295,11 +348,28 @@ impl CliCommand<TransactionSummary> for PublishPackage {
 #[derive(Parser)]
 pub struct RunFunction {
     #[clap(flatten)]
-    encoding_options: EncodingOptions,
+    txn_options: TransactionOptions,
     #[clap(flatten)]
-    write_options: WriteTransactionOptions,
-    #[clap(flatten)]
-    profile_options: ProfileOptions,
+    function_options: FunctionCallOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for RunFunction {
+    fn command_name(&self) -> &'static str {
+        "RunFunction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        self.txn_options
+            .submit_transaction(self.function_options.payload()?)
+            .await
+            .map(TransactionSummary::from)
+    }
+}
+
+/// Options for calling a Move function in a transaction
+#[derive(Debug, Parser)]
+pub struct FunctionCallOptions {
     /// Function name as `<ADDRESS>::<MODULE_ID>::<FUNCTION_NAME>`
     ///
     /// Example: `0x842ed41fad9640a2ad08fdd7d3e4f7f505319aac7d67e1c0dd6a7cce8732c7e3::Message::set_message`
@@ -317,13 +387,9 @@ pub struct RunFunction {
     type_args: Vec<MoveType>,
 }
 
-#[async_trait]
-impl CliCommand<TransactionSummary> for RunFunction {
-    fn command_name(&self) -> &'static str {
-        "RunFunction"
-    }
-
-    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+impl FunctionCallOptions {
+    /// Builds the transaction payload calling the function
+    pub fn payload(&self) -> CliTypedResult<TransactionPayload> {
         let args: Vec<Vec<u8>> = self
             .args
             .iter()
@@ -344,23 +410,7 @@ impl CliCommand<TransactionSummary> for RunFunction {
             type_args,
             args,
         );
-
-        submit_transaction(
-            self.write_options
-                .rest_options
-                .url(&self.profile_options.profile)?,
-            self.write_options
-                .chain_id(&self.profile_options.profile)
-                .await?,
-            self.write_options.private_key_options.extract_private_key(
-                self.encoding_options.encoding,
-                &self.profile_options.profile,
-            )?,
-            TransactionPayload::ScriptFunction(script_function),
-            self.write_options.max_gas,
-        )
-        .await
-        .map(TransactionSummary::from)
+        Ok(TransactionPayload::ScriptFunction(script_function))
     }
 }
 
@@ -423,6 +473,7 @@ impl FromStr for FunctionArgType {
 }
 
 /// A parseable arg with a type separated by a colon
+#[derive(Debug)]
 pub struct ArgWithType {
     _ty: FunctionArgType,
     arg: Vec<u8>,
@@ -447,6 +498,7 @@ impl FromStr for ArgWithType {
     }
 }
 
+#[derive(Debug)]
 pub struct FunctionId {
     pub module_id: ModuleId,
     pub function_id: Identifier,
@@ -0,0 +1,515 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            CliCommand, CliError, CliResult, CliTypedResult, ProfileOptions, PromptOptions,
+            RestOptions, TransactionOptions, TransactionSummary,
+        },
+        utils::prompt_yes_with_override,
+    },
+    genesis::config::HostAndPort,
+};
+use aptos_config::config::NodeConfig;
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    x25519, ValidCryptoMaterialStringExt,
+};
+use aptos_faucet::Service;
+use aptos_rest_client::{
+    aptos_api_types::{Address, HexEncodedBytes, U64},
+    Client,
+};
+use aptos_sdk::types::LocalAccount;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_address::AccountAddress, account_config::aptos_root_address, chain_id::ChainId,
+    network_address::NetworkAddress,
+};
+use async_trait::async_trait;
+use cached_framework_packages::aptos_stdlib;
+use clap::Parser;
+use hex::FromHex;
+use rand::{rngs::StdRng, SeedableRng};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+const MINT_KEY_FILE: &str = "mint.key";
+const NODE_READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Tool for operations related to nodes
+///
+#[derive(Parser)]
+pub enum NodeTool {
+    JoinValidatorSet(JoinValidatorSet),
+    LeaveValidatorSet(LeaveValidatorSet),
+    RegisterValidatorCandidate(RegisterValidatorCandidate),
+    RotateConsensusKey(RotateConsensusKey),
+    RunLocalTestnet(RunLocalTestnet),
+    ShowValidatorSet(ShowValidatorSet),
+    UpdateValidatorNetworkAddresses(UpdateValidatorNetworkAddresses),
+}
+
+impl NodeTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            NodeTool::JoinValidatorSet(tool) => tool.execute_serialized().await,
+            NodeTool::LeaveValidatorSet(tool) => tool.execute_serialized().await,
+            NodeTool::RegisterValidatorCandidate(tool) => tool.execute_serialized().await,
+            NodeTool::RotateConsensusKey(tool) => tool.execute_serialized().await,
+            NodeTool::RunLocalTestnet(tool) => tool.execute_serialized_success().await,
+            NodeTool::ShowValidatorSet(tool) => tool.execute_serialized().await,
+            NodeTool::UpdateValidatorNetworkAddresses(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// Network addresses of a validator and of its full nodes
+#[derive(Parser)]
+pub struct ValidatorNetworkAddressesOptions {
+    /// Host and port pair for the validator e.g. 127.0.0.1:6180
+    #[clap(long)]
+    validator_host: HostAndPort,
+    /// x25519 public key of the validator network identity, hex encoded
+    #[clap(long, parse(try_from_str = x25519::PublicKey::from_encoded_string))]
+    validator_network_public_key: x25519::PublicKey,
+    /// Host and port pair for the fullnode e.g. 127.0.0.1:6182
+    #[clap(long)]
+    full_node_host: Option<HostAndPort>,
+    /// x25519 public key of the full node network identity, hex encoded
+    #[clap(long, parse(try_from_str = x25519::PublicKey::from_encoded_string))]
+    full_node_network_public_key: Option<x25519::PublicKey>,
+}
+
+impl ValidatorNetworkAddressesOptions {
+    /// Returns the BCS encoded validator and full node network addresses, as stored on chain
+    fn encode(&self) -> CliTypedResult<(Vec<u8>, Vec<u8>)> {
+        let validator_addresses = vec![self
+            .validator_host
+            .as_network_address(self.validator_network_public_key)?];
+        let full_node_addresses = match (&self.full_node_host, self.full_node_network_public_key) {
+            (Some(host), Some(key)) => vec![host.as_network_address(key)?],
+            (None, None) => vec![],
+            _ => {
+                return Err(CliError::CommandArgumentError(
+                    "--full-node-host and --full-node-network-public-key must be used together"
+                        .to_string(),
+                ))
+            }
+        };
+        Ok((
+            bcs::to_bytes(&validator_addresses)
+                .map_err(|err| CliError::BCS("validator network addresses", err))?,
+            bcs::to_bytes(&full_node_addresses)
+                .map_err(|err| CliError::BCS("full node network addresses", err))?,
+        ))
+    }
+}
+
+/// Register the account as a validator candidate, with its consensus key and network addresses
+///
+/// The account can join the validator set once it has enough stake.
+#[derive(Parser)]
+pub struct RegisterValidatorCandidate {
+    #[clap(flatten)]
+    txn_options: TransactionOptions,
+    /// Ed25519 public key of the validator consensus key, hex encoded
+    #[clap(long, parse(try_from_str = Ed25519PublicKey::from_encoded_string))]
+    consensus_public_key: Ed25519PublicKey,
+    #[clap(flatten)]
+    network_addresses: ValidatorNetworkAddressesOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for RegisterValidatorCandidate {
+    fn command_name(&self) -> &'static str {
+        "RegisterValidatorCandidate"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let (validator_addresses, full_node_addresses) = self.network_addresses.encode()?;
+        self.txn_options
+            .submit_transaction(aptos_stdlib::encode_stake_register_validator_candidate(
+                self.consensus_public_key.to_bytes().to_vec(),
+                validator_addresses,
+                full_node_addresses,
+            ))
+            .await
+            .map(TransactionSummary::from)
+    }
+}
+
+/// Join the validator set from the next epoch
+///
+/// The account must be a validator candidate with enough stake.
+#[derive(Parser)]
+pub struct JoinValidatorSet {
+    #[clap(flatten)]
+    txn_options: TransactionOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for JoinValidatorSet {
+    fn command_name(&self) -> &'static str {
+        "JoinValidatorSet"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        self.txn_options
+            .submit_transaction(aptos_stdlib::encode_stake_join_validator_set())
+            .await
+            .map(TransactionSummary::from)
+    }
+}
+
+/// Leave the validator set from the next epoch
+#[derive(Parser)]
+pub struct LeaveValidatorSet {
+    #[clap(flatten)]
+    txn_options: TransactionOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for LeaveValidatorSet {
+    fn command_name(&self) -> &'static str {
+        "LeaveValidatorSet"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        self.txn_options
+            .submit_transaction(aptos_stdlib::encode_stake_leave_validator_set())
+            .await
+            .map(TransactionSummary::from)
+    }
+}
+
+/// Rotate the consensus key of the validator, from the next epoch
+///
+/// The validator must be configured with the new consensus private key by the next epoch.
+#[derive(Parser)]
+pub struct RotateConsensusKey {
+    #[clap(flatten)]
+    txn_options: TransactionOptions,
+    /// Ed25519 public key of the new validator consensus key, hex encoded
+    #[clap(long, parse(try_from_str = Ed25519PublicKey::from_encoded_string))]
+    consensus_public_key: Ed25519PublicKey,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for RotateConsensusKey {
+    fn command_name(&self) -> &'static str {
+        "RotateConsensusKey"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        self.txn_options
+            .submit_transaction(aptos_stdlib::encode_stake_rotate_consensus_key(
+                self.consensus_public_key.to_bytes().to_vec(),
+            ))
+            .await
+            .map(TransactionSummary::from)
+    }
+}
+
+/// Update the network addresses of the validator and its full nodes, from the next epoch
+///
+/// The network keys are rotated by updating the addresses with the new network public keys.
+#[derive(Parser)]
+pub struct UpdateValidatorNetworkAddresses {
+    #[clap(flatten)]
+    txn_options: TransactionOptions,
+    #[clap(flatten)]
+    network_addresses: ValidatorNetworkAddressesOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for UpdateValidatorNetworkAddresses {
+    fn command_name(&self) -> &'static str {
+        "UpdateValidatorNetworkAddresses"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let (validator_addresses, full_node_addresses) = self.network_addresses.encode()?;
+        self.txn_options
+            .submit_transaction(
+                aptos_stdlib::encode_stake_update_network_and_fullnode_addresses(
+                    validator_addresses,
+                    full_node_addresses,
+                ),
+            )
+            .await
+            .map(TransactionSummary::from)
+    }
+}
+
+/// Show the validators of the current epoch, and the ones joining or leaving in the next epoch
+#[derive(Parser)]
+pub struct ShowValidatorSet {
+    #[clap(flatten)]
+    rest_options: RestOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<ValidatorSetSummary> for ShowValidatorSet {
+    fn command_name(&self) -> &'static str {
+        "ShowValidatorSet"
+    }
+
+    async fn execute(self) -> CliTypedResult<ValidatorSetSummary> {
+        let client = Client::new(self.rest_options.url(&self.profile_options.profile)?);
+        let validator_set: ValidatorSet = client
+            .get_resource(aptos_root_address(), "0x1::Stake::ValidatorSet")
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner();
+        ValidatorSetSummary::new(validator_set)
+    }
+}
+
+/// `0x1::Stake::ValidatorSet` resource
+#[derive(Deserialize)]
+struct ValidatorSet {
+    consensus_scheme: u8,
+    minimum_stake: U64,
+    maximum_stake: U64,
+    active_validators: Vec<ValidatorInfo>,
+    pending_inactive: Vec<ValidatorInfo>,
+    pending_active: Vec<ValidatorInfo>,
+}
+
+/// `0x1::Stake::ValidatorInfo` struct
+#[derive(Deserialize)]
+struct ValidatorInfo {
+    addr: Address,
+    voting_power: U64,
+    config: ValidatorConfig,
+}
+
+/// `0x1::Stake::ValidatorConfig` struct
+#[derive(Deserialize)]
+struct ValidatorConfig {
+    consensus_pubkey: HexEncodedBytes,
+    network_address: HexEncodedBytes,
+    fullnode_address: HexEncodedBytes,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidatorSetSummary {
+    pub consensus_scheme: u8,
+    pub minimum_stake: u64,
+    pub maximum_stake: u64,
+    pub active_validators: Vec<ValidatorSummary>,
+    pub pending_inactive: Vec<ValidatorSummary>,
+    pub pending_active: Vec<ValidatorSummary>,
+}
+
+impl ValidatorSetSummary {
+    fn new(set: ValidatorSet) -> CliTypedResult<Self> {
+        let summarize = |validators: Vec<ValidatorInfo>| {
+            validators
+                .into_iter()
+                .map(ValidatorSummary::new)
+                .collect::<CliTypedResult<Vec<_>>>()
+        };
+        Ok(ValidatorSetSummary {
+            consensus_scheme: set.consensus_scheme,
+            minimum_stake: *set.minimum_stake.inner(),
+            maximum_stake: *set.maximum_stake.inner(),
+            active_validators: summarize(set.active_validators)?,
+            pending_inactive: summarize(set.pending_inactive)?,
+            pending_active: summarize(set.pending_active)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidatorSummary {
+    pub account_address: AccountAddress,
+    pub voting_power: u64,
+    pub consensus_public_key: String,
+    pub validator_network_addresses: Vec<NetworkAddress>,
+    pub full_node_network_addresses: Vec<NetworkAddress>,
+}
+
+impl ValidatorSummary {
+    fn new(info: ValidatorInfo) -> CliTypedResult<Self> {
+        let decode_addresses = |name: &'static str, bytes: HexEncodedBytes| {
+            let bytes: Vec<u8> = bytes.into();
+            // Validators registered without addresses, e.g. in tests, have no encoded addresses.
+            if bytes.is_empty() {
+                return Ok(vec![]);
+            }
+            bcs::from_bytes(&bytes).map_err(|err| CliError::BCS(name, err))
+        };
+        Ok(ValidatorSummary {
+            account_address: info.addr.into(),
+            voting_power: *info.voting_power.inner(),
+            consensus_public_key: info.config.consensus_pubkey.to_string(),
+            validator_network_addresses: decode_addresses(
+                "validator network addresses",
+                info.config.network_address,
+            )?,
+            full_node_network_addresses: decode_addresses(
+                "full node network addresses",
+                info.config.fullnode_address,
+            )?,
+        })
+    }
+}
+
+/// Run a local testnet of a single validator, for development
+///
+/// The validator is started in process with its REST API enabled, from a genesis built with the
+/// cached Move framework. The testnet runs until ctrl-c is pressed.
+#[derive(Parser)]
+pub struct RunLocalTestnet {
+    /// Directory of the testnet configs and data, kept across runs
+    ///
+    /// The testnet is resumed if the directory already holds one. Defaults to a temporary
+    /// directory, removed on exit.
+    #[clap(long, parse(from_os_str))]
+    test_dir: Option<PathBuf>,
+    /// Remove the testnet in `test_dir` first, to start over from genesis
+    #[clap(long, requires = "test-dir")]
+    force_restart: bool,
+    /// Seed of the keys of the testnet, as 32 hex encoded bytes
+    #[clap(long, parse(try_from_str = FromHex::from_hex))]
+    seed: Option<[u8; 32]>,
+    /// Only commit blocks when there are transactions in mempool
+    #[clap(long)]
+    lazy: bool,
+    /// Also run a faucet, minting coins from the aptos root account
+    #[clap(long)]
+    with_faucet: bool,
+    /// Port of the faucet
+    #[clap(long, default_value = "8081")]
+    faucet_port: u16,
+    #[clap(flatten)]
+    prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<()> for RunLocalTestnet {
+    fn command_name(&self) -> &'static str {
+        "RunLocalTestnet"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        // Kept until exit when the testnet is ephemeral, so the testnet is removed on exit.
+        let temp_dir = TempPath::new();
+        let test_dir = match self.test_dir {
+            Some(test_dir) => {
+                if self.force_restart && test_dir.exists() {
+                    prompt_yes_with_override(
+                        &format!(
+                            "Are you sure you want to delete the testnet in {:?}?",
+                            test_dir.as_os_str()
+                        ),
+                        self.prompt_options,
+                    )?;
+                    std::fs::remove_dir_all(&test_dir)
+                        .map_err(|err| CliError::IO(test_dir.display().to_string(), err))?;
+                }
+                test_dir
+            }
+            None => temp_dir.path().to_path_buf(),
+        };
+
+        let rng = self
+            .seed
+            .map(StdRng::from_seed)
+            .unwrap_or_else(StdRng::from_entropy);
+        let config = aptos_node::create_single_node_test_config(
+            &test_dir,
+            false,
+            self.lazy,
+            cached_framework_packages::module_blobs().to_vec(),
+            rng,
+        )
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to create testnet: {}", err)))?;
+
+        let rest_url = Url::parse(&format!(
+            "http://{}:{}",
+            Ipv4Addr::LOCALHOST,
+            config.api.address.port()
+        ))
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        start_node(config);
+        wait_for_node(&rest_url).await?;
+        eprintln!("Testnet directory: {:?}", test_dir.as_os_str());
+        eprintln!("REST API endpoint: {}", rest_url);
+        eprintln!("ChainId: {}", ChainId::test());
+
+        if self.with_faucet {
+            let faucet_address = start_faucet(&test_dir, &rest_url, self.faucet_port)?;
+            eprintln!("Faucet endpoint: http://{}", faucet_address);
+        }
+
+        eprintln!("\nAptos is running, press ctrl-c to exit\n");
+        tokio::signal::ctrl_c()
+            .await
+            .map_err(|err| CliError::IO("ctrl-c signal".to_string(), err))
+    }
+}
+
+/// Runs the validator in its own thread, as it manages its own runtimes.
+fn start_node(config: NodeConfig) {
+    thread::spawn(move || {
+        let _node_handle = aptos_node::setup_environment(&config, None);
+        loop {
+            thread::park();
+        }
+    });
+}
+
+/// Waits until the REST API of the validator serves the ledger info.
+async fn wait_for_node(rest_url: &Url) -> CliTypedResult<()> {
+    let client = Client::new(rest_url.clone());
+    let start = Instant::now();
+    while client.get_ledger_information().await.is_err() {
+        if start.elapsed() > NODE_READY_TIMEOUT {
+            return Err(CliError::UnexpectedError(format!(
+                "Validator REST API at {} is not up after {} seconds",
+                rest_url,
+                NODE_READY_TIMEOUT.as_secs()
+            )));
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    Ok(())
+}
+
+/// Serves a faucet minting from the aptos root account of the testnet, whose key is in the
+/// testnet directory.
+fn start_faucet(test_dir: &Path, rest_url: &Url, port: u16) -> CliTypedResult<SocketAddr> {
+    let mint_key_path = test_dir.join(MINT_KEY_FILE);
+    let mint_key = std::fs::read(&mint_key_path).map_err(|err| {
+        CliError::UnableToReadFile(mint_key_path.display().to_string(), err.to_string())
+    })?;
+    let mint_key: Ed25519PrivateKey =
+        bcs::from_bytes(&mint_key).map_err(|err| CliError::BCS(MINT_KEY_FILE, err))?;
+
+    // The faucet catches up with the on-chain sequence number of the account by itself.
+    let faucet_account = LocalAccount::new(aptos_root_address(), mint_key, 0);
+    let service = Arc::new(Service::new(
+        rest_url.to_string(),
+        ChainId::test(),
+        faucet_account,
+        None,
+    ));
+    let (address, server) = warp::serve(aptos_faucet::routes(service))
+        .try_bind_ephemeral((Ipv4Addr::LOCALHOST, port))
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to start faucet: {}", err)))?;
+    tokio::spawn(server);
+    Ok(address)
+}
@@ -0,0 +1,113 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{
+    CliCommand, CliResult, CliTypedResult, TransactionOptions, TransactionSummary,
+};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use cached_framework_packages::aptos_stdlib;
+use clap::Parser;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Minimum lockup period of a stake, as enforced by the `Stake` module
+const MINIMUM_LOCKUP_SECS: u64 = 86400;
+
+/// Tool for manipulating stake
+///
+#[derive(Parser)]
+pub enum StakeTool {
+    AddStake(AddStake),
+    UnlockStake(UnlockStake),
+}
+
+impl StakeTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            StakeTool::AddStake(tool) => tool.execute_serialized().await,
+            StakeTool::UnlockStake(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// Stake coins with a validator
+///
+/// The stake adds to the voting power of the validator from the next epoch if the validator is in
+/// the validator set, and immediately otherwise.
+#[derive(Parser)]
+pub struct AddStake {
+    #[clap(flatten)]
+    txn_options: TransactionOptions,
+    /// Address of the validator to stake with
+    ///
+    /// Defaults to the account of the private key
+    #[clap(long, parse(try_from_str = crate::common::types::load_account_arg))]
+    validator_address: Option<AccountAddress>,
+    /// Amount of coins to stake
+    #[clap(long)]
+    amount: u64,
+    /// Number of seconds the stake is locked up for
+    ///
+    /// Must be more than one day
+    #[clap(long, default_value_t = 2 * MINIMUM_LOCKUP_SECS)]
+    lockup_secs: u64,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for AddStake {
+    fn command_name(&self) -> &'static str {
+        "AddStake"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let validator_address = match self.validator_address {
+            Some(validator_address) => validator_address,
+            None => self.txn_options.sender_address()?,
+        };
+        let now_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Current time is before the unix epoch")
+            .as_secs();
+        self.txn_options
+            .submit_transaction(aptos_stdlib::encode_stake_delegate_stake(
+                validator_address,
+                self.amount,
+                now_secs + self.lockup_secs,
+            ))
+            .await
+            .map(TransactionSummary::from)
+    }
+}
+
+/// Unlock the stake of the account with a validator
+///
+/// The stake can be withdrawn from the next epoch if the validator is in the validator set, and
+/// immediately otherwise.
+#[derive(Parser)]
+pub struct UnlockStake {
+    #[clap(flatten)]
+    txn_options: TransactionOptions,
+    /// Address of the validator the coins are staked with
+    ///
+    /// Defaults to the account of the private key
+    #[clap(long, parse(try_from_str = crate::common::types::load_account_arg))]
+    validator_address: Option<AccountAddress>,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for UnlockStake {
+    fn command_name(&self) -> &'static str {
+        "UnlockStake"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let validator_address = match self.validator_address {
+            Some(validator_address) => validator_address,
+            None => self.txn_options.sender_address()?,
+        };
+        self.txn_options
+            .submit_transaction(aptos_stdlib::encode_stake_unlock(validator_address))
+            .await
+            .map(TransactionSummary::from)
+    }
+}
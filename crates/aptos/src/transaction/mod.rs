@@ -0,0 +1,274 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            load_account_arg, CliCommand, CliError, CliResult, CliTypedResult, EncodingOptions,
+            PrivateKeyInputOptions, ProfileOptions, RestOptions, SaveFile, TransactionSummary,
+        },
+        utils::{get_chain_id, read_from_file},
+    },
+    move_tool::FunctionCallOptions,
+};
+use aptos_crypto::PrivateKey;
+use aptos_rest_client::Client;
+use aptos_sdk::transaction_builder::TransactionFactory;
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{RawTransaction, SignedTransaction},
+};
+use async_trait::async_trait;
+use clap::{ArgEnum, Parser, Subcommand};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Default number of seconds until a built transaction expires
+const DEFAULT_EXPIRATION_SECS: u64 = 3600;
+
+/// Tool for building, signing and submitting transactions as separate steps
+///
+/// This allows keys to be kept on a machine without network access: the transaction is built on
+/// a networked machine, signed on the offline machine, and submitted from the networked machine.
+#[derive(Debug, Subcommand)]
+pub enum TransactionTool {
+    Build(BuildTransaction),
+    Sign(SignTransaction),
+    Submit(SubmitTransaction),
+}
+
+impl TransactionTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            TransactionTool::Build(tool) => tool.execute_serialized().await,
+            TransactionTool::Sign(tool) => tool.execute_serialized().await,
+            TransactionTool::Submit(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// Formats of transaction files
+#[derive(ArgEnum, Clone, Copy, Debug)]
+pub enum TransactionFileFormat {
+    /// Binary Canonical Serialization
+    BCS,
+    /// JSON, for inspecting the transaction
+    Json,
+}
+
+impl TransactionFileFormat {
+    fn encode<T: Serialize>(&self, name: &'static str, value: &T) -> CliTypedResult<Vec<u8>> {
+        match self {
+            TransactionFileFormat::BCS => {
+                bcs::to_bytes(value).map_err(|err| CliError::BCS(name, err))
+            }
+            TransactionFileFormat::Json => serde_json::to_vec_pretty(value)
+                .map_err(|err| CliError::UnexpectedError(err.to_string())),
+        }
+    }
+
+    fn load<T: DeserializeOwned>(&self, name: &'static str, path: &Path) -> CliTypedResult<T> {
+        let bytes = read_from_file(path)?;
+        match self {
+            TransactionFileFormat::BCS => {
+                bcs::from_bytes(&bytes).map_err(|err| CliError::BCS(name, err))
+            }
+            TransactionFileFormat::Json => serde_json::from_slice(&bytes)
+                .map_err(|err| CliError::UnableToParse(name, err.to_string())),
+        }
+    }
+}
+
+impl FromStr for TransactionFileFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bcs" => Ok(TransactionFileFormat::BCS),
+            "json" => Ok(TransactionFileFormat::Json),
+            _ => Err("Invalid transaction file format"),
+        }
+    }
+}
+
+/// An insertable option for the format of transaction files
+#[derive(Debug, Parser)]
+pub struct TransactionFileFormatOptions {
+    /// Format of transaction files as `bcs` or `json`
+    #[clap(long, default_value = "bcs")]
+    pub format: TransactionFileFormat,
+}
+
+/// Build an unsigned transaction calling a Move function
+///
+/// The raw transaction is saved to `output_file`, to be signed with `aptos transaction sign`.
+/// This doesn't need network access if `--chain-id` is given.
+#[derive(Debug, Parser)]
+pub struct BuildTransaction {
+    /// Address of the account sending the transaction
+    #[clap(long, parse(try_from_str = load_account_arg))]
+    sender: AccountAddress,
+    /// Sequence number of the transaction
+    ///
+    /// This must be the next sequence number of the sender on-chain when the transaction is
+    /// submitted
+    #[clap(long)]
+    sequence_number: u64,
+    /// Expiration time of the transaction, in seconds since the unix epoch
+    ///
+    /// Defaults to one hour from now
+    #[clap(long)]
+    expiration_timestamp_secs: Option<u64>,
+    /// Chain id of the network the transaction is for
+    ///
+    /// Defaults to the chain id of the network at `url`
+    #[clap(long)]
+    chain_id: Option<ChainId>,
+    /// Maximum gas to be used by the transaction
+    #[clap(long, default_value_t = 1000)]
+    max_gas: u64,
+    /// Price of each unit of gas
+    #[clap(long, default_value_t = 1)]
+    gas_unit_price: u64,
+    #[clap(flatten)]
+    function_options: FunctionCallOptions,
+    #[clap(flatten)]
+    format_options: TransactionFileFormatOptions,
+    #[clap(flatten)]
+    rest_options: RestOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+    #[clap(flatten)]
+    save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<RawTransaction> for BuildTransaction {
+    fn command_name(&self) -> &'static str {
+        "BuildTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<RawTransaction> {
+        self.save_file.check_file()?;
+
+        let chain_id = match self.chain_id {
+            Some(chain_id) => chain_id,
+            None => {
+                get_chain_id(&Client::new(
+                    self.rest_options.url(&self.profile_options.profile)?,
+                ))
+                .await?
+            }
+        };
+        let transaction_factory = TransactionFactory::new(chain_id)
+            .with_gas_unit_price(self.gas_unit_price)
+            .with_max_gas_amount(self.max_gas)
+            .with_transaction_expiration_time(DEFAULT_EXPIRATION_SECS);
+        let mut builder = transaction_factory
+            .payload(self.function_options.payload()?)
+            .sender(self.sender)
+            .sequence_number(self.sequence_number);
+        if let Some(expiration_timestamp_secs) = self.expiration_timestamp_secs {
+            builder = builder.expiration_timestamp_secs(expiration_timestamp_secs);
+        }
+        let raw_txn = builder.build();
+
+        self.save_file.save_to_file(
+            "Raw transaction",
+            &self
+                .format_options
+                .format
+                .encode("Raw transaction", &raw_txn)?,
+        )?;
+        Ok(raw_txn)
+    }
+}
+
+/// Sign a transaction built by `aptos transaction build`
+///
+/// This doesn't need network access.  The signed transaction is saved to `output_file`, in the
+/// same format as the raw transaction, to be submitted with `aptos transaction submit`.  The
+/// transaction is returned so that it can be reviewed.
+#[derive(Debug, Parser)]
+pub struct SignTransaction {
+    /// Raw transaction file, from `aptos transaction build`
+    #[clap(long, parse(from_os_str))]
+    raw_txn_file: PathBuf,
+    #[clap(flatten)]
+    format_options: TransactionFileFormatOptions,
+    #[clap(flatten)]
+    private_key_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+    #[clap(flatten)]
+    save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<RawTransaction> for SignTransaction {
+    fn command_name(&self) -> &'static str {
+        "SignTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<RawTransaction> {
+        self.save_file.check_file()?;
+
+        let format = self.format_options.format;
+        let raw_txn: RawTransaction = format.load("Raw transaction", &self.raw_txn_file)?;
+        let private_key = self.private_key_options.extract_private_key(
+            self.encoding_options.encoding,
+            &self.profile_options.profile,
+        )?;
+        let signed_txn = raw_txn
+            .clone()
+            .sign(&private_key, private_key.public_key())
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+            .into_inner();
+
+        self.save_file.save_to_file(
+            "Signed transaction",
+            &format.encode("Signed transaction", &signed_txn)?,
+        )?;
+        Ok(raw_txn)
+    }
+}
+
+/// Submit a transaction signed by `aptos transaction sign`, and wait for its execution
+#[derive(Debug, Parser)]
+pub struct SubmitTransaction {
+    /// Signed transaction file, from `aptos transaction sign`
+    #[clap(long, parse(from_os_str))]
+    signed_txn_file: PathBuf,
+    #[clap(flatten)]
+    format_options: TransactionFileFormatOptions,
+    #[clap(flatten)]
+    rest_options: RestOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for SubmitTransaction {
+    fn command_name(&self) -> &'static str {
+        "SubmitTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let signed_txn: SignedTransaction = self
+            .format_options
+            .format
+            .load("Signed transaction", &self.signed_txn_file)?;
+        let client = Client::new(self.rest_options.url(&self.profile_options.profile)?);
+        client
+            .submit_and_wait(&signed_txn)
+            .await
+            .map(|response| TransactionSummary::from(response.into_inner()))
+            .map_err(|err| CliError::ApiError(err.to_string()))
+    }
+}
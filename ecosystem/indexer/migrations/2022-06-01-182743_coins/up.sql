@@ -0,0 +1,38 @@
+-- Your SQL goes here
+CREATE TABLE coin_infos
+(
+    coin_type VARCHAR NOT NULL,
+    transaction_version BIGINT NOT NULL,
+    creator_address VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    symbol VARCHAR NOT NULL,
+    decimals BIGINT NOT NULL,
+    supply NUMERIC,
+    inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (coin_type, transaction_version)
+);
+
+CREATE TABLE coin_balances
+(
+    owner_address VARCHAR NOT NULL,
+    coin_type VARCHAR NOT NULL,
+    transaction_version BIGINT NOT NULL,
+    amount NUMERIC NOT NULL,
+    inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (owner_address, coin_type, transaction_version)
+);
+CREATE INDEX cb_coin_type_version_index ON coin_balances (coin_type, transaction_version);
+
+CREATE TABLE coin_activities
+(
+    event_key VARCHAR NOT NULL,
+    sequence_number BIGINT NOT NULL,
+    transaction_version BIGINT NOT NULL,
+    owner_address VARCHAR NOT NULL,
+    coin_type VARCHAR NOT NULL,
+    activity_type VARCHAR NOT NULL,
+    amount NUMERIC NOT NULL,
+    inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (event_key, sequence_number)
+);
+CREATE INDEX ca_owner_coin_type_index ON coin_activities (owner_address, coin_type);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE token_mints
+(
+    event_key VARCHAR NOT NULL,
+    sequence_number BIGINT NOT NULL,
+    token_id VARCHAR NOT NULL,
+    amount BIGINT NOT NULL,
+    transaction_hash VARCHAR NOT NULL,
+    inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (event_key, sequence_number)
+);
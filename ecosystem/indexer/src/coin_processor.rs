@@ -0,0 +1,78 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    indexer::{
+        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        transaction_processor::TransactionProcessor,
+    },
+    models::{coin::CoinRecords, transactions::TransactionModel},
+    storage::{IndexerStorage, Record},
+};
+use aptos_rest_client::Transaction;
+use async_trait::async_trait;
+use std::{fmt::Debug, sync::Arc};
+
+/// Indexes the coins of `Coin.move`: the `CoinInfo<T>` of each coin type, the `CoinStore<T>`
+/// balance of each account after each transaction changing it, and their deposits and withdrawals.
+#[derive(Debug)]
+pub struct CoinTransactionProcessor {
+    storage: Arc<dyn IndexerStorage>,
+}
+
+impl CoinTransactionProcessor {
+    pub fn new(storage: Arc<dyn IndexerStorage>) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait]
+impl TransactionProcessor for CoinTransactionProcessor {
+    fn name(&self) -> &'static str {
+        "coin_processor"
+    }
+
+    async fn process_transaction(
+        &self,
+        transaction: Arc<Transaction>,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let version = transaction.version().unwrap_or(0);
+
+        let (transaction_model, _, maybe_events, maybe_write_set_changes) =
+            TransactionModel::from_transaction(&transaction);
+
+        let tx_result = CoinRecords::from_transaction(
+            transaction_model.version,
+            maybe_write_set_changes.as_deref().unwrap_or_default(),
+            maybe_events.as_deref().unwrap_or_default(),
+        )
+        .and_then(|coin_records| {
+            let records: Vec<_> = coin_records
+                .infos
+                .into_iter()
+                .map(Record::CoinInfo)
+                .chain(coin_records.balances.into_iter().map(Record::CoinBalance))
+                .chain(
+                    coin_records
+                        .activities
+                        .into_iter()
+                        .map(Record::CoinActivity),
+                )
+                .collect();
+            self.storage.write_records(&records)
+        });
+
+        match tx_result {
+            Ok(_) => Ok(ProcessingResult::new(self.name(), version)),
+            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+                err,
+                version,
+                self.name(),
+            ))),
+        }
+    }
+
+    fn storage(&self) -> &dyn IndexerStorage {
+        self.storage.as_ref()
+    }
+}
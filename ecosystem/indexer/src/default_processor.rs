@@ -2,126 +2,29 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
     indexer::{
         errors::TransactionProcessingError, processing_result::ProcessingResult,
         transaction_processor::TransactionProcessor,
     },
-    models::{
-        events::EventModel,
-        transactions::{BlockMetadataTransactionModel, TransactionModel, UserTransactionModel},
-        write_set_changes::WriteSetChangeModel,
-    },
-    schema,
+    models::transactions::TransactionModel,
+    storage::{IndexerStorage, Record},
 };
 use aptos_rest_client::Transaction;
 use async_trait::async_trait;
-use diesel::Connection;
 use futures::future::Either;
 use std::{fmt::Debug, sync::Arc};
 
+#[derive(Debug)]
 pub struct DefaultTransactionProcessor {
-    connection_pool: PgDbPool,
+    storage: Arc<dyn IndexerStorage>,
 }
 
 impl DefaultTransactionProcessor {
-    pub fn new(connection_pool: PgDbPool) -> Self {
-        Self { connection_pool }
+    pub fn new(storage: Arc<dyn IndexerStorage>) -> Self {
+        Self { storage }
     }
 }
 
-impl Debug for DefaultTransactionProcessor {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let state = &self.connection_pool.state();
-        write!(
-            f,
-            "DefaultTransactionProcessor {{ connections: {:?}  idle_connections: {:?} }}",
-            state.connections, state.idle_connections
-        )
-    }
-}
-
-fn insert_events(conn: &PgPoolConnection, events: &Vec<EventModel>) {
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::events::table)
-            .values(events)
-            .on_conflict_do_nothing(),
-    )
-    .expect("Error inserting row into database");
-}
-
-fn insert_write_set_changes(conn: &PgPoolConnection, write_set_changes: &Vec<WriteSetChangeModel>) {
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::write_set_changes::table)
-            .values(write_set_changes)
-            .on_conflict_do_nothing(),
-    )
-    .expect("Error inserting row into database");
-}
-
-fn insert_transaction(conn: &PgPoolConnection, version: u64, transaction_model: &TransactionModel) {
-    aptos_logger::trace!(
-        "[default_processor] inserting 'transaction' version {} with hash {}",
-        version,
-        transaction_model.hash
-    );
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::transactions::table)
-            .values(transaction_model)
-            .on_conflict(schema::transactions::dsl::hash)
-            .do_update()
-            .set(transaction_model),
-    )
-    .expect("Error inserting row into database");
-}
-
-fn insert_user_transaction(
-    conn: &PgPoolConnection,
-    version: u64,
-    transaction_model: &TransactionModel,
-    user_transaction_model: &UserTransactionModel,
-) {
-    aptos_logger::trace!(
-        "[default_processor] inserting 'user_transaction' version {} with hash {}",
-        version,
-        &transaction_model.hash
-    );
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::user_transactions::table)
-            .values(user_transaction_model)
-            .on_conflict(schema::user_transactions::dsl::hash)
-            .do_update()
-            .set(user_transaction_model),
-    )
-    .expect("Error inserting row into database");
-}
-
-fn insert_block_metadata_transaction(
-    conn: &PgPoolConnection,
-    version: u64,
-    transaction_model: &TransactionModel,
-    block_metadata_transaction_model: &BlockMetadataTransactionModel,
-) {
-    aptos_logger::trace!(
-        "[default_processor] inserting 'block_metadata_transaction' version {} with hash {}",
-        version,
-        &transaction_model.hash
-    );
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::block_metadata_transactions::table)
-            .values(block_metadata_transaction_model)
-            .on_conflict(schema::block_metadata_transactions::dsl::hash)
-            .do_update()
-            .set(block_metadata_transaction_model),
-    )
-    .expect("Error inserting row into database");
-}
-
 #[async_trait]
 impl TransactionProcessor for DefaultTransactionProcessor {
     fn name(&self) -> &'static str {
@@ -137,51 +40,42 @@ impl TransactionProcessor for DefaultTransactionProcessor {
         let (transaction_model, maybe_details_model, maybe_events, maybe_write_set_changes) =
             TransactionModel::from_transaction(&transaction);
 
-        let conn = self.get_conn();
-
-        let tx_result = conn.transaction::<(), diesel::result::Error, _>(|| {
-            insert_transaction(&conn, version, &transaction_model);
-            if let Some(tx_details_model) = maybe_details_model {
-                match tx_details_model {
-                    Either::Left(user_transaction_model) => {
-                        insert_user_transaction(
-                            &conn,
-                            version,
-                            &transaction_model,
-                            &user_transaction_model,
-                        );
-                    }
-                    Either::Right(block_metadata_transaction_model) => {
-                        insert_block_metadata_transaction(
-                            &conn,
-                            version,
-                            &transaction_model,
-                            &block_metadata_transaction_model,
-                        );
-                    }
-                };
-            };
+        aptos_logger::trace!(
+            "[default_processor] processing version {} with hash {}",
+            version,
+            transaction_model.hash
+        );
+        let mut records = vec![Record::Transaction(transaction_model)];
+        if let Some(tx_details_model) = maybe_details_model {
+            records.push(match tx_details_model {
+                Either::Left(user_transaction_model) => {
+                    Record::UserTransaction(user_transaction_model)
+                }
+                Either::Right(block_metadata_transaction_model) => {
+                    Record::BlockMetadataTransaction(block_metadata_transaction_model)
+                }
+            });
+        }
+        if let Some(events) = maybe_events {
+            records.extend(events.into_iter().map(Record::Event));
+        }
+        if let Some(write_set_changes) = maybe_write_set_changes {
+            records.extend(write_set_changes.into_iter().map(Record::WriteSetChange));
+        }
 
-            if let Some(events) = maybe_events {
-                insert_events(&conn, &events);
-            };
-            if let Some(write_set_changes) = maybe_write_set_changes {
-                insert_write_set_changes(&conn, &write_set_changes);
-            };
-            Ok(())
-        });
+        let tx_result = self.storage.write_records(&records);
 
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(self.name(), version)),
             Err(err) => Err(TransactionProcessingError::TransactionCommitError((
-                anyhow::Error::from(err),
+                err,
                 version,
                 self.name(),
             ))),
         }
     }
 
-    fn connection_pool(&self) -> &PgDbPool {
-        &self.connection_pool
+    fn storage(&self) -> &dyn IndexerStorage {
+        self.storage.as_ref()
     }
 }
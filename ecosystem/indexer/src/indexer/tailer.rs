@@ -1,11 +1,11 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    database::PgDbPool,
     indexer::{
         errors::TransactionProcessingError, fetcher::TransactionFetcher,
         processing_result::ProcessingResult, transaction_processor::TransactionProcessor,
     },
+    storage::IndexerStorage,
 };
 use aptos_logger::info;
 use aptos_rest_client::Transaction;
@@ -14,8 +14,6 @@ use std::{fmt::Debug, sync::Arc};
 use tokio::{sync::Mutex, task::JoinHandle};
 use url::{ParseError, Url};
 
-diesel_migrations::embed_migrations!();
-
 pub fn string_null_byte_replacement(value: &mut str) -> String {
     value.replace('\u{0000}', "").replace("\\u0000", "")
 }
@@ -53,31 +51,22 @@ pub fn remove_null_bytes_from_txn(txn: Arc<Transaction>) -> Arc<Transaction> {
 pub struct Tailer {
     transaction_fetcher: Arc<Mutex<TransactionFetcher>>,
     processors: Vec<Arc<dyn TransactionProcessor>>,
-    connection_pool: PgDbPool,
+    storage: Arc<dyn IndexerStorage>,
 }
 
 impl Tailer {
-    pub fn new(node_url: &str, connection_pool: PgDbPool) -> Result<Tailer, ParseError> {
+    pub fn new(node_url: &str, storage: Arc<dyn IndexerStorage>) -> Result<Tailer, ParseError> {
         let url = Url::parse(node_url)?;
         let transaction_fetcher = TransactionFetcher::new(url, None);
         Ok(Self {
             transaction_fetcher: Arc::new(Mutex::new(transaction_fetcher)),
             processors: vec![],
-            connection_pool,
+            storage,
         })
     }
 
     pub fn run_migrations(&self) {
-        info!("Running migrations...");
-        embedded_migrations::run_with_output(
-            &self
-                .connection_pool
-                .get()
-                .expect("Could not get connection for migrations"),
-            &mut std::io::stdout(),
-        )
-        .expect("migrations failed!");
-        info!("Migrations complete!");
+        self.storage.run_migrations().expect("migrations failed!");
     }
 
     pub fn add_processor(&mut self, processor: Arc<dyn TransactionProcessor>) {
@@ -233,9 +222,10 @@ pub async fn await_tasks<T: Debug>(tasks: Vec<JoinHandle<T>>) -> Vec<T> {
 mod test {
     use super::*;
     use crate::{
-        database::{new_db_pool, PgPoolConnection},
+        database::{new_db_pool, PgDbPool, PgPoolConnection},
         default_processor::DefaultTransactionProcessor,
         models::transactions::TransactionModel,
+        storage::PostgresStorage,
         token_processor::TokenTransactionProcessor,
     };
     use diesel::Connection;
@@ -243,6 +233,14 @@ mod test {
 
     pub fn wipe_database(conn: &PgPoolConnection) {
         for table in [
+            "token_mints",
+            "token_activities",
+            "ownerships",
+            "collections",
+            "tokens",
+            "coin_activities",
+            "coin_balances",
+            "coin_infos",
             "write_set_changes",
             "events",
             "user_transactions",
@@ -262,11 +260,12 @@ mod test {
         let conn_pool = new_db_pool(database_url.as_str())?;
         wipe_database(&conn_pool.get()?);
 
-        let mut tailer = Tailer::new("http://fake-url.aptos.dev", conn_pool.clone())?;
+        let storage: Arc<dyn IndexerStorage> = Arc::new(PostgresStorage::new(conn_pool.clone()));
+        let mut tailer = Tailer::new("http://fake-url.aptos.dev", storage.clone())?;
         tailer.run_migrations();
 
-        let pg_transaction_processor = DefaultTransactionProcessor::new(conn_pool.clone());
-        let token_transaction_processor = TokenTransactionProcessor::new(conn_pool.clone());
+        let pg_transaction_processor = DefaultTransactionProcessor::new(storage.clone());
+        let token_transaction_processor = TokenTransactionProcessor::new(storage);
         tailer.add_processor(Arc::new(pg_transaction_processor));
         tailer.add_processor(Arc::new(token_transaction_processor));
         Ok((conn_pool, tailer))
@@ -2,19 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    counters::{
-        GOT_CONNECTION, PROCESSOR_ERRORS, PROCESSOR_INVOCATIONS, PROCESSOR_SUCCESSES,
-        UNABLE_TO_GET_CONNECTION,
-    },
-    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
+    counters::{PROCESSOR_ERRORS, PROCESSOR_INVOCATIONS, PROCESSOR_SUCCESSES},
     indexer::{errors::TransactionProcessingError, processing_result::ProcessingResult},
     models::processor_statuses::ProcessorStatusModel,
-    schema,
+    storage::IndexerStorage,
 };
 use aptos_rest_client::Transaction;
 use async_trait::async_trait;
-use diesel::{prelude::*, RunQueryDsl};
-use schema::processor_statuses::{self, dsl};
 use std::{fmt::Debug, sync::Arc};
 
 /// The `TransactionProcessor` is used by an instance of a `Tailer` to process transactions
@@ -32,34 +26,12 @@ pub trait TransactionProcessor: Send + Sync + Debug {
         transaction: Arc<Transaction>,
     ) -> Result<ProcessingResult, TransactionProcessingError>;
 
-    /// Gets a reference to the connection pool
-    /// This is used by the `get_conn()` helper below
-    fn connection_pool(&self) -> &PgDbPool;
+    /// Gets a reference to the storage the processor writes to
+    /// This is used by the status helpers below
+    fn storage(&self) -> &dyn IndexerStorage;
 
     //* Below are helper methods that don't need to be implemented *//
 
-    /// Gets the connection.
-    /// If it was unable to do so (default timeout: 30s), it will keep retrying until it can.
-    fn get_conn(&self) -> PgPoolConnection {
-        let pool = self.connection_pool();
-        loop {
-            match pool.get() {
-                Ok(conn) => {
-                    GOT_CONNECTION.inc();
-                    return conn;
-                }
-                Err(err) => {
-                    UNABLE_TO_GET_CONNECTION.inc();
-                    aptos_logger::error!(
-                        "Could not get DB connection from pool, will retry in {:?}. Err: {:?}",
-                        pool.connection_timeout(),
-                        err
-                    );
-                }
-            };
-        }
-    }
-
     /// This is a helper method, tying together the other helper methods to allow tracking status in the DB
     async fn process_transaction_with_status(
         &self,
@@ -116,47 +88,24 @@ pub trait TransactionProcessor: Send + Sync + Debug {
 
     /// Actually performs the write for a `ProcessorStatusModel` changeset
     fn apply_processor_status(&self, psm: &ProcessorStatusModel) {
-        let conn = self.get_conn();
-        execute_with_better_error(
-            &conn,
-            diesel::insert_into(processor_statuses::table)
-                .values(psm)
-                .on_conflict((dsl::name, dsl::version))
-                .do_update()
-                .set(psm),
-        )
-        .expect("Error updating Processor Status!");
+        self.storage()
+            .apply_processor_status(psm)
+            .expect("Error updating Processor Status!");
     }
 
     /// Gets all versions which were not successfully processed for this `TransactionProcessor` from the DB
     /// This is so the `Tailer` can know which versions to retry
     fn get_error_versions(&self) -> Vec<u64> {
-        let conn = self.get_conn();
-
-        dsl::processor_statuses
-            .select(dsl::version)
-            .filter(
-                dsl::success
-                    .eq(false)
-                    .and(dsl::name.eq(self.name().to_string())),
-            )
-            .load::<i64>(&conn)
+        self.storage()
+            .get_error_versions(self.name())
             .expect("Error loading the error versions only query")
-            .iter()
-            .map(|v| *v as u64)
-            .collect()
     }
 
     /// Gets the highest version for this `TransactionProcessor` from the DB
     /// This is so we know where to resume from on restarts
     fn get_max_version(&self) -> Option<u64> {
-        let conn = self.get_conn();
-
-        dsl::processor_statuses
-            .select(diesel::dsl::max(dsl::version))
-            .filter(dsl::name.eq(self.name().to_string()))
-            .first::<Option<i64>>(&conn)
+        self.storage()
+            .get_max_version(self.name())
             .expect("Error loading the max version query")
-            .map(|v| v as u64)
     }
 }
@@ -0,0 +1,312 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    models::{events::EventModel, write_set_changes::WriteSetChangeModel},
+    schema::{coin_activities as coin_activitys, coin_balances, coin_infos},
+};
+use anyhow::{Context, Result};
+use aptos_rest_client::types;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const COIN_INFO_TYPE_PREFIX: &str = "0x1::Coin::CoinInfo<";
+const COIN_STORE_TYPE_PREFIX: &str = "0x1::Coin::CoinStore<";
+const DEPOSIT_EVENT_TYPE: &str = "0x1::Coin::DepositEvent";
+const WITHDRAW_EVENT_TYPE: &str = "0x1::Coin::WithdrawEvent";
+
+/// The `CoinInfo<T>` of a coin type as of a version, e.g. after its supply changed.
+#[derive(Debug, Insertable, Queryable, Serialize)]
+#[diesel(table_name = "coin_infos")]
+pub struct CoinInfo {
+    pub coin_type: String,
+    pub transaction_version: i64,
+    pub creator_address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: i64,
+    pub supply: Option<BigDecimal>,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+/// The balance of an account in a coin type as of a version, from its `CoinStore<T>`.
+#[derive(Debug, Insertable, Queryable, Serialize)]
+#[diesel(table_name = "coin_balances")]
+pub struct CoinBalance {
+    pub owner_address: String,
+    pub coin_type: String,
+    pub transaction_version: i64,
+    pub amount: BigDecimal,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+/// A deposit into or a withdrawal from the `CoinStore<T>` of an account.
+#[derive(Debug, Insertable, Queryable, Serialize)]
+#[diesel(table_name = "coin_activities")]
+pub struct CoinActivity {
+    pub event_key: String,
+    pub sequence_number: i64,
+    pub transaction_version: i64,
+    pub owner_address: String,
+    pub coin_type: String,
+    pub activity_type: String,
+    pub amount: BigDecimal,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+/// The coin records of a transaction.
+#[derive(Debug, Default)]
+pub struct CoinRecords {
+    pub infos: Vec<CoinInfo>,
+    pub balances: Vec<CoinBalance>,
+    pub activities: Vec<CoinActivity>,
+}
+
+impl CoinRecords {
+    /// Decodes the `CoinInfo<T>` and `CoinStore<T>` written by a transaction, and the deposit and
+    /// withdraw events it emitted.
+    pub fn from_transaction(
+        version: i64,
+        write_set_changes: &[WriteSetChangeModel],
+        events: &[EventModel],
+    ) -> Result<Self> {
+        let mut records = CoinRecords::default();
+        // The events don't have the coin type: it's found from the `CoinStore<T>` owning their
+        // handle, which is written by the transaction emitting them.
+        let mut event_handles = HashMap::new();
+        let inserted_at = chrono::Utc::now().naive_utc();
+
+        for write_set_change in write_set_changes {
+            if write_set_change.type_ != "write_resource" {
+                continue;
+            }
+            let resource_type = match write_set_change.data["type"].as_str() {
+                Some(resource_type) => resource_type,
+                None => continue,
+            };
+            let data = &write_set_change.data["data"];
+
+            if let Some(coin_type) = generic_type_param(resource_type, COIN_INFO_TYPE_PREFIX) {
+                let coin_info: CoinInfoResource = serde_json::from_value(data.clone())
+                    .with_context(|| format!("Invalid {}", resource_type))?;
+                records.infos.push(CoinInfo {
+                    coin_type: coin_type.to_string(),
+                    transaction_version: version,
+                    creator_address: write_set_change.address.clone(),
+                    name: coin_info.name,
+                    symbol: coin_info.symbol,
+                    decimals: coin_info.decimals,
+                    supply: match coin_info.supply.vec.first() {
+                        Some(supply) => Some(supply.parse()?),
+                        None => None,
+                    },
+                    inserted_at,
+                });
+            } else if let Some(coin_type) =
+                generic_type_param(resource_type, COIN_STORE_TYPE_PREFIX)
+            {
+                let coin_store: CoinStoreResource = serde_json::from_value(data.clone())
+                    .with_context(|| format!("Invalid {}", resource_type))?;
+                for handle in [&coin_store.deposit_events, &coin_store.withdraw_events] {
+                    event_handles.insert(
+                        handle.guid.guid.id.event_key(),
+                        (write_set_change.address.clone(), coin_type),
+                    );
+                }
+                records.balances.push(CoinBalance {
+                    owner_address: write_set_change.address.clone(),
+                    coin_type: coin_type.to_string(),
+                    transaction_version: version,
+                    amount: coin_store.coin.value,
+                    inserted_at,
+                });
+            }
+        }
+
+        for event in events {
+            if event.type_ != DEPOSIT_EVENT_TYPE && event.type_ != WITHDRAW_EVENT_TYPE {
+                continue;
+            }
+            let (owner_address, coin_type) = match event_handles.get(&event.key) {
+                Some(handle) => handle,
+                None => {
+                    aptos_logger::warn!(
+                        "[coin_processor] no CoinStore found for event {} in version {}",
+                        event.key,
+                        version
+                    );
+                    continue;
+                }
+            };
+            let coin_event: CoinEventResource = serde_json::from_value(event.data.clone())
+                .with_context(|| format!("Invalid {}", event.type_))?;
+            records.activities.push(CoinActivity {
+                event_key: event.key.clone(),
+                sequence_number: event.sequence_number,
+                transaction_version: version,
+                owner_address: owner_address.clone(),
+                coin_type: coin_type.to_string(),
+                activity_type: event.type_.clone(),
+                amount: coin_event.amount,
+                inserted_at,
+            });
+        }
+        Ok(records)
+    }
+}
+
+/// Gets `T` from a resource type `<prefix>T>`, e.g. `0x1::Coin::CoinStore<T>`.
+fn generic_type_param<'a>(resource_type: &'a str, prefix: &str) -> Option<&'a str> {
+    resource_type.strip_prefix(prefix)?.strip_suffix('>')
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinInfoResource {
+    name: String,
+    symbol: String,
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    decimals: i64,
+    supply: OptionResource,
+}
+
+/// An `Option<T>`, which is a vector of zero or one element.
+#[derive(Debug, Deserialize)]
+struct OptionResource {
+    vec: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinStoreResource {
+    coin: CoinResource,
+    deposit_events: EventHandleResource,
+    withdraw_events: EventHandleResource,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinResource {
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    value: BigDecimal,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinEventResource {
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    amount: BigDecimal,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventHandleResource {
+    guid: GuidWrapperResource,
+}
+
+#[derive(Debug, Deserialize)]
+struct GuidWrapperResource {
+    guid: GuidResource,
+}
+
+#[derive(Debug, Deserialize)]
+struct GuidResource {
+    id: GuidIdResource,
+}
+
+#[derive(Debug, Deserialize)]
+struct GuidIdResource {
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    creation_num: u64,
+    addr: String,
+}
+
+impl GuidIdResource {
+    /// The key of the events emitted to the handle: the little-endian creation number, followed by
+    /// the address.
+    fn event_key(&self) -> String {
+        let creation_num: String = self
+            .creation_num
+            .to_le_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let addr = self.addr.trim_start_matches("0x");
+        format!("0x{}{:0>64}", creation_num, addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event_handle(creation_num: u64) -> serde_json::Value {
+        json!({
+            "counter": "1",
+            "guid": {
+                "len_bytes": 40,
+                "guid": { "id": { "creation_num": creation_num.to_string(), "addr": "0xa550c18" } }
+            }
+        })
+    }
+
+    #[test]
+    fn test_coin_records() {
+        let now = chrono::Utc::now().naive_utc();
+        let write_set_change =
+            |type_: &str, address: &str, data: serde_json::Value| WriteSetChangeModel {
+                transaction_hash: "0x1".to_string(),
+                hash: format!("{}{}", address, type_),
+                type_: "write_resource".to_string(),
+                address: address.to_string(),
+                module: Default::default(),
+                resource: Default::default(),
+                data: json!({ "type": type_, "data": data }),
+                inserted_at: now,
+            };
+        let write_set_changes = vec![
+            write_set_change(
+                "0x1::Coin::CoinInfo<0x1::TestCoin::TestCoin>",
+                "0x1",
+                json!({
+                    "name": "Test Coin",
+                    "symbol": "TC",
+                    "decimals": "6",
+                    "supply": { "vec": ["18446744073709551615"] }
+                }),
+            ),
+            write_set_change(
+                "0x1::Coin::CoinStore<0x1::TestCoin::TestCoin>",
+                "0xa550c18",
+                json!({
+                    "coin": { "value": "100" },
+                    "deposit_events": event_handle(2),
+                    "withdraw_events": event_handle(3),
+                }),
+            ),
+        ];
+        let events = vec![EventModel {
+            transaction_hash: "0x1".to_string(),
+            key:
+                "0x0200000000000000000000000000000000000000000000000000000000000000000000000a550c18"
+                    .to_string(),
+            sequence_number: 0,
+            type_: "0x1::Coin::DepositEvent".to_string(),
+            data: json!({ "amount": "100" }),
+            inserted_at: now,
+        }];
+
+        let records = CoinRecords::from_transaction(5, &write_set_changes, &events).unwrap();
+        assert_eq!(records.infos.len(), 1);
+        assert_eq!(records.infos[0].coin_type, "0x1::TestCoin::TestCoin");
+        assert_eq!(records.infos[0].decimals, 6);
+        assert_eq!(
+            records.infos[0].supply,
+            Some("18446744073709551615".parse().unwrap())
+        );
+        assert_eq!(records.balances.len(), 1);
+        assert_eq!(records.balances[0].owner_address, "0xa550c18");
+        assert_eq!(records.balances[0].amount, BigDecimal::from(100));
+        assert_eq!(records.activities.len(), 1);
+        assert_eq!(records.activities[0].coin_type, "0x1::TestCoin::TestCoin");
+        assert_eq!(records.activities[0].owner_address, "0xa550c18");
+        assert_eq!(records.activities[0].transaction_version, 5);
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod coin;
 pub mod collection;
 pub mod events;
 pub mod ownership;
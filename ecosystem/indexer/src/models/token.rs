@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{models::events::Event, schema::tokens};
+use crate::{
+    models::events::Event,
+    schema::{token_mints, tokens},
+};
 use aptos_rest_client::types;
 use std::{collections::HashMap, fmt, fmt::Formatter, str::FromStr};
 
@@ -23,6 +26,18 @@ pub struct Token {
     pub inserted_at: chrono::NaiveDateTime,
 }
 
+/// Tokens minted for an existing token by a mint event, which increase its supply.
+#[derive(Debug, Insertable, Queryable, Serialize)]
+#[diesel(table_name = "token_mints")]
+pub struct TokenMint {
+    pub event_key: String,
+    pub sequence_number: i64,
+    pub token_id: String,
+    pub amount: i64,
+    pub transaction_hash: String,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TokenId {
     pub creator: String,
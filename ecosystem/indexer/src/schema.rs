@@ -15,6 +15,42 @@ table! {
     }
 }
 
+table! {
+    coin_activities (event_key, sequence_number) {
+        event_key -> Varchar,
+        sequence_number -> Int8,
+        transaction_version -> Int8,
+        owner_address -> Varchar,
+        coin_type -> Varchar,
+        activity_type -> Varchar,
+        amount -> Numeric,
+        inserted_at -> Timestamp,
+    }
+}
+
+table! {
+    coin_balances (owner_address, coin_type, transaction_version) {
+        owner_address -> Varchar,
+        coin_type -> Varchar,
+        transaction_version -> Int8,
+        amount -> Numeric,
+        inserted_at -> Timestamp,
+    }
+}
+
+table! {
+    coin_infos (coin_type, transaction_version) {
+        coin_type -> Varchar,
+        transaction_version -> Int8,
+        creator_address -> Varchar,
+        name -> Varchar,
+        symbol -> Varchar,
+        decimals -> Int8,
+        supply -> Nullable<Numeric>,
+        inserted_at -> Timestamp,
+    }
+}
+
 table! {
     collections (creator, name) {
         creator -> Varchar,
@@ -73,6 +109,17 @@ table! {
     }
 }
 
+table! {
+    token_mints (event_key, sequence_number) {
+        event_key -> Varchar,
+        sequence_number -> Int8,
+        token_id -> Varchar,
+        amount -> Int8,
+        transaction_hash -> Varchar,
+        inserted_at -> Timestamp,
+    }
+}
+
 table! {
     tokens (token_id) {
         token_id -> Varchar,
@@ -135,11 +182,15 @@ table! {
 
 allow_tables_to_appear_in_same_query!(
     block_metadata_transactions,
+    coin_activities,
+    coin_balances,
+    coin_infos,
     collections,
     events,
     ownerships,
     processor_statuses,
     token_activities,
+    token_mints,
     tokens,
     transactions,
     user_transactions,
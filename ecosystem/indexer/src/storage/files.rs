@@ -0,0 +1,478 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    models::processor_statuses::ProcessorStatusModel,
+    storage::{scalar_value, IndexerStorage, Record},
+};
+use anyhow::{format_err, Result};
+use aptos_logger::{info, warn};
+use arrow::{
+    array::{ArrayRef, BooleanArray, Int64Array, StringArray},
+    datatypes::{DataType, Field, Schema},
+    record_batch::RecordBatch,
+};
+use parquet::arrow::ArrowWriter;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// Rows buffered before being written out as Parquet files.
+const PARQUET_BUFFERED_ROWS: usize = 10_000;
+
+const STATUSES_FILE: &str = "processor_statuses.json";
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileFormat {
+    /// Newline-delimited JSON, appended to `<table>.jsonl`
+    #[clap(name = "ndjson")]
+    NdJson,
+    /// Parquet, written in parts to `<table>/part-<n>.parquet`
+    Parquet,
+}
+
+/// Storage in files of a directory, one per table, for feeding data pipelines.
+///
+/// The statuses of the processors are written to `processor_statuses.json` once the records of
+/// their versions are in the files, so that restarting the indexer processes again the versions
+/// whose records may have been lost, rather than skipping them. Records may then be written more
+/// than once, and consumers should deduplicate them by primary key.
+pub struct FileStorage {
+    dir: PathBuf,
+    format: FileFormat,
+    state: Mutex<FileState>,
+}
+
+#[derive(Default)]
+struct FileState {
+    /// Rows waiting to be written, by table.
+    buffers: HashMap<&'static str, Vec<Map<String, Value>>>,
+    buffered_rows: usize,
+    statuses: HashMap<String, ProcessorProgress>,
+}
+
+/// The statuses of a processor, reduced to what the `Tailer` asks for.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct ProcessorProgress {
+    max_version: Option<u64>,
+    error_versions: BTreeSet<u64>,
+}
+
+impl FileStorage {
+    pub fn new<P: AsRef<Path>>(dir: P, format: FileFormat) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        let statuses_path = dir.join(STATUSES_FILE);
+        let statuses = if statuses_path.exists() {
+            serde_json::from_slice(&std::fs::read(&statuses_path)?).map_err(|err| {
+                format_err!("Unable to parse {}: {}", statuses_path.display(), err)
+            })?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            dir,
+            format,
+            state: Mutex::new(FileState {
+                statuses,
+                ..FileState::default()
+            }),
+        })
+    }
+
+    /// Writes the buffered rows out, and then the statuses of the processors.
+    pub fn flush(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        self.flush_buffers(&mut state)?;
+        self.write_statuses(&state)
+    }
+
+    fn append_ndjson(&self, table: &str, rows: &[Map<String, Value>]) -> Result<()> {
+        let mut bytes = vec![];
+        for row in rows {
+            serde_json::to_writer(&mut bytes, row)?;
+            bytes.push(b'\n');
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(format!("{}.jsonl", table)))?;
+        file.write_all(&bytes)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    fn flush_buffers(&self, state: &mut FileState) -> Result<()> {
+        for (table, rows) in state.buffers.drain() {
+            if !rows.is_empty() {
+                write_parquet(&self.dir.join(table), table, &rows)?;
+            }
+        }
+        state.buffered_rows = 0;
+        Ok(())
+    }
+
+    fn write_statuses(&self, state: &FileState) -> Result<()> {
+        let path = self.dir.join(STATUSES_FILE);
+        // Written to a temporary file first, so that a crash doesn't leave a truncated file
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, serde_json::to_vec(&state.statuses)?)?;
+        std::fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FileStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FileStorage {{ dir: {:?}, format: {:?} }}",
+            self.dir, self.format
+        )
+    }
+}
+
+impl Drop for FileStorage {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            warn!("Unable to flush the records to {:?}: {}", self.dir, err);
+        }
+    }
+}
+
+impl IndexerStorage for FileStorage {
+    fn run_migrations(&self) -> Result<()> {
+        info!("Writing records to {:?} as {:?}", self.dir, self.format);
+        Ok(())
+    }
+
+    fn write_records(&self, records: &[Record]) -> Result<()> {
+        let mut rows: HashMap<&'static str, Vec<_>> = HashMap::new();
+        for record in records {
+            let row = record
+                .to_row()
+                .into_iter()
+                .map(|(column, value)| (column, scalar_value(value)))
+                .collect();
+            rows.entry(record.table()).or_default().push(row);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        match self.format {
+            FileFormat::NdJson => {
+                for (table, rows) in rows {
+                    self.append_ndjson(table, &rows)?;
+                }
+            }
+            FileFormat::Parquet => {
+                for (table, rows) in rows {
+                    state.buffered_rows += rows.len();
+                    state.buffers.entry(table).or_default().extend(rows);
+                }
+                if state.buffered_rows >= PARQUET_BUFFERED_ROWS {
+                    self.flush_buffers(&mut state)?;
+                    self.write_statuses(&state)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_processor_status(&self, status: &ProcessorStatusModel) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let progress = state.statuses.entry(status.name.to_string()).or_default();
+        let version = status.version as u64;
+        progress.max_version = progress.max_version.max(Some(version));
+        if status.success {
+            progress.error_versions.remove(&version);
+        } else {
+            progress.error_versions.insert(version);
+        }
+        // Buffered records are written out with the statuses when they're flushed
+        if state.buffered_rows == 0 {
+            self.write_statuses(&state)?;
+        }
+        Ok(())
+    }
+
+    fn get_error_versions(&self, processor_name: &str) -> Result<Vec<u64>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .statuses
+            .get(processor_name)
+            .map(|progress| progress.error_versions.iter().copied().collect())
+            .unwrap_or_default())
+    }
+
+    fn get_max_version(&self, processor_name: &str) -> Result<Option<u64>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .statuses
+            .get(processor_name)
+            .and_then(|progress| progress.max_version))
+    }
+}
+
+/// Columns of the Parquet files of a table, after the fields of its records, so that all the parts
+/// of a table have the same schema, whatever the values in them. JSON, numeric and timestamp
+/// columns are written as strings.
+fn table_schema(table: &str) -> Result<Schema> {
+    use DataType::{Boolean, Int64, Utf8};
+
+    let columns = match table {
+        "transactions" => vec![
+            ("type", Utf8),
+            ("payload", Utf8),
+            ("version", Int64),
+            ("hash", Utf8),
+            ("state_root_hash", Utf8),
+            ("event_root_hash", Utf8),
+            ("gas_used", Int64),
+            ("success", Boolean),
+            ("vm_status", Utf8),
+            ("accumulator_root_hash", Utf8),
+            ("inserted_at", Utf8),
+        ],
+        "user_transactions" => vec![
+            ("hash", Utf8),
+            ("signature", Utf8),
+            ("sender", Utf8),
+            ("sequence_number", Int64),
+            ("max_gas_amount", Int64),
+            ("expiration_timestamp_secs", Utf8),
+            ("gas_unit_price", Int64),
+            ("timestamp", Utf8),
+            ("inserted_at", Utf8),
+        ],
+        "block_metadata_transactions" => vec![
+            ("hash", Utf8),
+            ("id", Utf8),
+            ("round", Int64),
+            ("previous_block_votes", Utf8),
+            ("proposer", Utf8),
+            ("timestamp", Utf8),
+            ("inserted_at", Utf8),
+            ("epoch", Int64),
+            ("previous_block_votes_bitmap", Utf8),
+        ],
+        "events" => vec![
+            ("transaction_hash", Utf8),
+            ("key", Utf8),
+            ("sequence_number", Int64),
+            ("type", Utf8),
+            ("data", Utf8),
+            ("inserted_at", Utf8),
+        ],
+        "write_set_changes" => vec![
+            ("transaction_hash", Utf8),
+            ("hash", Utf8),
+            ("type", Utf8),
+            ("address", Utf8),
+            ("module", Utf8),
+            ("resource", Utf8),
+            ("data", Utf8),
+            ("inserted_at", Utf8),
+        ],
+        "tokens" => vec![
+            ("token_id", Utf8),
+            ("creator", Utf8),
+            ("collection", Utf8),
+            ("name", Utf8),
+            ("description", Utf8),
+            ("max_amount", Int64),
+            ("supply", Int64),
+            ("uri", Utf8),
+            ("minted_at", Utf8),
+            ("inserted_at", Utf8),
+        ],
+        "token_mints" => vec![
+            ("event_key", Utf8),
+            ("sequence_number", Int64),
+            ("token_id", Utf8),
+            ("amount", Int64),
+            ("transaction_hash", Utf8),
+            ("inserted_at", Utf8),
+        ],
+        "coin_infos" => vec![
+            ("coin_type", Utf8),
+            ("transaction_version", Int64),
+            ("creator_address", Utf8),
+            ("name", Utf8),
+            ("symbol", Utf8),
+            ("decimals", Int64),
+            ("supply", Utf8),
+            ("inserted_at", Utf8),
+        ],
+        "coin_balances" => vec![
+            ("owner_address", Utf8),
+            ("coin_type", Utf8),
+            ("transaction_version", Int64),
+            ("amount", Utf8),
+            ("inserted_at", Utf8),
+        ],
+        "coin_activities" => vec![
+            ("event_key", Utf8),
+            ("sequence_number", Int64),
+            ("transaction_version", Int64),
+            ("owner_address", Utf8),
+            ("coin_type", Utf8),
+            ("activity_type", Utf8),
+            ("amount", Utf8),
+            ("inserted_at", Utf8),
+        ],
+        _ => return Err(format_err!("No Parquet schema for the table {}", table)),
+    };
+    Ok(Schema::new(
+        columns
+            .into_iter()
+            .map(|(column, data_type)| Field::new(column, data_type, true))
+            .collect(),
+    ))
+}
+
+/// Writes the rows of a table to a new part in its directory.
+fn write_parquet(dir: &Path, table: &str, rows: &[Map<String, Value>]) -> Result<()> {
+    let schema = Arc::new(table_schema(table)?);
+    if let Some(column) = rows
+        .iter()
+        .flat_map(|row| row.keys())
+        .find(|column| schema.field_with_name(column).is_err())
+    {
+        return Err(format_err!(
+            "The column {} isn't in the Parquet schema of {}",
+            column,
+            table
+        ));
+    }
+    let arrays = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let values: Vec<&Value> = rows
+                .iter()
+                .map(|row| row.get(field.name()).unwrap_or(&Value::Null))
+                .collect();
+            column_array(field.data_type(), &values).map_err(|err| {
+                format_err!(
+                    "Unable to write the column {} of {}: {}",
+                    field.name(),
+                    table,
+                    err
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    std::fs::create_dir_all(dir)?;
+    let part = std::fs::read_dir(dir)?.count();
+    let path = dir.join(format!("part-{:05}.parquet", part));
+    let batch = RecordBatch::try_new(schema.clone(), arrays)?;
+    let mut writer = ArrowWriter::try_new(File::create(&path)?, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Builds the array of a column of the given type.
+fn column_array(data_type: &DataType, values: &[&Value]) -> Result<ArrayRef> {
+    Ok(match data_type {
+        DataType::Boolean => Arc::new(BooleanArray::from(
+            values
+                .iter()
+                .map(|v| typed_value(v, Value::as_bool))
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        DataType::Int64 => Arc::new(Int64Array::from(
+            values
+                .iter()
+                .map(|v| typed_value(v, Value::as_i64))
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        DataType::Utf8 => Arc::new(StringArray::from(
+            values
+                .iter()
+                .map(|v| match v {
+                    Value::Null => None,
+                    Value::String(s) => Some(s.clone()),
+                    v => Some(v.to_string()),
+                })
+                .collect::<Vec<_>>(),
+        )),
+        data_type => return Err(format_err!("Unsupported column type {:?}", data_type)),
+    })
+}
+
+/// Converts a value to the type of its column, null being a missing value.
+fn typed_value<T>(value: &Value, convert: impl Fn(&Value) -> Option<T>) -> Result<Option<T>> {
+    match value {
+        Value::Null => Ok(None),
+        value => convert(value)
+            .map(Some)
+            .ok_or_else(|| format_err!("Unexpected value {}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::token::Token;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn token(max_amount: Option<i64>) -> Token {
+        let now = chrono::Utc::now().naive_utc();
+        Token {
+            token_id: "0x1::Collection::Token".to_string(),
+            creator: "0x1".to_string(),
+            collection: "Collection".to_string(),
+            name: "Token".to_string(),
+            description: "A token".to_string(),
+            max_amount,
+            supply: 1,
+            uri: "https://aptoslabs.com".to_string(),
+            minted_at: now,
+            inserted_at: now,
+        }
+    }
+
+    #[test]
+    fn test_parquet_parts_have_the_same_schema() {
+        let dir = std::env::temp_dir().join(format!("indexer-parquet-{}", std::process::id()));
+        let storage = FileStorage::new(&dir, FileFormat::Parquet).unwrap();
+
+        // Without a value to infer the type of `max_amount` from in the first part
+        storage
+            .write_records(&[Record::Token(token(None))])
+            .unwrap();
+        storage.flush().unwrap();
+        storage
+            .write_records(&[Record::Token(token(Some(10)))])
+            .unwrap();
+        storage.flush().unwrap();
+
+        let schemas: Vec<_> = (0..2)
+            .map(|part| {
+                let path = dir.join("tokens").join(format!("part-{:05}.parquet", part));
+                let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+                reader.metadata().file_metadata().schema().clone()
+            })
+            .collect();
+        assert_eq!(schemas[0], schemas[1]);
+
+        drop(storage);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_column_array_rejects_values_of_another_type() {
+        assert!(column_array(&DataType::Int64, &[&Value::from(1), &Value::Null]).is_ok());
+        assert!(column_array(&DataType::Int64, &[&Value::from("1")]).is_err());
+        assert!(column_array(&DataType::Boolean, &[&Value::from(1)]).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Storage the processors write their records to, and track their progress in.
+//!
+//! * `PostgresStorage` writes to the tables of the diesel migrations, and is the one to serve
+//!   queries from.
+//! * `SqliteStorage` writes to equivalent tables in a local SQLite database, for local development
+//!   without a Postgres server.
+//! * `FileStorage` appends the records to newline-delimited JSON or Parquet files, one per table,
+//!   for data pipelines.
+
+use crate::models::{
+    coin::{CoinActivity, CoinBalance, CoinInfo},
+    events::EventModel,
+    processor_statuses::ProcessorStatusModel,
+    token::{Token, TokenMint},
+    transactions::{BlockMetadataTransactionModel, TransactionModel, UserTransactionModel},
+    write_set_changes::WriteSetChangeModel,
+};
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt::Debug;
+
+pub mod files;
+pub mod postgres;
+pub mod sqlite;
+
+pub use files::{FileFormat, FileStorage};
+pub use postgres::PostgresStorage;
+pub use sqlite::SqliteStorage;
+
+/// Where the `TransactionProcessor`s write their records and statuses.
+pub trait IndexerStorage: Send + Sync + Debug {
+    /// Creates the tables, or whatever else the storage needs before being written to.
+    fn run_migrations(&self) -> Result<()>;
+
+    /// Writes the records produced by processing a transaction, all of them or none.
+    /// Writing the same records again must neither fail nor count them twice, as transactions can
+    /// be processed again.
+    fn write_records(&self, records: &[Record]) -> Result<()>;
+
+    /// Writes the status of a version for a processor, replacing the previous one.
+    fn apply_processor_status(&self, status: &ProcessorStatusModel) -> Result<()>;
+
+    /// Gets the versions which weren't processed successfully by the processor.
+    fn get_error_versions(&self, processor_name: &str) -> Result<Vec<u64>>;
+
+    /// Gets the highest version the processor has a status for.
+    fn get_max_version(&self, processor_name: &str) -> Result<Option<u64>>;
+}
+
+/// A record produced by a processor, which is a row of the table of the same name.
+#[derive(Debug)]
+pub enum Record {
+    Transaction(TransactionModel),
+    UserTransaction(UserTransactionModel),
+    BlockMetadataTransaction(BlockMetadataTransactionModel),
+    Event(EventModel),
+    WriteSetChange(WriteSetChangeModel),
+    Token(Token),
+    /// Increases the supply of the token the first time it's written.
+    TokenMint(TokenMint),
+    CoinInfo(CoinInfo),
+    CoinBalance(CoinBalance),
+    CoinActivity(CoinActivity),
+}
+
+impl Record {
+    /// Name of the table of the record.
+    pub fn table(&self) -> &'static str {
+        match self {
+            Record::Transaction(_) => "transactions",
+            Record::UserTransaction(_) => "user_transactions",
+            Record::BlockMetadataTransaction(_) => "block_metadata_transactions",
+            Record::Event(_) => "events",
+            Record::WriteSetChange(_) => "write_set_changes",
+            Record::Token(_) => "tokens",
+            Record::TokenMint(_) => "token_mints",
+            Record::CoinInfo(_) => "coin_infos",
+            Record::CoinBalance(_) => "coin_balances",
+            Record::CoinActivity(_) => "coin_activities",
+        }
+    }
+
+    /// Whether the record replaces an existing one with the same primary key, rather than being
+    /// ignored.
+    pub fn replaces_existing(&self) -> bool {
+        matches!(
+            self,
+            Record::Transaction(_)
+                | Record::UserTransaction(_)
+                | Record::BlockMetadataTransaction(_)
+        )
+    }
+
+    /// Columns of the record, by column name.
+    pub fn to_row(&self) -> Map<String, Value> {
+        match self {
+            Record::Transaction(record) => to_row(record),
+            Record::UserTransaction(record) => to_row(record),
+            Record::BlockMetadataTransaction(record) => to_row(record),
+            Record::Event(record) => to_row(record),
+            Record::WriteSetChange(record) => to_row(record),
+            Record::Token(record) => to_row(record),
+            Record::CoinInfo(record) => to_row(record),
+            Record::CoinBalance(record) => to_row(record),
+            Record::CoinActivity(record) => to_row(record),
+            Record::TokenMint(record) => to_row(record),
+        }
+    }
+}
+
+fn to_row<T: Serialize>(record: &T) -> Map<String, Value> {
+    match serde_json::to_value(record).expect("Records serialize to JSON") {
+        Value::Object(fields) => fields
+            .into_iter()
+            .map(|(field, value)| (column_name(field), value))
+            .collect(),
+        value => panic!("Record serialized to a non-object: {}", value),
+    }
+}
+
+/// Fields named after SQL keywords have a trailing underscore, e.g. `type_`.
+fn column_name(field: String) -> String {
+    match field.strip_suffix('_') {
+        Some(column) => column.to_string(),
+        None => field,
+    }
+}
+
+/// Value of a column holding JSON in Postgres, for storages without a JSON type.
+pub(crate) fn scalar_value(value: Value) -> Value {
+    match value {
+        Value::Array(_) | Value::Object(_) => Value::String(value.to_string()),
+        value => value,
+    }
+}
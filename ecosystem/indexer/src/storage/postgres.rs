@@ -0,0 +1,253 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters::{GOT_CONNECTION, UNABLE_TO_GET_CONNECTION},
+    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
+    models::{
+        events::EventModel, processor_statuses::ProcessorStatusModel,
+        write_set_changes::WriteSetChangeModel,
+    },
+    schema::{self, processor_statuses::dsl},
+    storage::{IndexerStorage, Record},
+};
+use anyhow::Result;
+use aptos_logger::info;
+use diesel::{prelude::*, RunQueryDsl};
+use std::fmt::Debug;
+
+diesel_migrations::embed_migrations!();
+
+/// Storage in the Postgres database of the diesel migrations.
+pub struct PostgresStorage {
+    connection_pool: PgDbPool,
+}
+
+impl PostgresStorage {
+    pub fn new(connection_pool: PgDbPool) -> Self {
+        Self { connection_pool }
+    }
+
+    /// Gets a connection.
+    /// If it was unable to do so (default timeout: 30s), it will keep retrying until it can.
+    pub fn get_conn(&self) -> PgPoolConnection {
+        let pool = &self.connection_pool;
+        loop {
+            match pool.get() {
+                Ok(conn) => {
+                    GOT_CONNECTION.inc();
+                    return conn;
+                }
+                Err(err) => {
+                    UNABLE_TO_GET_CONNECTION.inc();
+                    aptos_logger::error!(
+                        "Could not get DB connection from pool, will retry in {:?}. Err: {:?}",
+                        pool.connection_timeout(),
+                        err
+                    );
+                }
+            };
+        }
+    }
+}
+
+impl Debug for PostgresStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "PostgresStorage {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+fn insert_events(conn: &PgPoolConnection, events: Vec<&EventModel>) -> QueryResult<usize> {
+    execute_with_better_error(
+        conn,
+        diesel::insert_into(schema::events::table)
+            .values(events)
+            .on_conflict_do_nothing(),
+    )
+}
+
+fn insert_write_set_changes(
+    conn: &PgPoolConnection,
+    write_set_changes: Vec<&WriteSetChangeModel>,
+) -> QueryResult<usize> {
+    execute_with_better_error(
+        conn,
+        diesel::insert_into(schema::write_set_changes::table)
+            .values(write_set_changes)
+            .on_conflict_do_nothing(),
+    )
+}
+
+fn insert_record(conn: &PgPoolConnection, record: &Record) -> QueryResult<usize> {
+    use schema::tokens::dsl::{supply, token_id as token_id_column, tokens};
+
+    match record {
+        Record::Transaction(transaction_model) => {
+            aptos_logger::trace!(
+                "[postgres] inserting 'transaction' version {} with hash {}",
+                transaction_model.version,
+                transaction_model.hash
+            );
+            execute_with_better_error(
+                conn,
+                diesel::insert_into(schema::transactions::table)
+                    .values(transaction_model)
+                    .on_conflict(schema::transactions::dsl::hash)
+                    .do_update()
+                    .set(transaction_model),
+            )
+        }
+        Record::UserTransaction(user_transaction_model) => {
+            aptos_logger::trace!(
+                "[postgres] inserting 'user_transaction' with hash {}",
+                user_transaction_model.hash
+            );
+            execute_with_better_error(
+                conn,
+                diesel::insert_into(schema::user_transactions::table)
+                    .values(user_transaction_model)
+                    .on_conflict(schema::user_transactions::dsl::hash)
+                    .do_update()
+                    .set(user_transaction_model),
+            )
+        }
+        Record::BlockMetadataTransaction(block_metadata_transaction_model) => {
+            aptos_logger::trace!(
+                "[postgres] inserting 'block_metadata_transaction' with hash {}",
+                block_metadata_transaction_model.hash
+            );
+            execute_with_better_error(
+                conn,
+                diesel::insert_into(schema::block_metadata_transactions::table)
+                    .values(block_metadata_transaction_model)
+                    .on_conflict(schema::block_metadata_transactions::dsl::hash)
+                    .do_update()
+                    .set(block_metadata_transaction_model),
+            )
+        }
+        Record::Event(event) => insert_events(conn, vec![event]),
+        Record::WriteSetChange(write_set_change) => {
+            insert_write_set_changes(conn, vec![write_set_change])
+        }
+        Record::Token(token) => execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::tokens::table)
+                .values(token)
+                .on_conflict_do_nothing(),
+        ),
+        Record::CoinInfo(coin_info) => execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::coin_infos::table)
+                .values(coin_info)
+                .on_conflict_do_nothing(),
+        ),
+        Record::CoinBalance(coin_balance) => execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::coin_balances::table)
+                .values(coin_balance)
+                .on_conflict_do_nothing(),
+        ),
+        Record::CoinActivity(coin_activity) => execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::coin_activities::table)
+                .values(coin_activity)
+                .on_conflict_do_nothing(),
+        ),
+        Record::TokenMint(token_mint) => {
+            let inserted = execute_with_better_error(
+                conn,
+                diesel::insert_into(schema::token_mints::table)
+                    .values(token_mint)
+                    .on_conflict_do_nothing(),
+            )?;
+            // Only the first time the mint is written, so that the supply doesn't count it twice
+            // when the transaction is processed again.
+            if inserted == 0 {
+                return Ok(0);
+            }
+            diesel::update(tokens.filter(token_id_column.eq(&token_mint.token_id)))
+                .set(supply.eq(supply + token_mint.amount))
+                .execute(conn)
+        }
+    }
+}
+
+impl IndexerStorage for PostgresStorage {
+    fn run_migrations(&self) -> Result<()> {
+        info!("Running migrations...");
+        embedded_migrations::run_with_output(&self.get_conn(), &mut std::io::stdout())?;
+        info!("Migrations complete!");
+        Ok(())
+    }
+
+    fn write_records(&self, records: &[Record]) -> Result<()> {
+        let conn = self.get_conn();
+        conn.transaction::<(), diesel::result::Error, _>(|| {
+            // A transaction has many events and write set changes, which are inserted with one
+            // statement per table, after the transaction they reference.
+            let mut events = vec![];
+            let mut write_set_changes = vec![];
+            for record in records {
+                match record {
+                    Record::Event(event) => events.push(event),
+                    Record::WriteSetChange(write_set_change) => {
+                        write_set_changes.push(write_set_change)
+                    }
+                    record => {
+                        insert_record(&conn, record)?;
+                    }
+                }
+            }
+            if !events.is_empty() {
+                insert_events(&conn, events)?;
+            }
+            if !write_set_changes.is_empty() {
+                insert_write_set_changes(&conn, write_set_changes)?;
+            }
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    fn apply_processor_status(&self, status: &ProcessorStatusModel) -> Result<()> {
+        let conn = self.get_conn();
+        execute_with_better_error(
+            &conn,
+            diesel::insert_into(schema::processor_statuses::table)
+                .values(status)
+                .on_conflict((dsl::name, dsl::version))
+                .do_update()
+                .set(status),
+        )?;
+        Ok(())
+    }
+
+    fn get_error_versions(&self, processor_name: &str) -> Result<Vec<u64>> {
+        let conn = self.get_conn();
+        Ok(dsl::processor_statuses
+            .select(dsl::version)
+            .filter(
+                dsl::success
+                    .eq(false)
+                    .and(dsl::name.eq(processor_name.to_string())),
+            )
+            .load::<i64>(&conn)?
+            .iter()
+            .map(|v| *v as u64)
+            .collect())
+    }
+
+    fn get_max_version(&self, processor_name: &str) -> Result<Option<u64>> {
+        let conn = self.get_conn();
+        Ok(dsl::processor_statuses
+            .select(diesel::dsl::max(dsl::version))
+            .filter(dsl::name.eq(processor_name.to_string()))
+            .first::<Option<i64>>(&conn)?
+            .map(|v| v as u64))
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    models::processor_statuses::ProcessorStatusModel,
+    storage::{scalar_value, IndexerStorage, Record},
+};
+use anyhow::Result;
+use aptos_logger::info;
+use rusqlite::{params, types::Value as SqlValue, Connection, OptionalExtension};
+use serde_json::Value;
+use std::{fmt, path::Path, sync::Mutex};
+
+/// Tables equivalent to the ones of the diesel migrations, with the JSON columns stored as text.
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS transactions (
+    "type" TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    version INTEGER NOT NULL UNIQUE,
+    hash TEXT NOT NULL PRIMARY KEY,
+    state_root_hash TEXT NOT NULL,
+    event_root_hash TEXT NOT NULL,
+    gas_used INTEGER NOT NULL,
+    success BOOLEAN NOT NULL,
+    vm_status TEXT NOT NULL,
+    accumulator_root_hash TEXT NOT NULL,
+    inserted_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS user_transactions (
+    hash TEXT NOT NULL PRIMARY KEY,
+    signature TEXT NOT NULL,
+    sender TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    max_gas_amount INTEGER NOT NULL,
+    expiration_timestamp_secs TEXT NOT NULL,
+    gas_unit_price INTEGER NOT NULL,
+    timestamp TEXT NOT NULL,
+    inserted_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS block_metadata_transactions (
+    hash TEXT NOT NULL PRIMARY KEY,
+    id TEXT NOT NULL,
+    round INTEGER NOT NULL,
+    previous_block_votes TEXT NOT NULL,
+    proposer TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    inserted_at TEXT NOT NULL,
+    epoch INTEGER NOT NULL,
+    previous_block_votes_bitmap TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    transaction_hash TEXT NOT NULL,
+    key TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    "type" TEXT NOT NULL,
+    data TEXT NOT NULL,
+    inserted_at TEXT NOT NULL,
+    PRIMARY KEY (key, sequence_number)
+);
+CREATE TABLE IF NOT EXISTS write_set_changes (
+    transaction_hash TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    "type" TEXT NOT NULL,
+    address TEXT NOT NULL,
+    module TEXT NOT NULL,
+    resource TEXT NOT NULL,
+    data TEXT NOT NULL,
+    inserted_at TEXT NOT NULL,
+    PRIMARY KEY (transaction_hash, hash)
+);
+CREATE TABLE IF NOT EXISTS tokens (
+    token_id TEXT NOT NULL PRIMARY KEY,
+    creator TEXT NOT NULL,
+    collection TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    max_amount INTEGER,
+    supply INTEGER NOT NULL,
+    uri TEXT NOT NULL,
+    minted_at TEXT NOT NULL,
+    inserted_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS token_mints (
+    event_key TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    token_id TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    inserted_at TEXT NOT NULL,
+    PRIMARY KEY (event_key, sequence_number)
+);
+CREATE TABLE IF NOT EXISTS coin_infos (
+    coin_type TEXT NOT NULL,
+    transaction_version INTEGER NOT NULL,
+    creator_address TEXT NOT NULL,
+    name TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    decimals INTEGER NOT NULL,
+    supply NUMERIC,
+    inserted_at TEXT NOT NULL,
+    PRIMARY KEY (coin_type, transaction_version)
+);
+CREATE TABLE IF NOT EXISTS coin_balances (
+    owner_address TEXT NOT NULL,
+    coin_type TEXT NOT NULL,
+    transaction_version INTEGER NOT NULL,
+    amount NUMERIC NOT NULL,
+    inserted_at TEXT NOT NULL,
+    PRIMARY KEY (owner_address, coin_type, transaction_version)
+);
+CREATE TABLE IF NOT EXISTS coin_activities (
+    event_key TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    transaction_version INTEGER NOT NULL,
+    owner_address TEXT NOT NULL,
+    coin_type TEXT NOT NULL,
+    activity_type TEXT NOT NULL,
+    amount NUMERIC NOT NULL,
+    inserted_at TEXT NOT NULL,
+    PRIMARY KEY (event_key, sequence_number)
+);
+CREATE TABLE IF NOT EXISTS processor_statuses (
+    name TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    success BOOLEAN NOT NULL,
+    details TEXT,
+    last_updated TEXT NOT NULL,
+    PRIMARY KEY (name, version)
+);
+"#;
+
+/// Storage in a SQLite database, for running the indexer locally without a Postgres server.
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Opens the database at `path`, creating it if needed. `:memory:` opens an in-memory one.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+impl fmt::Debug for SqliteStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conn = self.conn.lock().unwrap();
+        write!(f, "SqliteStorage {{ path: {:?} }}", conn.path())
+    }
+}
+
+fn insert_record(conn: &Connection, record: &Record) -> rusqlite::Result<usize> {
+    let row = record.to_row();
+    let columns: Vec<_> = row.keys().map(|column| format!("\"{}\"", column)).collect();
+    let placeholders: Vec<_> = (1..=row.len()).map(|i| format!("?{}", i)).collect();
+    let sql = format!(
+        "INSERT OR {} INTO {} ({}) VALUES ({})",
+        if record.replaces_existing() {
+            "REPLACE"
+        } else {
+            "IGNORE"
+        },
+        record.table(),
+        columns.join(", "),
+        placeholders.join(", ")
+    );
+    let values: Vec<_> = row.into_iter().map(|(_, value)| sql_value(value)).collect();
+    let inserted = conn.execute(&sql, rusqlite::params_from_iter(values))?;
+
+    // Only the first time the mint is written, so that the supply doesn't count it twice when the
+    // transaction is processed again.
+    match record {
+        Record::TokenMint(token_mint) if inserted > 0 => conn.execute(
+            "UPDATE tokens SET supply = supply + ?1 WHERE token_id = ?2",
+            params![token_mint.amount, token_mint.token_id],
+        ),
+        _ => Ok(inserted),
+    }
+}
+
+fn sql_value(value: Value) -> SqlValue {
+    match scalar_value(value) {
+        Value::Null => SqlValue::Null,
+        Value::Bool(value) => SqlValue::Integer(value as i64),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => SqlValue::Integer(value),
+            None => SqlValue::Real(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(value) => SqlValue::Text(value),
+        value => SqlValue::Text(value.to_string()),
+    }
+}
+
+impl IndexerStorage for SqliteStorage {
+    fn run_migrations(&self) -> Result<()> {
+        info!("Creating the SQLite tables...");
+        self.conn.lock().unwrap().execute_batch(SCHEMA)?;
+        Ok(())
+    }
+
+    fn write_records(&self, records: &[Record]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let txn = conn.transaction()?;
+        for record in records {
+            insert_record(&txn, record)?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn apply_processor_status(&self, status: &ProcessorStatusModel) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO processor_statuses (name, version, success, details, last_updated) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                status.name,
+                status.version,
+                status.success,
+                status.details,
+                status.last_updated.to_string()
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_error_versions(&self, processor_name: &str) -> Result<Vec<u64>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement =
+            conn.prepare("SELECT version FROM processor_statuses WHERE success = 0 AND name = ?1")?;
+        let versions = statement
+            .query_map(params![processor_name], |row| row.get::<_, i64>(0))?
+            .map(|version| version.map(|v| v as u64))
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(versions)
+    }
+
+    fn get_max_version(&self, processor_name: &str) -> Result<Option<u64>> {
+        let conn = self.conn.lock().unwrap();
+        let version = conn
+            .query_row(
+                "SELECT MAX(version) FROM processor_statuses WHERE name = ?1",
+                params![processor_name],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(version.map(|v| v as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::token::{Token, TokenMint};
+
+    fn token(supply: i64) -> Token {
+        let now = chrono::Utc::now().naive_utc();
+        Token {
+            token_id: "0x1::Collection::Token".to_string(),
+            creator: "0x1".to_string(),
+            collection: "Collection".to_string(),
+            name: "Token".to_string(),
+            description: "A token".to_string(),
+            max_amount: None,
+            supply,
+            uri: "https://aptoslabs.com".to_string(),
+            minted_at: now,
+            inserted_at: now,
+        }
+    }
+
+    fn token_mint(sequence_number: i64, amount: i64) -> TokenMint {
+        TokenMint {
+            event_key: "0x0100000000000000000000000000000000000000000000000000000000000000001"
+                .to_string(),
+            sequence_number,
+            token_id: "0x1::Collection::Token".to_string(),
+            amount,
+            transaction_hash: format!("0x{}", sequence_number),
+            inserted_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_records_and_statuses() {
+        let storage = SqliteStorage::open(":memory:").unwrap();
+        storage.run_migrations().unwrap();
+
+        // Writing the same token again is ignored, and mints add to its supply once
+        let records = vec![
+            Record::Token(token(1)),
+            Record::Token(token(5)),
+            Record::TokenMint(token_mint(0, 2)),
+        ];
+        storage.write_records(&records).unwrap();
+        storage.write_records(&records).unwrap();
+        storage
+            .write_records(&[Record::TokenMint(token_mint(1, 3))])
+            .unwrap();
+        let supply: i64 = storage
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT supply FROM tokens", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(supply, 6);
+
+        assert_eq!(storage.get_max_version("processor").unwrap(), None);
+        storage
+            .apply_processor_status(&ProcessorStatusModel::for_mark_started("processor", 1))
+            .unwrap();
+        storage
+            .apply_processor_status(&ProcessorStatusModel::for_mark_started("processor", 2))
+            .unwrap();
+        storage
+            .apply_processor_status(&ProcessorStatusModel::new("processor", 1, true, None))
+            .unwrap();
+        assert_eq!(storage.get_error_versions("processor").unwrap(), vec![2]);
+        assert_eq!(storage.get_max_version("processor").unwrap(), Some(2));
+        assert_eq!(storage.get_max_version("other").unwrap(), None);
+    }
+}
@@ -2,60 +2,44 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
     indexer::{
         errors::TransactionProcessingError, processing_result::ProcessingResult,
         transaction_processor::TransactionProcessor,
     },
     models::{
         events::EventModel,
-        token::{CreationEventType, MintEventType, Token, TokenEvent},
+        token::{CreationEventType, MintEventType, Token, TokenEvent, TokenMint},
         transactions::{TransactionModel, UserTransaction},
     },
-    schema,
-    schema::tokens::{
-        dsl::{supply, tokens},
-        token_id,
-    },
+    storage::{IndexerStorage, Record},
 };
 use aptos_rest_client::Transaction;
 use async_trait::async_trait;
-use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
 use futures::future::Either;
 use std::{fmt::Debug, sync::Arc};
 
+#[derive(Debug)]
 pub struct TokenTransactionProcessor {
-    connection_pool: PgDbPool,
+    storage: Arc<dyn IndexerStorage>,
 }
 
 impl TokenTransactionProcessor {
-    pub fn new(connection_pool: PgDbPool) -> Self {
-        Self { connection_pool }
+    pub fn new(storage: Arc<dyn IndexerStorage>) -> Self {
+        Self { storage }
     }
 }
 
-impl Debug for TokenTransactionProcessor {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let state = &self.connection_pool.state();
-        write!(
-            f,
-            "TokenTransactionProcessor {{ connections: {:?}  idle_connections: {:?} }}",
-            state.connections, state.idle_connections
-        )
-    }
-}
-
-fn process_token(conn: &PgPoolConnection, events: &[EventModel], txn: &UserTransaction) {
+fn process_token(events: &[EventModel], txn: &UserTransaction) -> Vec<Record> {
+    let mut records = vec![];
     // filter events to only keep token events
     let token_events = events
         .iter()
-        .map(TokenEvent::from_event)
-        .filter(|e| e.is_some())
-        .collect::<Vec<Option<TokenEvent>>>();
+        .filter_map(|e| TokenEvent::from_event(e).map(|token_event| (e, token_event)))
+        .collect::<Vec<(&EventModel, TokenEvent)>>();
     // for create token event, insert a new token to token table,
     // if token exists, increase the supply
-    for event in token_events {
-        match event.unwrap() {
+    for (event, token_event) in token_events {
+        match token_event {
             TokenEvent::CreationEvent(CreationEventType { id, token_data }) => {
                 let token = Token {
                     token_id: id.to_string(),
@@ -69,22 +53,17 @@ fn process_token(conn: &PgPoolConnection, events: &[EventModel], txn: &UserTrans
                     minted_at: txn.timestamp,
                     inserted_at: chrono::Utc::now().naive_utc(),
                 };
-                execute_with_better_error(
-                    conn,
-                    diesel::insert_into(schema::tokens::table)
-                        .values(&token)
-                        .on_conflict_do_nothing(),
-                )
-                .expect("Error inserting row into token");
+                records.push(Record::Token(token));
             }
             TokenEvent::MintEvent(MintEventType { amount, id }) => {
-                let result: Token = tokens
-                    .filter(token_id.eq(id.to_string()))
-                    .first(conn)
-                    .expect("Error in loading Tokens");
-                let new_supply = result.supply + amount;
-                let query = diesel::update(tokens.find(id.to_string())).set(supply.eq(new_supply));
-                query.execute(conn).expect("Error updating row in token");
+                records.push(Record::TokenMint(TokenMint {
+                    event_key: event.key.clone(),
+                    sequence_number: event.sequence_number,
+                    token_id: id.to_string(),
+                    amount,
+                    transaction_hash: event.transaction_hash.clone(),
+                    inserted_at: chrono::Utc::now().naive_utc(),
+                }));
             }
             _ => (),
         }
@@ -94,6 +73,7 @@ fn process_token(conn: &PgPoolConnection, events: &[EventModel], txn: &UserTrans
     // TODO for withdraw/deposite update the ownership table
 
     // TODO add the information to token activity table
+    records
 }
 
 #[async_trait]
@@ -111,28 +91,26 @@ impl TransactionProcessor for TokenTransactionProcessor {
         let (_, maybe_details_model, maybe_events, _) =
             TransactionModel::from_transaction(&transaction);
 
-        let conn = self.get_conn();
-
-        let tx_result = conn.transaction::<(), diesel::result::Error, _>(|| {
-            if let Some(Either::Left(user_txn)) = maybe_details_model {
-                if let Some(events) = maybe_events {
-                    process_token(&conn, &events, &user_txn);
-                }
+        let mut records = vec![];
+        if let Some(Either::Left(user_txn)) = maybe_details_model {
+            if let Some(events) = maybe_events {
+                records = process_token(&events, &user_txn);
             }
-            Ok(())
-        });
+        }
+
+        let tx_result = self.storage.write_records(&records);
 
         match tx_result {
             Ok(_) => Ok(ProcessingResult::new(self.name(), version)),
             Err(err) => Err(TransactionProcessingError::TransactionCommitError((
-                anyhow::Error::from(err),
+                err,
                 version,
                 self.name(),
             ))),
         }
     }
 
-    fn connection_pool(&self) -> &PgDbPool {
-        &self.connection_pool
+    fn storage(&self) -> &dyn IndexerStorage {
+        self.storage.as_ref()
     }
 }
@@ -123,6 +123,16 @@ impl Mempool {
         self.transactions.get_by_hash(hash)
     }
 
+    /// Returns the gas unit price of the transaction at the given rank of the priority queue,
+    /// i.e. the price a new transaction has to beat to be ordered ahead of it.
+    /// Returns `None` if there are not more than `rank` transactions ready for consensus.
+    pub(crate) fn get_gas_price_at_rank(&self, rank: usize) -> Option<u64> {
+        self.transactions
+            .iter_queue()
+            .nth(rank)
+            .map(|key| key.gas_ranking_score)
+    }
+
     /// Used to add a transaction to the Mempool.
     /// Performs basic validation: checks account's sequence number.
     pub(crate) fn add_txn(
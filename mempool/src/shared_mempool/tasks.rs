@@ -141,6 +141,27 @@ pub(crate) async fn process_client_get_transaction<V>(
     }
 }
 
+/// Processes get gas price at rank request by client.
+pub(crate) async fn process_client_get_gas_price<V>(
+    smp: SharedMempool<V>,
+    rank: usize,
+    callback: oneshot::Sender<Option<u64>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let gas_price = smp.mempool.lock().get_gas_price_at_rank(rank);
+
+    if callback.send(gas_price).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::GetGasPrice,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
 /// Processes transactions from other nodes.
 pub(crate) async fn process_transaction_broadcast<V>(
     smp: SharedMempool<V>,
@@ -216,6 +216,7 @@ pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    GetGasPriceAtRank(usize, oneshot::Sender<Option<u64>>),
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
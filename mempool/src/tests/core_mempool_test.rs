@@ -66,6 +66,27 @@ fn test_transaction_ordering_only_seqnos() {
     }
 }
 
+#[test]
+fn test_get_gas_price_at_rank() {
+    let (mut mempool, _) = setup_mempool();
+    assert_eq!(mempool.get_gas_price_at_rank(0), None);
+
+    add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 3),
+            TestTransaction::new(1, 0, 5),
+            TestTransaction::new(2, 0, 1),
+            // Not ready for consensus, so not part of the priority queue.
+            TestTransaction::new(3, 1, 10),
+        ],
+    );
+    assert_eq!(mempool.get_gas_price_at_rank(0), Some(5));
+    assert_eq!(mempool.get_gas_price_at_rank(1), Some(3));
+    assert_eq!(mempool.get_gas_price_at_rank(2), Some(1));
+    assert_eq!(mempool.get_gas_price_at_rank(3), None);
+}
+
 #[test]
 fn test_transaction_ordering_only_crsns() {
     let (mut mempool, mut consensus) = setup_mempool();
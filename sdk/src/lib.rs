@@ -8,7 +8,9 @@
 //! This SDK provides all the necessary components for building on top of the Aptos Blockchain. Some of the important modules are:
 //!
 //! * `crypto` - Types used for signing and verifying
+//! * `rest_client` - Client for the REST API of a node
 //! * `transaction_builder` - Includes helpers for constructing transactions
+//! * `transaction_submitter` - Submits transactions concurrently, tracking sequence numbers
 //! * `types` - Includes types for Aptos on-chain data structures
 //!
 //! ## Example
@@ -21,8 +23,14 @@ pub mod crypto {
     pub use aptos_crypto::*;
 }
 
+pub mod rest_client {
+    pub use aptos_rest_client::*;
+}
+
 pub mod transaction_builder;
 
+pub mod transaction_submitter;
+
 pub mod types;
 
 pub mod move_types {
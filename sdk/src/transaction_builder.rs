@@ -148,6 +148,14 @@ impl TransactionFactory {
         ))
     }
 
+    /// Rotates the authentication key of the sender, after which its transactions must be signed
+    /// with the key of `new_auth_key`
+    pub fn rotate_authentication_key(&self, new_auth_key: AuthenticationKey) -> TransactionBuilder {
+        self.payload(aptos_stdlib::encode_account_rotate_authentication_key(
+            new_auth_key.to_vec(),
+        ))
+    }
+
     pub fn transfer(&self, to: AccountAddress, amount: u64) -> TransactionBuilder {
         self.payload(aptos_stdlib::encode_test_coin_transfer(to, amount))
     }
@@ -0,0 +1,397 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    rest_client::{Client, SubmissionStatus, Transaction},
+    transaction_builder::TransactionFactory,
+    types::{
+        account_address::AccountAddress,
+        transaction::{SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use anyhow::{anyhow, Result};
+use futures::stream::{FuturesUnordered, Stream};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Mutex,
+    time::Duration,
+};
+
+/// Default number of times a transaction is signed and submitted before giving up.
+pub const DEFAULT_MAX_ATTEMPTS: usize = 5;
+
+/// Delay before resubmitting a transaction that mempool didn't accept.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Submits transactions from a single account concurrently, and tracks their sequence numbers.
+///
+/// Each transaction is signed with the next available sequence number and submitted right away,
+/// without waiting for the previous ones to be committed.  When a transaction is rejected because
+/// of its sequence number, or expires before being committed, the sequence numbers are resynced
+/// from the account on-chain and the transaction is signed again and resubmitted.  The sequence
+/// number of a transaction which may or may not be committed, e.g. on a network error, is only
+/// reused once the account on-chain shows it expired unused.
+pub struct TransactionSubmitter {
+    client: Client,
+    transaction_factory: TransactionFactory,
+    account: LocalAccount,
+    sequence_numbers: Mutex<SequenceNumbers>,
+    max_attempts: usize,
+}
+
+impl TransactionSubmitter {
+    /// Creates a submitter starting from the sequence number of `account`.
+    pub fn new(
+        client: Client,
+        transaction_factory: TransactionFactory,
+        account: LocalAccount,
+    ) -> Self {
+        let sequence_numbers = Mutex::new(SequenceNumbers::new(account.sequence_number()));
+        Self {
+            client,
+            transaction_factory,
+            account,
+            sequence_numbers,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.account.address()
+    }
+
+    /// Next sequence number a transaction will be signed with.
+    pub fn next_sequence_number(&self) -> u64 {
+        self.sequence_numbers.lock().unwrap().next
+    }
+
+    /// Resyncs the sequence numbers from the account on-chain, returning its sequence number.
+    pub async fn resync(&self) -> Result<u64> {
+        let response = self.client.get_account(self.address()).await?;
+        let on_chain_sequence_number = response.inner().sequence_number;
+        let ledger_timestamp_secs = response.state().timestamp_usecs / 1_000_000;
+        self.sequence_numbers
+            .lock()
+            .unwrap()
+            .resync(on_chain_sequence_number, ledger_timestamp_secs);
+        Ok(on_chain_sequence_number)
+    }
+
+    /// Submits the transactions concurrently, returning a stream of their outcomes along with
+    /// their index in `payloads`, in the order they complete.
+    pub fn submit_all<'a, I>(
+        &'a self,
+        payloads: I,
+    ) -> impl Stream<Item = (usize, Result<Transaction>)> + 'a
+    where
+        I: IntoIterator<Item = TransactionPayload>,
+    {
+        payloads
+            .into_iter()
+            .enumerate()
+            .map(|(index, payload)| async move { (index, self.submit(payload).await) })
+            .collect::<FuturesUnordered<_>>()
+    }
+
+    /// Submits a transaction and waits for it to be committed, resubmitting it if it's dropped.
+    ///
+    /// The committed transaction is returned, which may have failed during execution.
+    pub async fn submit(&self, payload: TransactionPayload) -> Result<Transaction> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let txn = self.sign(payload.clone());
+            let sequence_number = txn.sequence_number();
+
+            let result = self
+                .client
+                .submit_batch(std::slice::from_ref(&txn))
+                .await
+                .and_then(|response| {
+                    response
+                        .into_inner()
+                        .pop()
+                        .ok_or_else(|| anyhow!("missing submission result"))
+                });
+            let result = match result {
+                Ok(result) => result,
+                Err(err) => {
+                    // The transaction may or may not have reached mempool
+                    self.abandon(&txn);
+                    return Err(err);
+                }
+            };
+
+            let (retry, reason) = match result.status {
+                SubmissionStatus::Accepted => match self.wait(&txn).await {
+                    Ok(Some(transaction)) => {
+                        self.release(sequence_number);
+                        return Ok(transaction);
+                    }
+                    Ok(None) => (true, "transaction expired".to_string()),
+                    Err(err) => {
+                        // The transaction was accepted and may still be committed
+                        self.abandon(&txn);
+                        return Err(err);
+                    }
+                },
+                status => {
+                    let retry = match status {
+                        SubmissionStatus::SequenceNumberTooOld
+                        | SubmissionStatus::SequenceNumberTooNew
+                        | SubmissionStatus::InvalidSequenceNumber
+                        | SubmissionStatus::InvalidUpdate
+                        | SubmissionStatus::TransactionExpired
+                        | SubmissionStatus::MempoolIsFull
+                        | SubmissionStatus::TooManyTransactions => true,
+                        _ => false,
+                    };
+                    let reason = format!(
+                        "transaction rejected: {:?}: {}",
+                        status,
+                        result.message.unwrap_or_default()
+                    );
+                    (retry, reason)
+                }
+            };
+
+            // The sequence number won't be used, so the next transactions must be resynced
+            self.release(sequence_number);
+            self.resync().await?;
+            if !retry || attempt >= self.max_attempts {
+                return Err(anyhow!("{} after {} attempts", reason, attempt));
+            }
+            if result.status != SubmissionStatus::Accepted {
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        }
+    }
+
+    fn sign(&self, payload: TransactionPayload) -> SignedTransaction {
+        let sequence_number = self.sequence_numbers.lock().unwrap().reserve();
+        let raw_txn = self
+            .transaction_factory
+            .payload(payload)
+            .sender(self.address())
+            .sequence_number(sequence_number)
+            .build();
+        self.account.sign_transaction(raw_txn)
+    }
+
+    fn release(&self, sequence_number: u64) {
+        self.sequence_numbers
+            .lock()
+            .unwrap()
+            .release(sequence_number);
+    }
+
+    /// Keeps the sequence number of a transaction whose outcome is unknown reserved until it's
+    /// known to be unused.
+    fn abandon(&self, txn: &SignedTransaction) {
+        self.sequence_numbers
+            .lock()
+            .unwrap()
+            .abandon(txn.sequence_number(), txn.expiration_timestamp_secs());
+    }
+
+    /// Waits for an accepted transaction to be committed, returning `None` if it expired.
+    async fn wait(&self, txn: &SignedTransaction) -> Result<Option<Transaction>> {
+        loop {
+            if let Ok(response) = self.client.wait_for_signed_transaction(txn).await {
+                return Ok(Some(response.into_inner()));
+            }
+
+            // Waiting fails when the transaction expired, failed during execution, or isn't
+            // committed yet, which is told apart from the account on-chain
+            let response = self.client.get_account(self.address()).await?;
+            let on_chain_sequence_number = response.inner().sequence_number;
+            let ledger_timestamp_secs = response.state().timestamp_usecs / 1_000_000;
+            self.sequence_numbers
+                .lock()
+                .unwrap()
+                .resync(on_chain_sequence_number, ledger_timestamp_secs);
+
+            if on_chain_sequence_number > txn.sequence_number() {
+                return self
+                    .client
+                    .get_transaction(txn.clone().committed_hash())
+                    .await
+                    .map(|response| Some(response.into_inner()));
+            }
+            if ledger_timestamp_secs >= txn.expiration_timestamp_secs() {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// Sequence numbers of the transactions of an account.
+struct SequenceNumbers {
+    /// Next sequence number to sign a transaction with.
+    next: u64,
+    /// Sequence numbers of the signed transactions which aren't known to be committed or dropped.
+    in_flight: BTreeSet<u64>,
+    /// Sequence numbers of the transactions given up on without knowing whether they reached
+    /// mempool, with their expiration timestamp: they may still be committed until then.
+    abandoned: BTreeMap<u64, u64>,
+    /// Highest sequence number seen on-chain.
+    on_chain: u64,
+}
+
+impl SequenceNumbers {
+    fn new(next: u64) -> Self {
+        Self {
+            next,
+            in_flight: BTreeSet::new(),
+            abandoned: BTreeMap::new(),
+            on_chain: next,
+        }
+    }
+
+    fn reserve(&mut self) -> u64 {
+        let sequence_number = self.next;
+        self.in_flight.insert(sequence_number);
+        self.advance();
+        sequence_number
+    }
+
+    fn release(&mut self, sequence_number: u64) {
+        self.in_flight.remove(&sequence_number);
+    }
+
+    /// Keeps the sequence number of a transaction which may still be committed reserved, until
+    /// a resync shows it's committed, or that it expired at `expiration_timestamp_secs`.
+    fn abandon(&mut self, sequence_number: u64, expiration_timestamp_secs: u64) {
+        self.in_flight.remove(&sequence_number);
+        self.abandoned
+            .insert(sequence_number, expiration_timestamp_secs);
+    }
+
+    /// Forgets the committed transactions and the expired abandoned ones, and reuses the lowest
+    /// sequence number that isn't reserved, so that a gap left by a dropped transaction is filled.
+    fn resync(&mut self, on_chain: u64, ledger_timestamp_secs: u64) {
+        self.on_chain = self.on_chain.max(on_chain);
+        self.in_flight = self.in_flight.split_off(&self.on_chain);
+        self.abandoned = self.abandoned.split_off(&self.on_chain);
+        self.abandoned.retain(|_, expiration_timestamp_secs| {
+            *expiration_timestamp_secs > ledger_timestamp_secs
+        });
+        self.next = self.on_chain;
+        self.advance();
+    }
+
+    fn advance(&mut self) {
+        while self.in_flight.contains(&self.next) || self.abandoned.contains_key(&self.next) {
+            self.next += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SequenceNumbers;
+
+    #[test]
+    fn test_reserve_and_release() {
+        let mut sequence_numbers = SequenceNumbers::new(5);
+        assert_eq!(sequence_numbers.reserve(), 5);
+        assert_eq!(sequence_numbers.reserve(), 6);
+        assert_eq!(sequence_numbers.reserve(), 7);
+
+        // Releasing doesn't reuse the sequence number until a resync
+        sequence_numbers.release(6);
+        assert_eq!(sequence_numbers.reserve(), 8);
+    }
+
+    #[test]
+    fn test_resync_fills_gaps() {
+        let mut sequence_numbers = SequenceNumbers::new(0);
+        for _ in 0..4 {
+            sequence_numbers.reserve();
+        }
+
+        // 0 committed, 2 dropped: 2 is reused, then the ones after those in flight
+        sequence_numbers.release(0);
+        sequence_numbers.release(2);
+        sequence_numbers.resync(1, 0);
+        assert_eq!(sequence_numbers.reserve(), 2);
+        assert_eq!(sequence_numbers.reserve(), 4);
+    }
+
+    #[test]
+    fn test_resync_forgets_committed() {
+        let mut sequence_numbers = SequenceNumbers::new(0);
+        for _ in 0..3 {
+            sequence_numbers.reserve();
+        }
+
+        // Committed transactions aren't in flight anymore, even if not released yet
+        sequence_numbers.resync(3, 0);
+        assert!(sequence_numbers.in_flight.is_empty());
+        assert_eq!(sequence_numbers.reserve(), 3);
+
+        // The on-chain sequence number never goes back
+        sequence_numbers.release(3);
+        sequence_numbers.resync(1, 0);
+        assert_eq!(sequence_numbers.reserve(), 3);
+    }
+
+    #[test]
+    fn test_abandoned_reserved_until_expired() {
+        let mut sequence_numbers = SequenceNumbers::new(0);
+        assert_eq!(sequence_numbers.reserve(), 0);
+        assert_eq!(sequence_numbers.reserve(), 1);
+
+        // 0 may have reached mempool: it isn't reused before it expires at 100
+        sequence_numbers.abandon(0, 100);
+        sequence_numbers.release(1);
+        sequence_numbers.resync(0, 50);
+        assert_eq!(sequence_numbers.reserve(), 1);
+
+        sequence_numbers.release(1);
+        sequence_numbers.resync(0, 100);
+        assert_eq!(sequence_numbers.reserve(), 0);
+    }
+
+    #[test]
+    fn test_abandoned_after_acceptance() {
+        let mut sequence_numbers = SequenceNumbers::new(0);
+        for _ in 0..3 {
+            sequence_numbers.reserve();
+        }
+
+        // 0 was accepted but waiting for it failed, 1 was rejected and 2 is in flight: 0 may
+        // still be committed, so only 1 is reused
+        sequence_numbers.abandon(0, 100);
+        sequence_numbers.release(1);
+        sequence_numbers.resync(0, 50);
+        assert_eq!(sequence_numbers.reserve(), 1);
+        assert_eq!(sequence_numbers.reserve(), 3);
+
+        // Once 0 expired unused, it's reused by the next resync
+        sequence_numbers.release(1);
+        sequence_numbers.release(3);
+        sequence_numbers.resync(0, 100);
+        assert_eq!(sequence_numbers.reserve(), 0);
+        assert_eq!(sequence_numbers.reserve(), 1);
+        assert_eq!(sequence_numbers.reserve(), 3);
+    }
+
+    #[test]
+    fn test_abandoned_committed() {
+        let mut sequence_numbers = SequenceNumbers::new(0);
+        assert_eq!(sequence_numbers.reserve(), 0);
+        sequence_numbers.abandon(0, 100);
+
+        // The abandoned transaction made it on-chain
+        sequence_numbers.resync(1, 50);
+        assert!(sequence_numbers.abandoned.is_empty());
+        assert_eq!(sequence_numbers.reserve(), 1);
+    }
+}
@@ -3,10 +3,11 @@
 
 use crate::{
     crypto::{
-        ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
-        traits::Uniform,
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+        multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+        traits::{CryptoMaterialError, SigningKey, Uniform},
     },
-    transaction_builder::TransactionBuilder,
+    transaction_builder::{TransactionBuilder, TransactionFactory},
     types::{
         account_address::AccountAddress,
         transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
@@ -114,11 +115,152 @@ impl LocalAccount {
         &mut self.sequence_number
     }
 
+    /// Signs a transaction rotating the authentication key of the account to the one of
+    /// `new_key`.  The account keeps signing with its current key until
+    /// [`LocalAccount::rotate_key`] is called, which should only be done once the transaction is
+    /// committed.
+    pub fn sign_rotate_key_transaction(
+        &mut self,
+        new_key: &AccountKey,
+        transaction_factory: &TransactionFactory,
+    ) -> SignedTransaction {
+        self.sign_with_transaction_builder(
+            transaction_factory.rotate_authentication_key(new_key.authentication_key()),
+        )
+    }
+
+    /// Replaces the key of the account, returning the previous one.  The address of the account
+    /// doesn't change.
     pub fn rotate_key<T: Into<AccountKey>>(&mut self, new_key: T) -> AccountKey {
         std::mem::replace(&mut self.key, new_key.into())
     }
 }
 
+/// A K-of-N account whose transactions are authorized by the signatures of at least K of its N
+/// keys, aggregated into a single MultiEd25519 signature.
+///
+/// The private keys are held by the individual key holders, which each sign the raw transaction
+/// with [`MultiSigAccount::sign_partial`]. The partial signatures are then combined with
+/// [`MultiSigAccount::combine_signatures`].
+#[derive(Debug)]
+pub struct MultiSigAccount {
+    /// Address of the account.
+    address: AccountAddress,
+    /// Public keys and signature threshold of the account.
+    public_key: MultiEd25519PublicKey,
+    /// Latest known sequence number of the account, it can be different from validator.
+    sequence_number: u64,
+}
+
+impl MultiSigAccount {
+    pub fn new(public_key: MultiEd25519PublicKey, sequence_number: u64) -> Self {
+        let address = AuthenticationKey::multi_ed25519(&public_key).derived_address();
+        Self {
+            address,
+            public_key,
+            sequence_number,
+        }
+    }
+
+    /// Builds the unsigned transaction to be signed by the key holders
+    pub fn build_transaction(&mut self, builder: TransactionBuilder) -> RawTransaction {
+        let raw_txn = builder
+            .sender(self.address())
+            .sequence_number(self.sequence_number())
+            .build();
+        *self.sequence_number_mut() += 1;
+        raw_txn
+    }
+
+    /// Position of the public key in the account's keys, if it's one of them
+    pub fn key_index(&self, public_key: &Ed25519PublicKey) -> Option<u8> {
+        self.public_key
+            .public_keys()
+            .iter()
+            .position(|key| key == public_key)
+            .map(|index| index as u8)
+    }
+
+    /// Signs the transaction with one of the account's keys, returning the signature along with
+    /// the index of the key, or `None` if the key doesn't belong to the account
+    pub fn sign_partial(
+        &self,
+        txn: &RawTransaction,
+        private_key: &Ed25519PrivateKey,
+    ) -> Option<(Ed25519Signature, u8)> {
+        let index = self.key_index(&Ed25519PublicKey::from(private_key))?;
+        Some((private_key.sign(txn), index))
+    }
+
+    /// Combines the partial signatures of at least `threshold` key holders into a signed
+    /// transaction
+    pub fn combine_signatures(
+        &self,
+        txn: RawTransaction,
+        signatures: Vec<(Ed25519Signature, u8)>,
+    ) -> Result<SignedTransaction, CryptoMaterialError> {
+        if signatures.len() < *self.public_key.threshold() as usize {
+            return Err(CryptoMaterialError::ValidationError);
+        }
+        let signature = MultiEd25519Signature::new(signatures)?;
+        Ok(SignedTransaction::new_multisig(
+            txn,
+            self.public_key.clone(),
+            signature,
+        ))
+    }
+
+    /// Signs the transaction with the given keys when they are all held locally
+    pub fn sign_transaction(
+        &self,
+        txn: RawTransaction,
+        private_keys: &[&Ed25519PrivateKey],
+    ) -> SignedTransaction {
+        let signatures = private_keys
+            .iter()
+            .map(|private_key| {
+                self.sign_partial(&txn, private_key)
+                    .expect("Private key doesn't belong to the multisig account")
+            })
+            .collect();
+        self.combine_signatures(txn, signatures)
+            .expect("Combining signatures failed")
+    }
+
+    pub fn sign_with_transaction_builder(
+        &mut self,
+        private_keys: &[&Ed25519PrivateKey],
+        builder: TransactionBuilder,
+    ) -> SignedTransaction {
+        let raw_txn = self.build_transaction(builder);
+        self.sign_transaction(raw_txn, private_keys)
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    pub fn public_key(&self) -> &MultiEd25519PublicKey {
+        &self.public_key
+    }
+
+    pub fn threshold(&self) -> u8 {
+        *self.public_key.threshold()
+    }
+
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        AuthenticationKey::multi_ed25519(&self.public_key)
+    }
+
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    pub fn sequence_number_mut(&mut self) -> &mut u64 {
+        &mut self.sequence_number
+    }
+}
+
 #[derive(Debug)]
 pub struct AccountKey {
     private_key: Ed25519PrivateKey,
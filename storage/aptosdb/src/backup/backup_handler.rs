@@ -23,7 +23,7 @@ use aptos_types::{
 };
 use itertools::zip_eq;
 use serde::{Deserialize, Serialize};
-use std::{fmt, sync::Arc};
+use std::{fmt, sync::Arc, time::Duration};
 
 /// `BackupHandler` provides functionalities for AptosDB data backup.
 #[derive(Clone)]
@@ -157,6 +157,18 @@ impl BackupHandler {
             .transpose()
     }
 
+    /// Blocks until there are commits after `known_committed_version`, or `timeout` elapses, and
+    /// gets the `DbState` then.
+    pub fn wait_for_new_db_state(
+        &self,
+        known_committed_version: Version,
+        timeout: Duration,
+    ) -> Result<Option<DbState>> {
+        self.ledger_store
+            .wait_for_ledger_info_after(known_committed_version, timeout);
+        self.get_db_state()
+    }
+
     /// Gets the proof of the state root at specified version.
     /// N.B. the `LedgerInfo` returned will always be in the same epoch of the version.
     pub fn get_state_root_proof(
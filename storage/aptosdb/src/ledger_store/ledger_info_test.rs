@@ -4,8 +4,10 @@
 use super::*;
 use crate::AptosDB;
 use aptos_temppath::TempPath;
+use aptos_types::{block_info::BlockInfo, ledger_info::LedgerInfo};
 use ledger_info_test_utils::*;
 use proptest::{collection::vec, prelude::*};
+use std::collections::BTreeMap;
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(20))]
@@ -135,3 +137,48 @@ fn put_transaction_infos(db: &AptosDB, txn_infos: &[TransactionInfo]) {
         .unwrap();
     db.db.write_schemas(cs.batch).unwrap()
 }
+
+fn ledger_info_at_version(version: Version) -> LedgerInfoWithSignatures {
+    LedgerInfoWithSignatures::new(
+        LedgerInfo::new(
+            BlockInfo::new(0, 0, HashValue::zero(), HashValue::zero(), version, 0, None),
+            HashValue::zero(),
+        ),
+        BTreeMap::new(),
+    )
+}
+
+#[test]
+fn test_wait_for_ledger_info_after() {
+    let tmp_dir = TempPath::new();
+    let db = Arc::new(AptosDB::new_for_test(&tmp_dir));
+    db.ledger_store
+        .set_latest_ledger_info(ledger_info_at_version(10));
+
+    // Times out without a later ledger info.
+    let start = Instant::now();
+    db.ledger_store
+        .wait_for_ledger_info_after(10, Duration::from_millis(100));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    // Returns right away if there's one already.
+    let start = Instant::now();
+    db.ledger_store
+        .wait_for_ledger_info_after(9, Duration::from_secs(60));
+    assert!(start.elapsed() < Duration::from_secs(60));
+
+    // Woken up once there's one.
+    let committer = {
+        let db = Arc::clone(&db);
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            db.ledger_store
+                .set_latest_ledger_info(ledger_info_at_version(11));
+        })
+    };
+    let start = Instant::now();
+    db.ledger_store
+        .wait_for_ledger_info_after(10, Duration::from_secs(60));
+    assert!(start.elapsed() < Duration::from_secs(60));
+    committer.join().unwrap();
+}
@@ -31,7 +31,11 @@ use aptos_types::{
 use arc_swap::ArcSwap;
 use itertools::Itertools;
 use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
-use std::{ops::Deref, sync::Arc};
+use std::{
+    ops::Deref,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
 #[derive(Debug)]
 pub struct LedgerStore {
@@ -41,6 +45,11 @@ pub struct LedgerStore {
     /// cache it in memory in order to avoid reading DB and deserializing the object frequently. It
     /// should be updated every time new ledger info and signatures are persisted.
     latest_ledger_info: ArcSwap<Option<LedgerInfoWithSignatures>>,
+
+    /// Notified every time the latest ledger info is updated, to wake up whoever waits for new
+    /// commits in `wait_for_ledger_info_after`.
+    latest_ledger_info_lock: Mutex<()>,
+    latest_ledger_info_updated: Condvar,
 }
 
 impl LedgerStore {
@@ -60,6 +69,8 @@ impl LedgerStore {
         Self {
             db,
             latest_ledger_info: ArcSwap::from(Arc::new(ledger_info)),
+            latest_ledger_info_lock: Mutex::new(()),
+            latest_ledger_info_updated: Condvar::new(),
         }
     }
 
@@ -131,6 +142,40 @@ impl LedgerStore {
     pub fn set_latest_ledger_info(&self, ledger_info_with_sigs: LedgerInfoWithSignatures) {
         self.latest_ledger_info
             .store(Arc::new(Some(ledger_info_with_sigs)));
+        // Notifying under the lock, so that waiters either see the new ledger info or get woken up.
+        let _lock = self
+            .latest_ledger_info_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.latest_ledger_info_updated.notify_all();
+    }
+
+    /// Blocks until the latest ledger info is at a version after `version`, or `timeout` elapses.
+    pub fn wait_for_ledger_info_after(&self, version: Version, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        let mut lock = self
+            .latest_ledger_info_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        loop {
+            let ledger_info_ptr = self.latest_ledger_info.load();
+            let ledger_info: &Option<LedgerInfoWithSignatures> = ledger_info_ptr.deref();
+            if ledger_info
+                .as_ref()
+                .map_or(false, |li| li.ledger_info().version() > version)
+            {
+                return;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return;
+            }
+            lock = self
+                .latest_ledger_info_updated
+                .wait_timeout(lock, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
     }
 
     pub fn get_latest_ledger_info_in_epoch(&self, epoch: u64) -> Result<LedgerInfoWithSignatures> {
@@ -54,6 +54,8 @@ use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
 use aptos_types::{
     account_address::AccountAddress,
+    account_config::NewBlockEvent,
+    block_metadata::new_block_event_key,
     contract_event::{ContractEvent, EventByVersionWithProof, EventWithProof},
     epoch_change::EpochChangeProof,
     event::EventKey,
@@ -80,6 +82,7 @@ use once_cell::sync::Lazy;
 use schemadb::{ColumnFamilyName, Options, SchemaBatch, DB, DEFAULT_CF_NAME};
 use std::{
     collections::HashMap,
+    convert::TryInto,
     iter::Iterator,
     path::Path,
     sync::{mpsc, Arc},
@@ -715,6 +718,20 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_prefixed_state_value_iterator(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        cursor: Option<&StateKey>,
+        version: Version,
+    ) -> Result<Box<dyn Iterator<Item = Result<(StateKey, StateValue)>> + '_>> {
+        gauged_api("get_prefixed_state_value_iterator", || {
+            let iter = self
+                .state_store
+                .get_prefixed_state_value_iterator(key_prefix, cursor, version)?;
+            Ok(Box::new(iter) as Box<dyn Iterator<Item = _> + '_>)
+        })
+    }
+
     fn get_latest_ledger_info_option(&self) -> Result<Option<LedgerInfoWithSignatures>> {
         gauged_api("get_latest_ledger_info_option", || {
             Ok(self.ledger_store.get_latest_ledger_info_option())
@@ -1203,6 +1220,53 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_block_height_by_version(
+        &self,
+        version: Version,
+        ledger_version: Version,
+    ) -> Result<Option<u64>> {
+        gauged_api("get_block_height_by_version", || {
+            ensure!(
+                version <= ledger_version,
+                "version {} is newer than ledger version {}",
+                version,
+                ledger_version,
+            );
+            self.event_store
+                .get_latest_sequence_number(version, &new_block_event_key())
+        })
+    }
+
+    fn get_block_info_by_height(
+        &self,
+        height: u64,
+        ledger_version: Version,
+    ) -> Result<(Version, Version, NewBlockEvent)> {
+        gauged_api("get_block_info_by_height", || {
+            let event_key = new_block_event_key();
+            // Fetch the event of the next block as well, which tells where this block ends.
+            let indices =
+                self.event_store
+                    .lookup_events_by_key(&event_key, height, 2, ledger_version)?;
+            let first_version = match indices.first() {
+                Some((_seq, version, _idx)) => *version,
+                None => {
+                    return Err(
+                        AptosDbError::NotFound(format!("Block at height {}", height)).into(),
+                    )
+                }
+            };
+            let last_version = match indices.get(1) {
+                Some((_seq, version, _idx)) => version - 1,
+                None => ledger_version,
+            };
+            let new_block_event =
+                self.event_store
+                    .get_event_by_key(&event_key, height, ledger_version)?;
+            Ok((first_version, last_version, (&new_block_event).try_into()?))
+        })
+    }
+
     fn get_latest_transaction_info_option(&self) -> Result<Option<(Version, TransactionInfo)>> {
         gauged_api("get_latest_transaction_info_option", || {
             self.ledger_store.get_latest_transaction_info_option()
@@ -32,7 +32,7 @@ use aptos_types::{
     },
     transaction::{Version, PRE_GENESIS_VERSION},
 };
-use schemadb::{SchemaBatch, DB};
+use schemadb::{SchemaBatch, SchemaIterator, DB};
 use std::{cmp::Ordering, collections::HashMap, sync::Arc};
 use storage_interface::StateSnapshotReceiver;
 
@@ -131,36 +131,10 @@ impl StateStore {
         key_prefix: &StateKeyPrefix,
         desired_version: Version,
     ) -> Result<HashMap<StateKey, StateValue>> {
-        let mut iter = self.db.iter::<StateValueSchema>(Default::default())?;
         let mut result = HashMap::new();
-        iter.seek(&(key_prefix))?;
-        while let Some(((state_key, first_version), state_value)) = iter.next().transpose()? {
-            // Cursor is currently at the first available version of the state key.
-            // Check if the key_prefix is a valid prefix of the state_key we got from DB.
-
-            if !key_prefix.is_prefix(&state_key)? {
-                // No more keys matching the key_prefix, we can return the result.
-                return Ok(result);
-            }
-            match first_version.cmp(&desired_version) {
-                Ordering::Less => {
-                    iter.seek_for_prev(&(state_key.clone(), desired_version))?;
-                    let ((state_key, _), state_value) =
-                        iter.next().transpose()?.ok_or_else(|| {
-                            anyhow!(
-                                "Failure seeking to desired version {:?} for state key {:?}",
-                                desired_version,
-                                state_key
-                            )
-                        })?;
-                    result.insert(state_key.clone(), state_value);
-                }
-
-                Ordering::Equal => {
-                    result.insert(state_key.clone(), state_value);
-                }
-                Ordering::Greater => {}
-            }
+        for item in self.get_prefixed_state_value_iterator(key_prefix, None, desired_version)? {
+            let (state_key, state_value) = item?;
+            result.insert(state_key, state_value);
             // We don't allow fetching arbitrarily large number of values to be fetched as this can
             // potentially slowdown the DB.
             if result.len() > MAX_VALUES_TO_FETCH_FOR_KEY_PREFIX {
@@ -170,12 +144,22 @@ impl StateStore {
                     MAX_VALUES_TO_FETCH_FOR_KEY_PREFIX
                 ));
             }
-            // Seek to the next key - this can be done by seeking to the current key with max version
-            iter.seek(&(state_key, u64::MAX))?;
         }
         Ok(result)
     }
 
+    /// Returns an iterator over the key, value pairs for a particular state key prefix at the
+    /// desired version, ordered by state key. Iteration starts at `first_key` if provided,
+    /// which allows paginating through the values without reading all of them at once.
+    pub fn get_prefixed_state_value_iterator(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        first_key: Option<&StateKey>,
+        desired_version: Version,
+    ) -> Result<PrefixedStateValueIterator> {
+        PrefixedStateValueIterator::new(&self.db, key_prefix.clone(), first_key, desired_version)
+    }
+
     pub fn get_value_at_version(
         &self,
         key_and_version: &(StateKey, Version),
@@ -490,3 +474,80 @@ fn add_kv_batch(batch: &mut SchemaBatch, kv_batch: &StateValueBatch) -> Result<(
     // Add kv_batch
     Ok(())
 }
+
+pub struct PrefixedStateValueIterator<'a> {
+    inner: SchemaIterator<'a, StateValueSchema>,
+    key_prefix: StateKeyPrefix,
+    desired_version: Version,
+    is_finished: bool,
+}
+
+impl<'a> PrefixedStateValueIterator<'a> {
+    fn new(
+        db: &'a DB,
+        key_prefix: StateKeyPrefix,
+        first_key: Option<&StateKey>,
+        desired_version: Version,
+    ) -> Result<Self> {
+        let mut inner = db.iter::<StateValueSchema>(Default::default())?;
+        match first_key {
+            // Seek to the first available version of the first key.
+            Some(first_key) => inner.seek(&(first_key.clone(), u64::MIN))?,
+            None => inner.seek(&&key_prefix)?,
+        }
+        Ok(Self {
+            inner,
+            key_prefix,
+            desired_version,
+            is_finished: false,
+        })
+    }
+
+    fn next_impl(&mut self) -> Result<Option<(StateKey, StateValue)>> {
+        if self.is_finished {
+            return Ok(None);
+        }
+
+        while let Some(((state_key, first_version), state_value)) = self.inner.next().transpose()? {
+            // Cursor is currently at the first available version of the state key.
+            // Check if the key_prefix is a valid prefix of the state_key we got from DB.
+            if !self.key_prefix.is_prefix(&state_key)? {
+                // No more keys matching the key_prefix.
+                break;
+            }
+            let ret = match first_version.cmp(&self.desired_version) {
+                Ordering::Less => {
+                    self.inner
+                        .seek_for_prev(&(state_key.clone(), self.desired_version))?;
+                    let (_, state_value) = self.inner.next().transpose()?.ok_or_else(|| {
+                        anyhow!(
+                            "Failure seeking to desired version {:?} for state key {:?}",
+                            self.desired_version,
+                            state_key
+                        )
+                    })?;
+                    Some(state_value)
+                }
+                Ordering::Equal => Some(state_value),
+                // The state key was created after the desired version.
+                Ordering::Greater => None,
+            };
+            // Seek to the next key - this can be done by seeking to the current key with max version
+            self.inner.seek(&(state_key.clone(), u64::MAX))?;
+            if let Some(state_value) = ret {
+                return Ok(Some((state_key, state_value)));
+            }
+        }
+
+        self.is_finished = true;
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for PrefixedStateValueIterator<'a> {
+    type Item = Result<(StateKey, StateValue)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_impl().transpose()
+    }
+}
@@ -232,6 +232,74 @@ fn test_get_values_by_key_prefix() {
     assert_eq!(*key_value_map.get(&key5).unwrap(), value5_v2);
 }
 
+#[test]
+fn test_get_prefixed_state_value_iterator() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let address = AccountAddress::new([12u8; AccountAddress::LENGTH]);
+    let account_key_prefx = StateKeyPrefix::new(StateKeyTag::AccessPath, address.to_vec());
+
+    let key1 = StateKey::AccessPath(AccessPath::new(address, b"state_key1".to_vec()));
+    let key2 = StateKey::AccessPath(AccessPath::new(address, b"state_key2".to_vec()));
+    let key3 = StateKey::AccessPath(AccessPath::new(address, b"state_key3".to_vec()));
+    let value1_v0 = StateValue::from(String::from("value1_v0").into_bytes());
+    let value2_v0 = StateValue::from(String::from("value2_v0").into_bytes());
+    let value2_v1 = StateValue::from(String::from("value2_v1").into_bytes());
+    let value3_v1 = StateValue::from(String::from("value3_v1").into_bytes());
+
+    // A key of another account sorted after the prefix must not be returned.
+    let address1 = AccountAddress::new([22u8; AccountAddress::LENGTH]);
+    let key4 = StateKey::AccessPath(AccessPath::new(address1, b"state_key4".to_vec()));
+    let value4_v0 = StateValue::from(String::from("value4_v0").into_bytes());
+
+    put_value_set(
+        store,
+        vec![
+            (key1.clone(), value1_v0.clone()),
+            (key2.clone(), value2_v0.clone()),
+            (key4, value4_v0),
+        ],
+        0,
+    );
+    put_value_set(
+        store,
+        vec![
+            (key2.clone(), value2_v1.clone()),
+            (key3.clone(), value3_v1.clone()),
+        ],
+        1,
+    );
+
+    let collect = |first_key: Option<&StateKey>, version: Version| {
+        store
+            .get_prefixed_state_value_iterator(&account_key_prefx, first_key, version)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+    };
+
+    assert_eq!(
+        collect(None, 0),
+        vec![(key1.clone(), value1_v0.clone()), (key2.clone(), value2_v0)]
+    );
+    assert_eq!(
+        collect(None, 1),
+        vec![
+            (key1, value1_v0),
+            (key2.clone(), value2_v1.clone()),
+            (key3.clone(), value3_v1.clone()),
+        ]
+    );
+    // Iteration resumes at the first key, inclusively.
+    assert_eq!(
+        collect(Some(&key2), 1),
+        vec![(key2, value2_v1), (key3.clone(), value3_v1)]
+    );
+    // Keys created after the desired version are skipped.
+    assert_eq!(collect(Some(&key3), 0), vec![]);
+}
+
 #[test]
 fn test_retired_records() {
     let key1 = StateKey::Raw(String::from("test_key1").into_bytes());
@@ -19,7 +19,7 @@ use crate::{
         GlobalRestoreOptions, RestoreRunMode,
     },
 };
-use anyhow::{anyhow, ensure, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use aptos_logger::prelude::*;
 use aptos_types::{
     contract_event::ContractEvent,
@@ -38,7 +38,7 @@ use futures::{
     future::TryFutureExt,
     stream,
     stream::{Peekable, Stream, TryStreamExt},
-    StreamExt,
+    Future, StreamExt,
 };
 use itertools::zip_eq;
 use std::{cmp::min, pin::Pin, sync::Arc, time::Instant};
@@ -429,8 +429,10 @@ impl TransactionRestoreBatchController {
             .try_buffered_x(self.global_opt.concurrent_downloads, 1)
             .and_then(future::ready);
 
+        // Committing a chunk doesn't have to wait for the next one to be replayed, which happens
+        // on top of the chunks replayed but not yet committed.
         db_commit_stream
-            .and_then(|()| {
+            .map_ok(|()| {
                 let chunk_replayer = chunk_replayer.clone();
                 async move {
                     tokio::task::spawn_blocking(move || {
@@ -448,6 +450,7 @@ impl TransactionRestoreBatchController {
                     .await?
                 }
             })
+            .try_buffered_x(self.global_opt.concurrent_downloads, 1)
             .try_fold((), |(), ()| future::ok(()))
             .await
     }
@@ -472,3 +475,85 @@ impl TransactionRestoreBatchController {
             .await
     }
 }
+
+/// Finds the version of the last transaction committed by `target_time_usecs`, i.e. the one right
+/// before the first block with a later timestamp, by binary searching the transaction chunks in
+/// the backups by the timestamps of the first blocks in them.
+pub async fn find_version_by_time(
+    storage: &Arc<dyn BackupStorage>,
+    manifest_handles: &[FileHandle],
+    target_time_usecs: u64,
+    concurrent_downloads: usize,
+) -> Result<Version> {
+    let chunks: Vec<TransactionChunk> = stream::iter(manifest_handles.iter().cloned())
+        .map(|hdl| {
+            let storage = storage.clone();
+            async move { storage.load_json_file(&hdl).await.err_notes(&hdl) }
+        })
+        .buffered_x(concurrent_downloads * 3, concurrent_downloads)
+        .and_then(|m: TransactionBackup| future::ready(m.verify().map(|_| m.chunks)))
+        .try_concat()
+        .await?;
+
+    search_chunks_by_time(&chunks, target_time_usecs, |chunk| async move {
+        Ok(LoadedChunk::load(chunk, storage, None).await?.txns)
+    })
+    .await
+}
+
+pub(crate) async fn search_chunks_by_time<F, Fut>(
+    chunks: &[TransactionChunk],
+    target_time_usecs: u64,
+    load_txns: F,
+) -> Result<Version>
+where
+    F: Fn(TransactionChunk) -> Fut,
+    Fut: Future<Output = Result<Vec<Transaction>>>,
+{
+    ensure!(!chunks.is_empty(), "No transaction backup found.");
+
+    // Number of chunks whose first block is by the target time. Chunks without a block, like the
+    // one with only the genesis transaction, count as such.
+    let (mut lo, mut hi) = (0, chunks.len());
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        let txns = load_txns(chunks[mid].clone()).await?;
+        let first_block_time = txns.iter().find_map(block_timestamp_usecs);
+        if first_block_time.map_or(true, |t| t <= target_time_usecs) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    if lo == 0 {
+        bail!(
+            "Target time {} is before the first block in the backups.",
+            target_time_usecs
+        );
+    }
+
+    // The block going past the target time can start in any of the following chunks.
+    for chunk in &chunks[lo - 1..] {
+        let txns = load_txns(chunk.clone()).await?;
+        if let Some(offset) = txns
+            .iter()
+            .position(|txn| block_timestamp_usecs(txn).map_or(false, |t| t > target_time_usecs))
+        {
+            // Never 0, the genesis transaction not being a block.
+            return Ok(chunk.first_version + offset as Version - 1);
+        }
+    }
+    let last_version = chunks.last().expect("Not empty.").last_version;
+    warn!(
+        "Target time {} is after the last block in the backups, using the last version {}.",
+        target_time_usecs, last_version,
+    );
+    Ok(last_version)
+}
+
+fn block_timestamp_usecs(txn: &Transaction) -> Option<u64> {
+    match txn {
+        Transaction::BlockMetadata(block_metadata) => Some(block_metadata.timestamp_usecs()),
+        _ => None,
+    }
+}
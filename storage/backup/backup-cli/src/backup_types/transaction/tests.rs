@@ -4,7 +4,8 @@
 use crate::{
     backup_types::transaction::{
         backup::{TransactionBackupController, TransactionBackupOpt},
-        restore::{TransactionRestoreController, TransactionRestoreOpt},
+        manifest::TransactionChunk,
+        restore::{search_chunks_by_time, TransactionRestoreController, TransactionRestoreOpt},
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
@@ -13,10 +14,16 @@ use crate::{
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
 };
+use aptos_crypto::HashValue;
 use aptos_temppath::TempPath;
-use aptos_types::transaction::Version;
+use aptos_types::{
+    account_address::AccountAddress,
+    block_metadata::BlockMetadata,
+    transaction::{ChangeSet, Transaction, Version, WriteSetPayload},
+    write_set::WriteSet,
+};
 use aptosdb::AptosDB;
-use std::{convert::TryInto, mem::size_of, sync::Arc};
+use std::{collections::HashMap, convert::TryInto, mem::size_of, sync::Arc};
 use storage_interface::DbReader;
 use tokio::time::Duration;
 
@@ -138,3 +145,68 @@ fn end_to_end() {
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
+
+#[test]
+fn search_by_time() {
+    let block = |timestamp_usecs| {
+        Transaction::BlockMetadata(BlockMetadata::new(
+            HashValue::zero(),
+            0,
+            0,
+            Vec::new(),
+            AccountAddress::ONE,
+            timestamp_usecs,
+        ))
+    };
+    let checkpoint = || Transaction::StateCheckpoint;
+    let txns = vec![
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(ChangeSet::new(
+            WriteSet::default(),
+            Vec::new(),
+        ))),
+        block(10),
+        checkpoint(),
+        block(20),
+        checkpoint(),
+        checkpoint(),
+        block(20),
+        checkpoint(),
+        block(30),
+    ];
+    // Chunks [0, 0], [1, 2], [3, 5], [6, 8]
+    let chunks: Vec<_> = [(0, 0), (1, 2), (3, 5), (6, 8)]
+        .iter()
+        .map(|&(first_version, last_version)| TransactionChunk {
+            first_version,
+            last_version,
+            transactions: format!("txns_{}", first_version),
+            proof: format!("proof_{}", first_version),
+        })
+        .collect();
+    let chunk_txns: HashMap<_, _> = chunks
+        .iter()
+        .map(|c| {
+            (
+                c.first_version,
+                txns[c.first_version as usize..=c.last_version as usize].to_vec(),
+            )
+        })
+        .collect();
+    let search = |target_time_usecs, chunks: &[TransactionChunk]| {
+        futures::executor::block_on(search_chunks_by_time(chunks, target_time_usecs, |c| {
+            futures::future::ok(chunk_txns[&c.first_version].clone())
+        }))
+    };
+
+    assert_eq!(search(5, &chunks).unwrap(), 0);
+    assert_eq!(search(10, &chunks).unwrap(), 2);
+    assert_eq!(search(19, &chunks).unwrap(), 2);
+    // Blocks with the same timestamp are both included.
+    assert_eq!(search(20, &chunks).unwrap(), 7);
+    assert_eq!(search(30, &chunks).unwrap(), 8);
+    assert_eq!(search(40, &chunks).unwrap(), 8);
+    // History before the target time garbage collected.
+    assert!(search(15, &chunks[2..]).is_err());
+    assert_eq!(search(20, &chunks[2..]).unwrap(), 7);
+    assert!(search(20, &[]).is_err());
+}
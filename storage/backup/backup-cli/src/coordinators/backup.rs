@@ -8,7 +8,7 @@ use crate::{
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
     metadata,
-    metadata::cache::MetadataCacheOpt,
+    metadata::{cache::MetadataCacheOpt, TransactionBackupMeta},
     metrics::backup::{
         EPOCH_ENDING_EPOCH, HEARTBEAT_TS, STATE_SNAPSHOT_VERSION, TRANSACTION_VERSION,
     },
@@ -27,7 +27,7 @@ use std::{fmt::Debug, sync::Arc};
 use structopt::StructOpt;
 use tokio::{
     sync::watch,
-    time::{interval, Duration},
+    time::{interval, Duration, MissedTickBehavior},
 };
 use tokio_stream::wrappers::IntervalStream;
 
@@ -46,6 +46,15 @@ pub struct BackupCoordinatorOpt {
     // slower than expected.
     #[structopt(long, default_value = "100000")]
     pub transaction_batch_size: usize,
+    // Only full batches of `transaction_batch_size` are backed up by default, so up to that many
+    // committed transactions can be missing from the backup. Setting this enables the continuous
+    // mode: at this interval, whatever is committed gets backed up, learning of new commits by
+    // long polling the backup service. The recovery point objective is then about this interval
+    // plus the time it takes to back up. Each of these backups has its own manifest and metadata
+    // file, so once a batch is complete, it's backed up again as a whole, superseding its parts,
+    // which restores no longer use and the GC coordinator deletes along with their metadata.
+    #[structopt(long)]
+    pub continuous_backup_interval_secs: Option<u64>,
     #[structopt(flatten)]
     pub concurernt_downloads: ConcurrentDownloadsOpt,
 }
@@ -56,6 +65,10 @@ impl BackupCoordinatorOpt {
             self.state_snapshot_interval > 0 && self.transaction_batch_size > 0,
             "Backup interval and batch size must be greater than 0."
         );
+        ensure!(
+            self.continuous_backup_interval_secs != Some(0),
+            "Continuous backup interval must be greater than 0."
+        );
         ensure!(
             self.state_snapshot_interval % self.transaction_batch_size == 0,
             "State snapshot interval should be N x transaction_batch_size, N >= 1. \
@@ -74,6 +87,7 @@ pub struct BackupCoordinator {
     metadata_cache_opt: MetadataCacheOpt,
     state_snapshot_interval: usize,
     transaction_batch_size: usize,
+    continuous_backup_interval: Option<Duration>,
     concurrent_downloads: usize,
 }
 
@@ -92,18 +106,29 @@ impl BackupCoordinator {
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval: opt.state_snapshot_interval,
             transaction_batch_size: opt.transaction_batch_size,
+            continuous_backup_interval: opt
+                .continuous_backup_interval_secs
+                .map(Duration::from_secs),
             concurrent_downloads: opt.concurernt_downloads.get(),
         }
     }
     pub async fn run(&self) -> Result<()> {
         // Connect to both the local node and the backup storage.
-        let backup_state = metadata::cache::sync_and_load(
+        let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
             self.concurrent_downloads,
         )
-        .await?
-        .get_storage_state();
+        .await?;
+        let backup_state = metadata_view.get_storage_state();
+        let transaction_progress = TransactionBackupProgress {
+            last_in_backup: backup_state.latest_transaction_version,
+            first_in_open_batch: get_first_in_open_batch(
+                metadata_view.transaction_backups(),
+                backup_state.latest_transaction_version,
+                self.transaction_batch_size,
+            ),
+        };
 
         // On new DbState retrieved:
         // `watch_db_state` informs `backup_epoch_endings` via channel 1,
@@ -114,7 +139,13 @@ impl BackupCoordinator {
         let (tx2, rx2) = watch::channel::<Option<DbState>>(None);
 
         // Schedule work streams.
-        let watch_db_state = IntervalStream::new(interval(Duration::from_secs(1)))
+        let mut db_state_interval = interval(
+            self.continuous_backup_interval
+                .unwrap_or_else(|| Duration::from_secs(1)),
+        );
+        // Long polls can outlast the interval, in which case carry on from when they return.
+        db_state_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let watch_db_state = IntervalStream::new(db_state_interval)
             .then(|_| self.try_refresh_db_state(&tx1))
             .boxed_local();
 
@@ -135,11 +166,7 @@ impl BackupCoordinator {
             )
             .boxed_local();
         let backup_transactions = self
-            .backup_work_stream(
-                backup_state.latest_transaction_version,
-                &rx2,
-                Self::backup_transactions,
-            )
+            .backup_work_stream(transaction_progress, &rx2, Self::backup_transactions)
             .boxed_local();
 
         info!("Backup coordinator started.");
@@ -161,7 +188,16 @@ impl BackupCoordinator {
 
 impl BackupCoordinator {
     async fn try_refresh_db_state(&self, db_state_broadcast: &watch::Sender<Option<DbState>>) {
-        match self.client.get_db_state().await {
+        let known_committed_version = db_state_broadcast.borrow().map(|s| s.committed_version);
+        let db_state = match (self.continuous_backup_interval, known_committed_version) {
+            (Some(_), Some(version)) => {
+                self.client
+                    .get_new_db_state(version, DB_STATE_LONG_POLL_TIMEOUT)
+                    .await
+            }
+            _ => self.client.get_db_state().await,
+        };
+        match db_state {
             Ok(s) => {
                 HEARTBEAT_TS.set(unix_timestamp_sec());
                 if s.is_none() {
@@ -253,27 +289,39 @@ impl BackupCoordinator {
 
     async fn backup_transactions(
         &self,
-        mut last_transaction_version_in_backup: Option<Version>,
+        mut progress: TransactionBackupProgress,
         db_state: DbState,
-    ) -> Result<Option<u64>> {
+    ) -> Result<TransactionBackupProgress> {
         loop {
-            if let Some(version) = last_transaction_version_in_backup {
+            if let Some(version) = progress.last_in_backup {
                 TRANSACTION_VERSION.set(version as i64);
             }
-            let (first, last) = get_batch_range(
-                last_transaction_version_in_backup,
-                self.transaction_batch_size,
-            );
-
-            if db_state.committed_version < last {
+            let (first, last) =
+                get_batch_range(progress.last_in_backup, self.transaction_batch_size);
+
+            let last_to_backup = match get_last_to_backup(
+                first,
+                last,
+                db_state,
+                self.continuous_backup_interval.is_some(),
+            ) {
+                Some(last_to_backup) => last_to_backup,
                 // wait for the next db_state update
-                return Ok(last_transaction_version_in_backup);
-            }
+                None => return Ok(progress),
+            };
+            // Once complete, a batch backed up in parts is backed up again as a whole.
+            let first_in_batch = progress.first_in_open_batch.unwrap_or(first);
+            let batch_complete = last_to_backup == last;
+            let first_to_backup = if batch_complete {
+                first_in_batch
+            } else {
+                first
+            };
 
             TransactionBackupController::new(
                 TransactionBackupOpt {
-                    start_version: first,
-                    num_transactions: (last + 1 - first) as usize,
+                    start_version: first_to_backup,
+                    num_transactions: (last_to_backup + 1 - first_to_backup) as usize,
                 },
                 self.global_opt.clone(),
                 Arc::clone(&self.client),
@@ -282,7 +330,14 @@ impl BackupCoordinator {
             .run()
             .await?;
 
-            last_transaction_version_in_backup = Some(last);
+            progress = TransactionBackupProgress {
+                last_in_backup: Some(last_to_backup),
+                first_in_open_batch: if batch_complete {
+                    None
+                } else {
+                    Some(first_in_batch)
+                },
+            };
         }
     }
 
@@ -329,6 +384,17 @@ where
 {
 }
 
+#[derive(Clone, Copy, Debug)]
+struct TransactionBackupProgress {
+    last_in_backup: Option<Version>,
+    /// The first version of the batch the continuous mode is backing up in parts, if any.
+    first_in_open_batch: Option<Version>,
+}
+
+/// Bounds how long a long poll waits for new commits, so that the heartbeat keeps being updated
+/// while nothing gets committed.
+const DB_STATE_LONG_POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
 fn get_batch_range(last_in_backup: Option<u64>, batch_size: usize) -> (u64, u64) {
     // say, 7 is already in backup, and we target batches of size 10, we will return (8, 10) in this
     // case, so 8, 9, 10 will be in this batch, and next time the backup worker will pass in 10,
@@ -341,6 +407,42 @@ fn get_batch_range(last_in_backup: Option<u64>, batch_size: usize) -> (u64, u64)
     })
 }
 
+fn get_last_to_backup(
+    first: Version,
+    last: Version,
+    db_state: DbState,
+    continuous: bool,
+) -> Option<Version> {
+    // In the continuous mode, the committed part of a batch is backed up without waiting for the
+    // rest of it, which is backed up later, in one or more backups, still ending at `last`.
+    if db_state.committed_version >= last {
+        Some(last)
+    } else if continuous && db_state.committed_version >= first {
+        Some(db_state.committed_version)
+    } else {
+        None
+    }
+}
+
+/// The first version of the batch following `last_in_backup`, if the continuous mode backed it up
+/// in parts, not compacted yet.
+fn get_first_in_open_batch(
+    transaction_backups: &[TransactionBackupMeta],
+    last_in_backup: Option<Version>,
+    batch_size: usize,
+) -> Option<Version> {
+    let (first, last) = get_batch_range(last_in_backup, batch_size);
+    if first == 0 {
+        return None;
+    }
+    let first_in_batch = last + 1 - batch_size as u64;
+    transaction_backups
+        .iter()
+        .map(|t| t.first_version)
+        .filter(|v| (first_in_batch..first).contains(v))
+        .min()
+}
+
 fn get_next_snapshot(last_in_backup: Option<u64>, db_state: DbState, interval: usize) -> u64 {
     // We don't try to guarantee snapshots are taken at each applicable interval: when the backup
     // progress can't keep up with the ledger growth, we favor timeliness over completeness.
@@ -359,7 +461,12 @@ fn get_next_snapshot(last_in_backup: Option<u64>, db_state: DbState, interval: u
 
 #[cfg(test)]
 mod tests {
-    use crate::coordinators::backup::{get_batch_range, get_next_snapshot};
+    use crate::{
+        coordinators::backup::{
+            get_batch_range, get_first_in_open_batch, get_last_to_backup, get_next_snapshot,
+        },
+        metadata::TransactionBackupMeta,
+    };
     use aptosdb::backup::backup_handler::DbState;
 
     #[test]
@@ -371,6 +478,47 @@ mod tests {
         assert_eq!(get_batch_range(Some(200), 100), (201, 300));
     }
 
+    #[test]
+    fn test_get_last_to_backup() {
+        let _state = |v| DbState {
+            epoch: 0,
+            committed_version: v,
+            synced_version: v,
+        };
+
+        assert_eq!(get_last_to_backup(101, 200, _state(250), false), Some(200));
+        assert_eq!(get_last_to_backup(101, 200, _state(200), false), Some(200));
+        assert_eq!(get_last_to_backup(101, 200, _state(150), false), None);
+        assert_eq!(get_last_to_backup(101, 200, _state(250), true), Some(200));
+        assert_eq!(get_last_to_backup(101, 200, _state(150), true), Some(150));
+        assert_eq!(get_last_to_backup(101, 200, _state(101), true), Some(101));
+        assert_eq!(get_last_to_backup(101, 200, _state(100), true), None);
+    }
+
+    #[test]
+    fn test_get_first_in_open_batch() {
+        let _backups = |ranges: &[(u64, u64)]| {
+            ranges
+                .iter()
+                .map(|(first, last)| TransactionBackupMeta {
+                    first_version: *first,
+                    last_version: *last,
+                    manifest: format!("transaction_{}", first),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(get_first_in_open_batch(&[], None, 100), None);
+        let backups = _backups(&[(0, 0), (1, 100), (101, 120), (121, 150)]);
+        assert_eq!(get_first_in_open_batch(&backups, Some(100), 100), None);
+        assert_eq!(get_first_in_open_batch(&backups, Some(150), 100), Some(101));
+        let backups = _backups(&[(0, 0), (1, 100), (101, 200), (201, 220)]);
+        assert_eq!(get_first_in_open_batch(&backups, Some(220), 100), Some(201));
+        // Compacted already.
+        let backups = _backups(&[(0, 0), (1, 40), (41, 100), (1, 100)]);
+        assert_eq!(get_first_in_open_batch(&backups, Some(100), 100), None);
+    }
+
     #[test]
     fn test_get_next_snapshot() {
         let _state = |v| DbState {
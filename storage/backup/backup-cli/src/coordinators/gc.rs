@@ -0,0 +1,398 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::manifest::StateSnapshotBackup, transaction::manifest::TransactionBackup,
+    },
+    metadata,
+    metadata::{
+        cache::MetadataCacheOpt, view::MetadataView, EpochEndingBackupMeta, Metadata,
+        StateSnapshotBackupMeta, TransactionBackupMeta,
+    },
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        error_notes::ErrorNotes, storage_ext::BackupStorageExt, stream::StreamX,
+        unix_timestamp_sec, ConcurrentDownloadsOpt,
+    },
+};
+use anyhow::{ensure, Result};
+use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
+use futures::{stream, TryStreamExt};
+use rand::random;
+use std::{convert::TryInto, sync::Arc};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct GcCoordinatorOpt {
+    #[structopt(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+    #[structopt(
+        long,
+        help = "Keep a state snapshot every this many epochs, the earliest one taken in them. \
+        The latest state snapshot is always kept."
+    )]
+    pub state_snapshot_epoch_interval: u64,
+    #[structopt(
+        long,
+        help = "Number of the state snapshots selected by --state-snapshot-epoch-interval to keep, \
+        the latest first. The transactions before the earliest one kept are deleted, so that \
+        history can be restored no further back than it."
+    )]
+    pub num_state_snapshots: usize,
+    #[structopt(
+        long,
+        help = "Only log what would be deleted, without deleting anything."
+    )]
+    pub dry_run: bool,
+    #[structopt(flatten)]
+    pub concurrent_downloads: ConcurrentDownloadsOpt,
+}
+
+impl GcCoordinatorOpt {
+    fn validate(&self) -> Result<()> {
+        ensure!(
+            self.state_snapshot_epoch_interval > 0 && self.num_state_snapshots > 0,
+            "State snapshot epoch interval and number of state snapshots must be greater than 0."
+        );
+        Ok(())
+    }
+}
+
+/// Deletes the backups no longer needed according to a retention policy, and compacts the
+/// metadata files into one holding the entries of the backups kept.
+///
+/// Epoch ending backups are always kept, since restoring from any state snapshot requires the
+/// whole epoch history to verify it.
+pub struct GcCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    state_snapshot_epoch_interval: u64,
+    num_state_snapshots: usize,
+    dry_run: bool,
+    concurrent_downloads: usize,
+}
+
+impl GcCoordinator {
+    pub fn new(opt: GcCoordinatorOpt, storage: Arc<dyn BackupStorage>) -> Result<Self> {
+        opt.validate()?;
+        Ok(Self {
+            storage,
+            metadata_cache_opt: opt.metadata_cache_opt,
+            state_snapshot_epoch_interval: opt.state_snapshot_epoch_interval,
+            num_state_snapshots: opt.num_state_snapshots,
+            dry_run: opt.dry_run,
+            concurrent_downloads: opt.concurrent_downloads.get(),
+        })
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!("GC coordinator started.");
+
+        // Listed before loading, so that the metadata files saved by backups running concurrently
+        // are never deleted, since their entries might not have been loaded.
+        let metadata_files = self.storage.list_metadata_files().await?;
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+        let plan = GcPlan::new(
+            &metadata_view,
+            self.state_snapshot_epoch_interval,
+            self.num_state_snapshots,
+        );
+        info!(
+            "GC planned: keeping {} entries of {} metadata files, deleting the state snapshots at \
+            versions {:?} and the transaction backups starting at versions {:?}.",
+            plan.kept.len(),
+            metadata_files.len(),
+            plan.state_snapshots_to_delete
+                .iter()
+                .map(|s| s.version)
+                .collect::<Vec<_>>(),
+            plan.transactions_to_delete
+                .iter()
+                .map(|t| t.first_version)
+                .collect::<Vec<_>>(),
+        );
+
+        let nothing_to_delete =
+            plan.state_snapshots_to_delete.is_empty() && plan.transactions_to_delete.is_empty();
+        if self.dry_run || (nothing_to_delete && metadata_files.len() <= 1) {
+            info!("GC coordinator exiting without changing the storage.");
+            return Ok(());
+        }
+
+        let mut files_to_delete = Vec::new();
+        for backup in &plan.state_snapshots_to_delete {
+            files_to_delete.extend(self.state_snapshot_files(&backup.manifest).await?);
+        }
+        for backup in &plan.transactions_to_delete {
+            files_to_delete.extend(self.transaction_files(&backup.manifest).await?);
+        }
+        // The backups are deleted only after no metadata refers to them. If interrupted before,
+        // the entries are duplicated at worst, which the metadata view tolerates; after, some
+        // files of the deleted backups might be left behind.
+        self.compact_metadata(&metadata_files, &plan.kept).await?;
+        self.delete_files(files_to_delete).await?;
+
+        info!("GC coordinator exiting with success.");
+        Ok(())
+    }
+}
+
+impl GcCoordinator {
+    async fn compact_metadata(
+        &self,
+        metadata_files: &[FileHandle],
+        kept: &[Metadata],
+    ) -> Result<()> {
+        let name = format!(
+            "compacted_{}.{:04x}.meta",
+            unix_timestamp_sec(),
+            random::<u16>()
+        )
+        .try_into()?;
+        let lines = kept
+            .iter()
+            .map(Metadata::to_text_line)
+            .collect::<Result<Vec<_>>>()?;
+        self.storage.save_metadata_lines(&name, &lines).await?;
+        info!(
+            "Compacted metadata file {} saved with {} entries.",
+            name.as_str(),
+            lines.len()
+        );
+
+        self.delete_files(metadata_files.to_vec()).await
+    }
+
+    async fn state_snapshot_files(&self, manifest: &FileHandle) -> Result<Vec<FileHandle>> {
+        let backup: StateSnapshotBackup = self
+            .storage
+            .load_json_file(manifest)
+            .await
+            .err_notes(manifest)?;
+        let mut files = Vec::new();
+        for chunk in backup.chunks {
+            files.push(chunk.blobs);
+            files.push(chunk.proof);
+        }
+        files.push(backup.proof);
+        files.push(manifest.clone());
+        Ok(files)
+    }
+
+    async fn transaction_files(&self, manifest: &FileHandle) -> Result<Vec<FileHandle>> {
+        let backup: TransactionBackup = self
+            .storage
+            .load_json_file(manifest)
+            .await
+            .err_notes(manifest)?;
+        let mut files = Vec::new();
+        for chunk in backup.chunks {
+            files.push(chunk.transactions);
+            files.push(chunk.proof);
+        }
+        files.push(manifest.clone());
+        Ok(files)
+    }
+
+    async fn delete_files(&self, file_handles: Vec<FileHandle>) -> Result<()> {
+        let num_files = file_handles.len();
+        let futs = file_handles.into_iter().map(|file_handle| {
+            let storage = &self.storage;
+            async move {
+                storage
+                    .delete_file(&file_handle)
+                    .await
+                    .err_notes(&file_handle)
+            }
+        });
+        stream::iter(futs)
+            .buffered_x(
+                self.concurrent_downloads * 2, /* buffer size */
+                self.concurrent_downloads,     /* concurrency */
+            )
+            .try_collect::<Vec<_>>()
+            .await?;
+        info!("{} files deleted.", num_files);
+        Ok(())
+    }
+}
+
+/// The backups to delete, and the metadata entries of the ones to keep.
+struct GcPlan {
+    kept: Vec<Metadata>,
+    state_snapshots_to_delete: Vec<StateSnapshotBackupMeta>,
+    transactions_to_delete: Vec<TransactionBackupMeta>,
+}
+
+impl GcPlan {
+    fn new(
+        view: &MetadataView,
+        state_snapshot_epoch_interval: u64,
+        num_state_snapshots: usize,
+    ) -> Self {
+        // Keep the earliest state snapshot of each span of epochs, and the latest one.
+        let state_snapshots = view.state_snapshot_backups();
+        let mut selected: Vec<&StateSnapshotBackupMeta> = Vec::new();
+        let mut last_span = None;
+        for (idx, backup) in state_snapshots.iter().enumerate() {
+            let span = epoch_at_version(view.epoch_ending_backups(), backup.version)
+                / state_snapshot_epoch_interval;
+            if last_span != Some(span) || idx + 1 == state_snapshots.len() {
+                selected.push(backup);
+                last_span = Some(span);
+            }
+        }
+        let kept_state_snapshots = &selected[selected.len().saturating_sub(num_state_snapshots)..];
+
+        // The transactions before the earliest state snapshot kept are only useful to restore the
+        // history before it.
+        let history_start: Option<Version> = kept_state_snapshots.first().map(|s| s.version + 1);
+
+        let mut plan = Self {
+            kept: Vec::new(),
+            state_snapshots_to_delete: Vec::new(),
+            transactions_to_delete: Vec::new(),
+        };
+        plan.kept.extend(
+            view.epoch_ending_backups()
+                .iter()
+                .cloned()
+                .map(Metadata::EpochEndingBackup),
+        );
+        for backup in state_snapshots {
+            if kept_state_snapshots.contains(&backup) {
+                plan.kept
+                    .push(Metadata::StateSnapshotBackup(backup.clone()));
+            } else {
+                plan.state_snapshots_to_delete.push(backup.clone());
+            }
+        }
+        // Superseded by the backups they've been compacted into.
+        plan.transactions_to_delete
+            .extend(view.superseded_transaction_backups().iter().cloned());
+        for backup in view.transaction_backups() {
+            if history_start.map_or(false, |start| backup.last_version < start) {
+                plan.transactions_to_delete.push(backup.clone());
+            } else {
+                plan.kept.push(Metadata::TransactionBackup(backup.clone()));
+            }
+        }
+        plan
+    }
+}
+
+/// The epoch of a version, as far as the epoch ending backups tell: exact if there's one backup
+/// per epoch, as the backup coordinator does, a lower bound otherwise.
+fn epoch_at_version(epoch_ending_backups: &[EpochEndingBackupMeta], version: Version) -> u64 {
+    epoch_ending_backups
+        .iter()
+        .filter(|e| e.last_version < version)
+        .map(|e| e.last_epoch + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gc_plan() {
+        // Epoch `e` spans versions [10e, 10e + 9], and transactions are backed up in batches of 10.
+        let mut metadata = Vec::new();
+        for epoch in 0..10 {
+            metadata.push(Metadata::new_epoch_ending_backup(
+                epoch,
+                epoch,
+                epoch * 10 + 9,
+                epoch * 10 + 9,
+                format!("epoch_ending_{}", epoch),
+            ));
+            metadata.push(Metadata::new_transaction_backup(
+                epoch * 10,
+                epoch * 10 + 9,
+                format!("transaction_{}", epoch * 10),
+            ));
+        }
+        // In epochs 0, 2, 4, 6, 8 and 9.
+        for version in [0, 20, 40, 60, 80, 95] {
+            metadata.push(Metadata::new_state_snapshot_backup(
+                version,
+                format!("state_snapshot_{}", version),
+            ));
+        }
+        let view = MetadataView::from(metadata);
+
+        // The snapshots at 0, 40 and 80 start spans of 4 epochs, and 95 is the latest.
+        let plan = GcPlan::new(&view, 4, 2);
+        assert_eq!(
+            plan.state_snapshots_to_delete
+                .iter()
+                .map(|s| s.version)
+                .collect::<Vec<_>>(),
+            vec![0, 20, 40, 60],
+        );
+        assert_eq!(
+            plan.transactions_to_delete
+                .iter()
+                .map(|t| t.first_version)
+                .collect::<Vec<_>>(),
+            vec![0, 10, 20, 30, 40, 50, 60, 70],
+        );
+
+        // What's kept can still be restored from the earliest state snapshot kept.
+        let kept = MetadataView::from(plan.kept);
+        assert_eq!(kept.epoch_ending_backups().len(), 10);
+        assert_eq!(kept.select_state_snapshot(85).unwrap().unwrap().version, 80);
+        let transactions = kept
+            .select_transaction_backups(kept.first_transaction_version().unwrap(), 99)
+            .unwrap();
+        assert_eq!(transactions.first().unwrap().first_version, 80);
+        assert!(kept.select_transaction_backups(0, 99).is_err());
+
+        // Keeping more snapshots than selected keeps them all, and everything since the earliest.
+        let plan = GcPlan::new(&view, 4, 10);
+        assert_eq!(plan.state_snapshots_to_delete.len(), 2);
+        assert!(plan.transactions_to_delete.is_empty());
+
+        // Nothing is deleted without state snapshots.
+        let view = MetadataView::from(vec![Metadata::new_transaction_backup(
+            0,
+            9,
+            "transaction_0".to_string(),
+        )]);
+        let plan = GcPlan::new(&view, 1, 1);
+        assert!(plan.transactions_to_delete.is_empty());
+        assert_eq!(plan.kept.len(), 1);
+    }
+
+    #[test]
+    fn test_gc_plan_deletes_compacted_transactions() {
+        // The continuous mode backed up [0, 9] in 3 parts before compacting them.
+        let view = MetadataView::from(vec![
+            Metadata::new_transaction_backup(0, 3, "transaction_0".to_string()),
+            Metadata::new_transaction_backup(4, 6, "transaction_4".to_string()),
+            Metadata::new_transaction_backup(7, 9, "transaction_7".to_string()),
+            Metadata::new_transaction_backup(0, 9, "transaction_0_compacted".to_string()),
+            Metadata::new_transaction_backup(10, 12, "transaction_10".to_string()),
+        ]);
+        assert_eq!(view.select_transaction_backups(0, 12).unwrap().len(), 2);
+
+        let plan = GcPlan::new(&view, 1, 1);
+        assert_eq!(
+            plan.transactions_to_delete
+                .iter()
+                .map(|t| t.manifest.as_str())
+                .collect::<Vec<_>>(),
+            vec!["transaction_0", "transaction_4", "transaction_7"],
+        );
+        assert_eq!(plan.kept.len(), 2);
+    }
+}
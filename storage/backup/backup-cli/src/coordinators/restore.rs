@@ -5,7 +5,7 @@ use crate::{
     backup_types::{
         epoch_ending::restore::EpochHistoryRestoreController,
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
-        transaction::restore::TransactionRestoreBatchController,
+        transaction::restore::{find_version_by_time, TransactionRestoreBatchController},
     },
     metadata,
     metadata::{cache::MetadataCacheOpt, TransactionBackupMeta},
@@ -15,10 +15,11 @@ use crate::{
     storage::BackupStorage,
     utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode},
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use std::{convert::TryFrom, sync::Arc};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -39,6 +40,13 @@ pub struct RestoreCoordinatorOpt {
     pub ledger_history_start_version: Version,
     #[structopt(long, help = "Skip restoring epoch ending info, used for debugging.")]
     pub skip_epoch_endings: bool,
+    #[structopt(
+        long,
+        help = "Restore to the last version committed by this time, in RFC 3339 format, e.g. \
+                2022-06-01T12:00:00Z, as told by the timestamps of the blocks in the backups. \
+                Alternative to --target-version."
+    )]
+    pub target_time: Option<DateTime<Utc>>,
 }
 
 pub struct RestoreCoordinator {
@@ -48,6 +56,7 @@ pub struct RestoreCoordinator {
     replay_all: bool,
     ledger_history_start_version: Version,
     skip_epoch_endings: bool,
+    target_time: Option<DateTime<Utc>>,
 }
 
 impl RestoreCoordinator {
@@ -63,6 +72,7 @@ impl RestoreCoordinator {
             replay_all: opt.replay_all,
             ledger_history_start_version: opt.ledger_history_start_version,
             skip_epoch_endings: opt.skip_epoch_endings,
+            target_time: opt.target_time,
         }
     }
 
@@ -86,7 +96,7 @@ impl RestoreCoordinator {
         ret
     }
 
-    async fn run_impl(self) -> Result<()> {
+    async fn run_impl(mut self) -> Result<()> {
         let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
@@ -94,8 +104,41 @@ impl RestoreCoordinator {
         )
         .await?;
 
-        let mut transactions =
-            metadata_view.select_transaction_backups(0, self.target_version())?;
+        if let Some(target_time) = self.target_time {
+            ensure!(
+                self.global_opt.target_version == Version::MAX,
+                "--target-time and --target-version can't be both set."
+            );
+            let target_time_usecs = u64::try_from(target_time.timestamp())
+                .map_err(|_| anyhow!("Target time {} before 1970.", target_time))?
+                .checked_mul(1_000_000)
+                .and_then(|usecs| usecs.checked_add(target_time.timestamp_subsec_micros() as u64))
+                .ok_or_else(|| anyhow!("Target time {} out of range.", target_time))?;
+            let manifests: Vec<_> = metadata_view
+                .select_transaction_backups(
+                    metadata_view.first_transaction_version().unwrap_or(0),
+                    Version::MAX,
+                )?
+                .into_iter()
+                .map(|b| b.manifest)
+                .collect();
+            self.global_opt.target_version = find_version_by_time(
+                &self.storage,
+                &manifests,
+                target_time_usecs,
+                self.global_opt.concurrent_downloads,
+            )
+            .await?;
+            info!(
+                "Target time {} resolved to version {}.",
+                target_time, self.global_opt.target_version
+            );
+        }
+
+        let mut transactions = metadata_view.select_transaction_backups(
+            metadata_view.first_transaction_version().unwrap_or(0),
+            self.target_version(),
+        )?;
         let actual_target_version = self.get_actual_target_version(&transactions)?;
         let epoch_endings = metadata_view.select_epoch_ending_backups(actual_target_version)?;
         let state_snapshot = if self.replay_all {
@@ -107,6 +150,16 @@ impl RestoreCoordinator {
             Some(b) => b.version + 1,
             None => 0,
         };
+        if let Some(first_version) = transactions.first().map(|t| t.first_version) {
+            ensure!(
+                first_version <= replay_transactions_from_version,
+                "Transactions before version {} are not in the backup, likely garbage collected, \
+                 can't replay from version {}. Restore to a version with a state snapshot \
+                 before it instead.",
+                first_version,
+                replay_transactions_from_version,
+            );
+        }
         COORDINATOR_TARGET_VERSION.set(actual_target_version as i64);
         info!("Planned to restore to version {}.", actual_target_version);
 
@@ -119,7 +119,7 @@ pub async fn sync_and_load(
             // download to tmp file ".xxxxxx"
             tokio::io::copy(
                 &mut storage_ref
-                    .open_for_read(file_handle)
+                    .open_metadata_for_read(file_handle)
                     .await
                     .err_notes(file_handle)?,
                 &mut OpenOptions::new()
@@ -6,13 +6,16 @@ use crate::metadata::{
 };
 use anyhow::{anyhow, ensure, Result};
 use aptos_types::transaction::Version;
-use itertools::Itertools;
-use std::{fmt, str::FromStr};
+use itertools::{Either, Itertools};
+use std::{cmp::Reverse, fmt, str::FromStr};
 
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
+    /// Transaction backups whose versions are all in another one, like the ones the continuous
+    /// mode of the backup coordinator makes before compacting them once the batch is complete.
+    superseded_transaction_backups: Vec<TransactionBackupMeta>,
 }
 
 impl MetadataView {
@@ -54,29 +57,48 @@ impl MetadataView {
     ) -> Result<Vec<TransactionBackupMeta>> {
         // This can be more flexible, but for now we assume and check backups are continuous in
         // range (which is always true when we backup from a single backup coordinator)
-        let mut next_ver = 0;
+        // Backups entirely before `start_version` are not needed, and might have been garbage
+        // collected.
+        let mut next_ver = None;
         let mut res = Vec::new();
         for backup in self.transaction_backups.iter().sorted() {
             if backup.first_version > target_version {
                 break;
             }
-            ensure!(
-                backup.first_version == next_ver,
-                "Transactioon backup ranges not continuous, expecting version {}, got {}.",
-                next_ver,
-                backup.first_version,
-            );
-
-            if backup.last_version >= start_version {
-                res.push(backup.clone());
+            if backup.last_version < start_version {
+                continue;
+            }
+            match next_ver {
+                None => ensure!(
+                    backup.first_version <= start_version,
+                    "Transaction backups start at version {}, after the requested version {}.",
+                    backup.first_version,
+                    start_version,
+                ),
+                Some(next_ver) => ensure!(
+                    backup.first_version == next_ver,
+                    "Transactioon backup ranges not continuous, expecting version {}, got {}.",
+                    next_ver,
+                    backup.first_version,
+                ),
             }
 
-            next_ver = backup.last_version + 1;
+            res.push(backup.clone());
+            next_ver = Some(backup.last_version + 1);
         }
 
         Ok(res)
     }
 
+    /// The first version in the transaction backups, which is 0 unless the ones before the
+    /// earliest state snapshot kept have been garbage collected.
+    pub fn first_transaction_version(&self) -> Option<Version> {
+        self.transaction_backups
+            .iter()
+            .map(|t| t.first_version)
+            .min()
+    }
+
     pub fn select_epoch_ending_backups(
         &self,
         target_version: Version,
@@ -105,6 +127,24 @@ impl MetadataView {
     }
 }
 
+impl MetadataView {
+    pub fn epoch_ending_backups(&self) -> &[EpochEndingBackupMeta] {
+        &self.epoch_ending_backups
+    }
+
+    pub fn state_snapshot_backups(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
+    pub fn transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
+
+    pub fn superseded_transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.superseded_transaction_backups
+    }
+}
+
 impl From<Vec<Metadata>> for MetadataView {
     fn from(metadata_vec: Vec<Metadata>) -> Self {
         let mut epoch_ending_backups = Vec::new();
@@ -119,10 +159,38 @@ impl From<Vec<Metadata>> for MetadataView {
             }
         }
 
+        // The same entry can be in more than one metadata file if garbage collection was
+        // interrupted after saving the compacted metadata file.
+        epoch_ending_backups.sort();
+        epoch_ending_backups.dedup();
+        state_snapshot_backups.sort();
+        state_snapshot_backups.dedup();
+        transaction_backups.sort();
+        transaction_backups.dedup();
+
+        // Backups covering the same versions are interchangeable, the widest ones are used.
+        let (superseded_transaction_backups, transaction_backups): (Vec<_>, Vec<_>) =
+            transaction_backups
+                .iter()
+                .sorted_by_key(|t| (t.first_version, Reverse(t.last_version)))
+                .scan(None, |covered_until, t| {
+                    let superseded = *covered_until >= Some(t.last_version);
+                    *covered_until = std::cmp::max(*covered_until, Some(t.last_version));
+                    Some((superseded, t.clone()))
+                })
+                .partition_map(|(superseded, t)| {
+                    if superseded {
+                        Either::Left(t)
+                    } else {
+                        Either::Right(t)
+                    }
+                });
+
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
             transaction_backups,
+            superseded_transaction_backups,
         }
     }
 }
@@ -0,0 +1,572 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use aes_gcm::{
+    aead::{Aead, NewAead, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_config::config::{PersistableConfig, SecureBackend};
+use aptos_secure_storage::{KVStorage, Storage};
+use async_trait::async_trait;
+use futures::{
+    future, ready,
+    stream::{self, StreamExt, TryStreamExt},
+};
+use std::{
+    convert::TryInto,
+    io,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use structopt::StructOpt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+#[derive(StructOpt)]
+pub struct EncodingOpt {
+    #[structopt(
+        long = "zstd-level",
+        help = "Compress the backup files written with zstd, at this level, 3 being the default \
+        of zstd. Files are decoded as recorded in their file handles when read regardless."
+    )]
+    pub zstd_level: Option<i32>,
+    #[structopt(
+        long = "encryption-key-file",
+        parse(from_os_str),
+        help = "File holding a hex encoded 256 bit key, to encrypt the backup files written with \
+        AES-256-GCM, and to decrypt the ones read, which are then all required to be encrypted."
+    )]
+    pub encryption_key_file: Option<PathBuf>,
+    #[structopt(
+        long = "encryption-key-secure-backend",
+        parse(from_os_str),
+        help = "Config of the aptos-secure-storage backend holding the encryption key, in the \
+        YAML format of the secure backends in node configs. Alternative to --encryption-key-file."
+    )]
+    pub encryption_key_secure_backend: Option<PathBuf>,
+    #[structopt(
+        long = "encryption-key-name",
+        help = "Name of the hex encoded encryption key in --encryption-key-secure-backend."
+    )]
+    pub encryption_key_name: Option<String>,
+}
+
+impl EncodingOpt {
+    pub async fn init_encoding(self) -> Result<FileEncoding> {
+        let key = match (self.encryption_key_file, self.encryption_key_secure_backend) {
+            (Some(_), Some(_)) => bail!(
+                "--encryption-key-file and --encryption-key-secure-backend can't be both set."
+            ),
+            (Some(path), None) => {
+                let hex_key = tokio::fs::read_to_string(&path).await.err_notes(&path)?;
+                Some(parse_key(&hex_key).err_notes(&path)?)
+            }
+            (None, Some(path)) => {
+                let name = self.encryption_key_name.ok_or_else(|| {
+                    anyhow!("--encryption-key-name is required by --encryption-key-secure-backend.")
+                })?;
+                let backend = SecureBackend::load_config(&path).err_notes(&path)?;
+                let hex_key = Storage::from(&backend)
+                    .get::<String>(&name)
+                    .err_notes(&name)?
+                    .value;
+                Some(parse_key(&hex_key).err_notes(&name)?)
+            }
+            (None, None) => None,
+        };
+
+        Ok(FileEncoding {
+            zstd_level: self.zstd_level,
+            cipher: key.map(|key| Aes256Gcm::new(Key::from_slice(&key))),
+        })
+    }
+}
+
+fn parse_key(hex_key: &str) -> Result<Vec<u8>> {
+    let key = hex::decode(hex_key.trim())?;
+    ensure!(
+        key.len() == 32,
+        "Encryption key must be 32 bytes, got {}.",
+        key.len()
+    );
+    Ok(key)
+}
+
+/// How the backup files are encoded on write: compressed and / or encrypted, or as is.
+///
+/// The encoding of a file is recorded in its file handle, like "backup/file#encoding=zstd", so
+/// that the manifests and metadata referring to the file tell how to decode it, and files written
+/// with different options, or before encoding existed, can all be read. When an encryption key is
+/// configured, files whose handle doesn't record encryption are rejected on read, so that
+/// plaintext files can't be passed off as backup files.
+///
+/// Encoded files are a header, followed by the content cut in segments of `SEGMENT_SIZE` bytes,
+/// each compressed and encrypted on its own, so that files are encoded and decoded as streams.
+pub struct FileEncoding {
+    zstd_level: Option<i32>,
+    cipher: Option<Aes256Gcm>,
+}
+
+impl FileEncoding {
+    const MAGIC: &'static [u8] = b"APTBKP";
+    const VERSION: u8 = 1;
+    const HEADER_LEN: usize = Self::MAGIC.len() + 2;
+    const NONCE_PREFIX_LEN: usize = 7;
+    const NONCE_LEN: usize = 12;
+
+    const FLAG_ZSTD: u8 = 1;
+    const FLAG_AES_256_GCM: u8 = 1 << 1;
+
+    /// Separates a file handle of the underlying storage from the encoding of the file.
+    const HANDLE_TAG: &'static str = "#encoding=";
+    const ZSTD: &'static str = "zstd";
+    const AES_256_GCM: &'static str = "aes-256-gcm";
+
+    /// Size of the segments the content is cut in, bounding the memory used by the encoding.
+    const SEGMENT_SIZE: usize = 1 << 20;
+    /// Bound on the size of an encoded segment, which can be slightly larger than the segment.
+    const MAX_ENCODED_SEGMENT_SIZE: usize = 2 * Self::SEGMENT_SIZE;
+
+    #[cfg(test)]
+    fn new(zstd_level: Option<i32>, key: Option<&[u8]>) -> Self {
+        Self {
+            zstd_level,
+            cipher: key.map(|key| Aes256Gcm::new(Key::from_slice(key))),
+        }
+    }
+
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.zstd_level.is_some() {
+            flags |= Self::FLAG_ZSTD;
+        }
+        if self.cipher.is_some() {
+            flags |= Self::FLAG_AES_256_GCM;
+        }
+        flags
+    }
+
+    /// Records the encoding of a file in its handle, unless written as is.
+    fn tag_file_handle(file_handle: FileHandle, flags: u8) -> Result<FileHandle> {
+        ensure!(
+            !file_handle.contains(Self::HANDLE_TAG),
+            "File handle {} can't record an encoding.",
+            file_handle
+        );
+        let mut encodings = Vec::new();
+        if flags & Self::FLAG_ZSTD != 0 {
+            encodings.push(Self::ZSTD);
+        }
+        if flags & Self::FLAG_AES_256_GCM != 0 {
+            encodings.push(Self::AES_256_GCM);
+        }
+        if encodings.is_empty() {
+            return Ok(file_handle);
+        }
+        Ok(format!(
+            "{}{}{}",
+            file_handle,
+            Self::HANDLE_TAG,
+            encodings.join(",")
+        ))
+    }
+
+    /// Returns the file handle of the underlying storage, and the flags of the encoding recorded.
+    fn untag_file_handle(file_handle: &FileHandleRef) -> Result<(&FileHandleRef, u8)> {
+        let (inner, encodings) = match file_handle.rfind(Self::HANDLE_TAG) {
+            Some(pos) => (
+                &file_handle[..pos],
+                &file_handle[pos + Self::HANDLE_TAG.len()..],
+            ),
+            None => return Ok((file_handle, 0)),
+        };
+        let mut flags = 0;
+        for encoding in encodings.split(',') {
+            flags |= match encoding {
+                Self::ZSTD => Self::FLAG_ZSTD,
+                Self::AES_256_GCM => Self::FLAG_AES_256_GCM,
+                _ => bail!("Unknown encoding {} of {}.", encoding, file_handle),
+            };
+        }
+        Ok((inner, flags))
+    }
+}
+
+/// Encodes the segments of a file on write, or decodes them on read, in order.
+struct SegmentCodec {
+    encoding: Arc<FileEncoding>,
+    flags: u8,
+    /// Header of the file: magic, version, flags, and the nonce prefix if encrypted. It's
+    /// authenticated as the associated data of the encryption of every segment.
+    header: Vec<u8>,
+    nonce_prefix: [u8; FileEncoding::NONCE_PREFIX_LEN],
+    /// Index of the next segment.
+    index: u32,
+}
+
+impl SegmentCodec {
+    fn new(
+        encoding: Arc<FileEncoding>,
+        flags: u8,
+        nonce_prefix: [u8; FileEncoding::NONCE_PREFIX_LEN],
+    ) -> Self {
+        let mut header = FileEncoding::MAGIC.to_vec();
+        header.push(FileEncoding::VERSION);
+        header.push(flags);
+        if flags & FileEncoding::FLAG_AES_256_GCM != 0 {
+            header.extend_from_slice(&nonce_prefix);
+        }
+        Self {
+            encoding,
+            flags,
+            header,
+            nonce_prefix,
+            index: 0,
+        }
+    }
+
+    fn for_write(encoding: Arc<FileEncoding>) -> Self {
+        let flags = encoding.flags();
+        Self::new(encoding, flags, rand::random())
+    }
+
+    /// Reads the header of a file, which must be encoded as recorded in its handle.
+    async fn for_read(
+        file: &mut (dyn AsyncRead + Send + Unpin),
+        encoding: Arc<FileEncoding>,
+        flags: u8,
+    ) -> Result<Self> {
+        let mut header = [0; FileEncoding::HEADER_LEN];
+        file.read_exact(&mut header).await?;
+        ensure!(
+            header.starts_with(FileEncoding::MAGIC),
+            "Not an encoded backup file."
+        );
+        let version = header[FileEncoding::MAGIC.len()];
+        ensure!(
+            version == FileEncoding::VERSION,
+            "Unknown backup file encoding version {}.",
+            version
+        );
+        ensure!(
+            header[FileEncoding::MAGIC.len() + 1] == flags,
+            "Backup file not encoded as recorded in its file handle."
+        );
+        let mut nonce_prefix = [0; FileEncoding::NONCE_PREFIX_LEN];
+        if flags & FileEncoding::FLAG_AES_256_GCM != 0 {
+            file.read_exact(&mut nonce_prefix).await?;
+        }
+        Ok(Self::new(encoding, flags, nonce_prefix))
+    }
+
+    fn cipher(&self) -> Result<&Aes256Gcm> {
+        self.encoding
+            .cipher
+            .as_ref()
+            .ok_or_else(|| anyhow!("Backup file is encrypted, an encryption key is required."))
+    }
+
+    /// The nonce prefix of the file, the index of the segment, and whether it's the last one, so
+    /// that segments can't be reordered, dropped or appended without failing decryption.
+    fn nonce(&self, last: bool) -> [u8; FileEncoding::NONCE_LEN] {
+        let mut nonce = [0; FileEncoding::NONCE_LEN];
+        nonce[..FileEncoding::NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[FileEncoding::NONCE_PREFIX_LEN..FileEncoding::NONCE_LEN - 1]
+            .copy_from_slice(&self.index.to_be_bytes());
+        nonce[FileEncoding::NONCE_LEN - 1] = last as u8;
+        nonce
+    }
+
+    fn advance(&mut self) -> Result<()> {
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| anyhow!("Too many segments in backup file."))?;
+        Ok(())
+    }
+
+    /// A byte telling whether the segment is the last one, the length of the encoded segment as a
+    /// big endian u32, and the encoded segment.
+    fn encode_segment(&mut self, segment: &[u8], last: bool) -> Result<Vec<u8>> {
+        let mut payload = match self.encoding.zstd_level {
+            Some(level) => zstd::bulk::compress(segment, level)?,
+            None => segment.to_vec(),
+        };
+        if self.flags & FileEncoding::FLAG_AES_256_GCM != 0 {
+            payload = self
+                .cipher()?
+                .encrypt(
+                    Nonce::from_slice(&self.nonce(last)),
+                    Payload {
+                        msg: &payload,
+                        aad: &self.header,
+                    },
+                )
+                .map_err(|_| anyhow!("Encryption failed."))?;
+        }
+        self.advance()?;
+
+        let mut encoded = Vec::with_capacity(5 + payload.len());
+        encoded.push(last as u8);
+        encoded.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        encoded.extend_from_slice(&payload);
+        Ok(encoded)
+    }
+
+    /// Reads and decodes the next segment, telling whether it's the last one.
+    async fn decode_segment(
+        &mut self,
+        file: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<(Vec<u8>, bool)> {
+        let mut segment_header = [0; 5];
+        file.read_exact(&mut segment_header).await?;
+        let last = match segment_header[0] {
+            0 => false,
+            1 => true,
+            _ => bail!("Corrupted backup file segment."),
+        };
+        let len = u32::from_be_bytes(segment_header[1..].try_into().expect("4 bytes.")) as usize;
+        ensure!(
+            len <= FileEncoding::MAX_ENCODED_SEGMENT_SIZE,
+            "Backup file segment too large."
+        );
+        let mut payload = vec![0; len];
+        file.read_exact(&mut payload).await?;
+
+        if self.flags & FileEncoding::FLAG_AES_256_GCM != 0 {
+            payload = self
+                .cipher()?
+                .decrypt(
+                    Nonce::from_slice(&self.nonce(last)),
+                    Payload {
+                        msg: &payload,
+                        aad: &self.header,
+                    },
+                )
+                .map_err(|_| {
+                    anyhow!("Backup file failed decryption: wrong encryption key, or corrupted.")
+                })?;
+        }
+        let segment = if self.flags & FileEncoding::FLAG_ZSTD != 0 {
+            zstd::bulk::decompress(&payload, FileEncoding::SEGMENT_SIZE)?
+        } else {
+            payload
+        };
+        ensure!(
+            segment.len() <= FileEncoding::SEGMENT_SIZE,
+            "Backup file segment too large."
+        );
+        self.advance()?;
+        Ok((segment, last))
+    }
+}
+
+/// Wraps a `BackupStorage`, encoding the files written to it and decoding the ones read from it.
+/// Metadata files are left as is: they hold nothing more than version ranges and file handles,
+/// and need to be listed and read even without an encryption key.
+pub struct EncodedStorage {
+    storage: Arc<dyn BackupStorage>,
+    encoding: Arc<FileEncoding>,
+}
+
+impl EncodedStorage {
+    pub fn new(storage: Arc<dyn BackupStorage>, encoding: FileEncoding) -> Self {
+        Self {
+            storage,
+            encoding: Arc::new(encoding),
+        }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for EncodedStorage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        self.storage.create_backup(name).await
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let (file_handle, file) = self.storage.create_for_write(backup_handle, name).await?;
+        let flags = self.encoding.flags();
+        let file_handle = FileEncoding::tag_file_handle(file_handle, flags)?;
+        if flags == 0 {
+            return Ok((file_handle, file));
+        }
+        Ok((
+            file_handle,
+            Box::new(EncodingWriter::new(
+                file,
+                SegmentCodec::for_write(self.encoding.clone()),
+            )),
+        ))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let (inner_handle, flags) = FileEncoding::untag_file_handle(file_handle)?;
+        ensure!(
+            self.encoding.cipher.is_none() || flags & FileEncoding::FLAG_AES_256_GCM != 0,
+            "{} is not encrypted, while an encryption key is configured.",
+            file_handle,
+        );
+        let mut file = self.storage.open_for_read(inner_handle).await?;
+        if flags == 0 {
+            return Ok(file);
+        }
+
+        let codec = SegmentCodec::for_read(file.as_mut(), self.encoding.clone(), flags)
+            .await
+            .err_notes(file_handle)?;
+        let file_handle = file_handle.to_string();
+        let segments = stream::try_unfold(
+            (file, codec, false),
+            |(mut file, mut codec, done)| async move {
+                if done {
+                    // Nothing is expected after the last segment.
+                    ensure!(
+                        file.read(&mut [0; 1]).await? == 0,
+                        "Backup file has trailing data."
+                    );
+                    return Ok(None);
+                }
+                let (segment, last) = codec.decode_segment(file.as_mut()).await?;
+                Result::<_>::Ok(Some((segment, (file, codec, last))))
+            },
+        )
+        // An empty chunk would read as the end of the file.
+        .try_filter(|segment| future::ready(!segment.is_empty()))
+        .map_err(move |e| io::Error::new(io::ErrorKind::Other, e.context(file_handle.clone())));
+        Ok(Box::new(segments.boxed().into_async_read().compat()))
+    }
+
+    async fn open_metadata_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        self.storage.open_metadata_for_read(file_handle).await
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        self.storage.save_metadata_lines(name, lines).await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.storage.list_metadata_files().await
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let (inner_handle, _flags) = FileEncoding::untag_file_handle(file_handle)?;
+        self.storage.delete_file(inner_handle).await
+    }
+}
+
+/// Encodes what's written to it segment by segment, writing each to the underlying file once the
+/// next one starts, or on shutdown for the last one.
+struct EncodingWriter {
+    file: Box<dyn AsyncWrite + Send + Unpin>,
+    codec: SegmentCodec,
+    /// Content of the segment being filled.
+    segment: Vec<u8>,
+    /// Encoded bytes to write to the file, from `written` on.
+    encoded: Vec<u8>,
+    written: usize,
+    /// Whether the last segment was encoded.
+    finished: bool,
+}
+
+impl EncodingWriter {
+    fn new(file: Box<dyn AsyncWrite + Send + Unpin>, codec: SegmentCodec) -> Self {
+        let encoded = codec.header.clone();
+        Self {
+            file,
+            codec,
+            segment: Vec::with_capacity(FileEncoding::SEGMENT_SIZE),
+            encoded,
+            written: 0,
+            finished: false,
+        }
+    }
+
+    fn encode_segment(&mut self, last: bool) -> io::Result<()> {
+        self.encoded = self
+            .codec
+            .encode_segment(&self.segment, last)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        self.written = 0;
+        self.segment.clear();
+        Ok(())
+    }
+
+    fn poll_write_encoded(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.written < self.encoded.len() {
+            let written =
+                ready!(Pin::new(&mut self.file).poll_write(cx, &self.encoded[self.written..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.written += written;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for EncodingWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.finished {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        ready!(this.poll_write_encoded(cx))?;
+        if this.segment.len() == FileEncoding::SEGMENT_SIZE {
+            // More content is coming, so the full segment isn't the last one.
+            this.encode_segment(false)?;
+            ready!(this.poll_write_encoded(cx))?;
+        }
+        let len = buf
+            .len()
+            .min(FileEncoding::SEGMENT_SIZE - this.segment.len());
+        this.segment.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // The segment being filled is only written once full.
+        let this = self.get_mut();
+        ready!(this.poll_write_encoded(cx))?;
+        Pin::new(&mut this.file).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_encoded(cx))?;
+        if !this.finished {
+            // The last segment, which is written even if empty so that truncation is detected.
+            this.encode_segment(true)?;
+            this.finished = true;
+            ready!(this.poll_write_encoded(cx))?;
+        }
+        Pin::new(&mut this.file).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::{
+    local_fs::LocalFs,
+    test_util::{arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl},
+};
+use aptos_temppath::TempPath;
+use proptest::prelude::*;
+use std::collections::HashMap;
+use tokio::{io::AsyncWriteExt, runtime::Runtime};
+
+const KEY: [u8; 32] = [1; 32];
+
+fn new_local_fs(tmpdir: &TempPath) -> Arc<dyn BackupStorage> {
+    tmpdir.create_as_dir().unwrap();
+    Arc::new(LocalFs::new(tmpdir.path().to_path_buf()))
+}
+
+fn new_encoding(compress: bool, encrypt: bool) -> FileEncoding {
+    FileEncoding::new(
+        if compress { Some(3) } else { None },
+        if encrypt { Some(&KEY) } else { None },
+    )
+}
+
+async fn write_file(
+    store: &dyn BackupStorage,
+    backup_handle: &BackupHandleRef,
+    name: &str,
+    content: &[u8],
+) -> Result<FileHandle> {
+    let (file_handle, mut file) = store
+        .create_for_write(backup_handle, &name.parse()?)
+        .await?;
+    file.write_all(content).await?;
+    file.shutdown().await?;
+    Ok(file_handle)
+}
+
+async fn read_file(store: &dyn BackupStorage, file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    store
+        .open_for_read(file_handle)
+        .await?
+        .read_to_end(&mut content)
+        .await?;
+    Ok(content)
+}
+
+async fn test_write_and_read_impl(
+    store: &dyn BackupStorage,
+    backups: HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>,
+) {
+    let mut written = Vec::new();
+    for (backup_name, files) in &backups {
+        let backup_handle = store.create_backup(backup_name).await.unwrap();
+        for (name, content) in files {
+            let file_handle = write_file(store, &backup_handle, name.as_ref(), content)
+                .await
+                .unwrap();
+            written.push((file_handle, content));
+        }
+    }
+
+    for (file_handle, content) in written {
+        assert_eq!(&read_file(store, &file_handle).await.unwrap(), content);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups(),
+        compress in any::<bool>(),
+        encrypt in any::<bool>(),
+    ) {
+        let tmpdir = TempPath::new();
+        let store = EncodedStorage::new(new_local_fs(&tmpdir), new_encoding(compress, encrypt));
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(&store, backups));
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        let tmpdir = TempPath::new();
+        let store = EncodedStorage::new(
+            new_local_fs(&tmpdir),
+            FileEncoding::new(Some(3), Some(&KEY)),
+        );
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
+    }
+}
+
+#[test]
+fn test_write_and_read_multiple_segments() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let tmpdir = TempPath::new();
+        let inner = new_local_fs(&tmpdir);
+        let backup = inner
+            .create_backup(&"backup".parse().unwrap())
+            .await
+            .unwrap();
+        let content = (0..FileEncoding::SEGMENT_SIZE * 5 / 2)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+
+        for (compress, encrypt) in &[(true, false), (false, true), (true, true)] {
+            let store = EncodedStorage::new(inner.clone(), new_encoding(*compress, *encrypt));
+            for (name, len) in &[
+                ("empty", 0),
+                ("one_segment", FileEncoding::SEGMENT_SIZE),
+                ("segments", content.len()),
+            ] {
+                let name = format!("{}_{}_{}", name, compress, encrypt);
+                let file_handle = write_file(&store, &backup, &name, &content[..*len])
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    read_file(&store, &file_handle).await.unwrap(),
+                    &content[..*len]
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn test_read_mixed_encodings() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let tmpdir = TempPath::new();
+        let inner = new_local_fs(&tmpdir);
+        let content = br#"{"json": "file content, long enough to be compressed"}"#.repeat(10);
+
+        let backup = inner
+            .create_backup(&"backup".parse().unwrap())
+            .await
+            .unwrap();
+
+        let plain = write_file(inner.as_ref(), &backup, "plain", &content)
+            .await
+            .unwrap();
+        let compressing = EncodedStorage::new(inner.clone(), new_encoding(true, false));
+        let compressed = write_file(&compressing, &backup, "compressed", &content)
+            .await
+            .unwrap();
+        let encrypting = EncodedStorage::new(inner.clone(), new_encoding(true, true));
+        let encrypted = write_file(&encrypting, &backup, "encrypted", &content)
+            .await
+            .unwrap();
+        assert!(compressed.ends_with("#encoding=zstd"));
+        assert!(encrypted.ends_with("#encoding=zstd,aes-256-gcm"));
+
+        for file_handle in &[&compressed, &encrypted] {
+            let (inner_handle, _flags) = FileEncoding::untag_file_handle(file_handle).unwrap();
+            let on_disk = read_file(inner.as_ref(), inner_handle).await.unwrap();
+            assert!(on_disk.starts_with(FileEncoding::MAGIC));
+            assert!(!on_disk.ends_with(&content));
+        }
+
+        // With a key, only encrypted files are accepted.
+        assert_eq!(read_file(&encrypting, &encrypted).await.unwrap(), content);
+        assert!(read_file(&encrypting, &plain).await.is_err());
+        assert!(read_file(&encrypting, &compressed).await.is_err());
+
+        let no_key = EncodedStorage::new(inner.clone(), new_encoding(false, false));
+        assert_eq!(read_file(&no_key, &plain).await.unwrap(), content);
+        assert_eq!(read_file(&no_key, &compressed).await.unwrap(), content);
+        assert!(read_file(&no_key, &encrypted).await.is_err());
+
+        let wrong_key = EncodedStorage::new(inner.clone(), FileEncoding::new(None, Some(&[2; 32])));
+        assert!(read_file(&wrong_key, &encrypted).await.is_err());
+    });
+}
+
+#[test]
+fn test_reject_tampered_files() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let tmpdir = TempPath::new();
+        let inner = new_local_fs(&tmpdir);
+        let store = EncodedStorage::new(inner.clone(), new_encoding(true, true));
+        let backup = store
+            .create_backup(&"backup".parse().unwrap())
+            .await
+            .unwrap();
+        let content = vec![7; FileEncoding::SEGMENT_SIZE * 3 / 2];
+        let encrypted = write_file(&store, &backup, "encrypted", &content)
+            .await
+            .unwrap();
+        let (inner_handle, _flags) = FileEncoding::untag_file_handle(&encrypted).unwrap();
+        let on_disk = read_file(inner.as_ref(), inner_handle).await.unwrap();
+
+        // Dropping the encoding from the handle.
+        assert!(read_file(&store, inner_handle).await.is_err());
+
+        // Dropping the last segment.
+        let first_segment_len = FileEncoding::HEADER_LEN
+            + FileEncoding::NONCE_PREFIX_LEN
+            + 5
+            + u32::from_be_bytes(
+                on_disk[FileEncoding::HEADER_LEN + FileEncoding::NONCE_PREFIX_LEN + 1..][..4]
+                    .try_into()
+                    .unwrap(),
+            ) as usize;
+        let truncated = write_file(
+            inner.as_ref(),
+            &backup,
+            "truncated",
+            &on_disk[..first_segment_len],
+        )
+        .await
+        .unwrap();
+        let truncated = FileEncoding::tag_file_handle(
+            truncated,
+            FileEncoding::FLAG_ZSTD | FileEncoding::FLAG_AES_256_GCM,
+        )
+        .unwrap();
+        assert!(read_file(&store, &truncated).await.is_err());
+
+        // Recording a different encoding in the handle than in the header.
+        let compressed = EncodedStorage::new(inner.clone(), new_encoding(true, false));
+        let compressed = write_file(&compressed, &backup, "compressed", &content)
+            .await
+            .unwrap();
+        let (inner_handle, _flags) = FileEncoding::untag_file_handle(&compressed).unwrap();
+        let mislabeled = FileEncoding::tag_file_handle(
+            inner_handle.to_string(),
+            FileEncoding::FLAG_ZSTD | FileEncoding::FLAG_AES_256_GCM,
+        )
+        .unwrap();
+        assert!(read_file(&store, &mislabeled).await.is_err());
+    });
+}
+
+#[test]
+fn test_parse_key() {
+    assert_eq!(parse_key(&format!("{}\n", hex::encode(KEY))).unwrap(), KEY);
+    assert!(parse_key(&hex::encode([1; 16])).is_err());
+    assert!(parse_key("not hex").is_err());
+}
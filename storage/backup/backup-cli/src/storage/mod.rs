@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod command_adapter;
+pub mod encoding;
 pub mod local_fs;
+pub mod object_store;
 
 #[cfg(test)]
 mod test_util;
@@ -11,7 +13,9 @@ mod tests;
 
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
+    encoding::{EncodedStorage, EncodingOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    object_store::{ObjectStore, ObjectStoreOpt},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -151,6 +155,14 @@ pub trait BackupStorage: Send + Sync {
         &self,
         file_handle: &FileHandleRef,
     ) -> Result<Box<dyn AsyncRead + Send + Unpin>>;
+    /// Open a metadata file for reading, given a file handle returned by `list_metadata_files()`.
+    /// Unlike other files, metadata files are never encoded.
+    async fn open_metadata_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        self.open_for_read(file_handle).await
+    }
     /// Asks to save a metadata entry. A metadata entry is one line of text.
     /// The backup system doesn't expect a metadata entry to exclusively map to a single file
     /// handle, or the same file handle when accessed later, so there's no need to return one. This
@@ -159,7 +171,13 @@ pub trait BackupStorage: Send + Sync {
     /// Behavior on duplicated names is undefined, overwriting the content upon an existing name
     /// is straightforward and acceptable.
     /// See `list_metadata_files`.
-    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()>;
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.save_metadata_lines(name, std::slice::from_ref(content))
+            .await
+    }
+    /// Asks to save a metadata file holding multiple entries, one per line, e.g. when compacting
+    /// existing metadata files into one. See `save_metadata_line`.
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()>;
     /// The backup system always asks for all metadata files and cache and build index on top of
     /// the content of them. This means:
     ///   1. The storage is free to reorganise the metadata files, like combining multiple ones to
@@ -167,21 +185,55 @@ pub trait BackupStorage: Send + Sync {
     ///   2. But the cache does expect the content stays the same for a file handle, so when
     /// reorganising metadata files, give them new unique names.
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
+    /// Deletes a file, be it one of a backup no longer needed or a metadata file whose entries
+    /// have been compacted into another one.
+    /// Storage is free to also clean up what's left of a backup once all its files are deleted,
+    /// like an empty folder.
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()>;
 }
 
 #[derive(StructOpt)]
 pub enum StorageOpt {
     #[structopt(about = "Select the LocalFs backup store.")]
-    LocalFs(LocalFsOpt),
+    LocalFs {
+        #[structopt(flatten)]
+        opt: LocalFsOpt,
+        #[structopt(flatten)]
+        encoding: EncodingOpt,
+    },
     #[structopt(about = "Select the CommandAdapter backup store.")]
-    CommandAdapter(CommandAdapterOpt),
+    CommandAdapter {
+        #[structopt(flatten)]
+        opt: CommandAdapterOpt,
+        #[structopt(flatten)]
+        encoding: EncodingOpt,
+    },
+    #[structopt(about = "Select the ObjectStore backup store (S3 compatible).")]
+    ObjectStore {
+        #[structopt(flatten)]
+        opt: ObjectStoreOpt,
+        #[structopt(flatten)]
+        encoding: EncodingOpt,
+    },
 }
 
 impl StorageOpt {
     pub async fn init_storage(self) -> Result<Arc<dyn BackupStorage>> {
-        Ok(match self {
-            StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
-            StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
-        })
+        let (storage, encoding): (Arc<dyn BackupStorage>, _) = match self {
+            StorageOpt::LocalFs { opt, encoding } => {
+                (Arc::new(LocalFs::new_with_opt(opt)), encoding)
+            }
+            StorageOpt::CommandAdapter { opt, encoding } => {
+                (Arc::new(CommandAdapter::new_with_opt(opt).await?), encoding)
+            }
+            StorageOpt::ObjectStore { opt, encoding } => {
+                (Arc::new(ObjectStore::new_with_opt(opt)?), encoding)
+            }
+        };
+        // Always wrapped, so that encoded files are decoded on read even if not encoding on write.
+        Ok(Arc::new(EncodedStorage::new(
+            storage,
+            encoding.init_encoding().await?,
+        )))
     }
 }
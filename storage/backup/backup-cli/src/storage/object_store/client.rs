@@ -0,0 +1,509 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, format_err, Result};
+use aptos_logger::prelude::*;
+use bytes::Bytes;
+use hmac::{Hmac, Mac, NewMac};
+use reqwest::{header::HeaderMap, Method, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, time::Duration};
+
+/// Credentials to sign requests with, see
+/// https://docs.aws.amazon.com/general/latest/gr/sigv4_signing.html
+#[derive(Clone)]
+pub(super) struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// A minimal client of the S3 API, covering the requests needed by `ObjectStore`.
+pub(super) struct S3Client {
+    client: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    path_style: bool,
+    region: String,
+    credentials: Credentials,
+    max_retries: usize,
+}
+
+/// A successful response, with its body read.
+pub(super) struct Response {
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+/// A request, which is signed again on each attempt.
+struct Request<'a> {
+    method: Method,
+    key: &'a str,
+    query: Vec<(&'a str, String)>,
+    headers: Vec<(&'static str, String)>,
+    body: Bytes,
+}
+
+impl<'a> Request<'a> {
+    fn new(method: Method, key: &'a str) -> Self {
+        Self {
+            method,
+            key,
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: Bytes::new(),
+        }
+    }
+
+    fn query(mut self, name: &'a str, value: impl ToString) -> Self {
+        self.query.push((name, value.to_string()));
+        self
+    }
+
+    fn header(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+
+    fn body(mut self, body: Bytes) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+impl S3Client {
+    const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+    pub fn new(
+        endpoint: Url,
+        bucket: String,
+        path_style: bool,
+        region: String,
+        credentials: Credentials,
+        max_retries: usize,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .no_proxy()
+                .build()
+                .expect("Http client should build."),
+            endpoint,
+            bucket,
+            path_style,
+            region,
+            credentials,
+            max_retries,
+        }
+    }
+
+    pub async fn put_object(&self, key: &str, body: Bytes) -> Result<()> {
+        self.send(Request::new(Method::PUT, key).body(body)).await?;
+        Ok(())
+    }
+
+    /// Returns the size of the object, or `None` if it doesn't exist.
+    pub async fn head_object(&self, key: &str) -> Result<Option<u64>> {
+        let response = self.send(Request::new(Method::HEAD, key)).await;
+        match response {
+            Ok(response) => Ok(Some(
+                response
+                    .headers
+                    .get(reqwest::header::CONTENT_LENGTH)
+                    .and_then(|length| length.to_str().ok())
+                    .and_then(|length| length.parse().ok())
+                    .ok_or_else(|| format_err!("No Content-Length for {}", key))?,
+            )),
+            Err(e) => match e.downcast_ref::<ResponseError>() {
+                Some(ResponseError { status, .. }) if *status == StatusCode::NOT_FOUND => Ok(None),
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Gets the bytes in `[start, end)` of the object.
+    pub async fn get_object_range(&self, key: &str, start: u64, end: u64) -> Result<Bytes> {
+        let response = self
+            .send(
+                Request::new(Method::GET, key)
+                    .header("range", format!("bytes={}-{}", start, end - 1)),
+            )
+            .await?;
+        let bytes = response.body;
+        ensure!(
+            bytes.len() as u64 == end - start,
+            "Expected {} bytes from {} at {}, got {}.",
+            end - start,
+            key,
+            start,
+            bytes.len(),
+        );
+        Ok(bytes)
+    }
+
+    /// Lists the keys starting with `prefix`, across all pages.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut request = Request::new(Method::GET, "")
+                .query("list-type", 2)
+                .query("prefix", prefix);
+            if let Some(token) = continuation_token.take() {
+                request = request.query("continuation-token", token);
+            }
+            let body = String::from_utf8_lossy(&self.send(request).await?.body).into_owned();
+            keys.extend(xml_values(&body, "Key"));
+            if xml_values(&body, "IsTruncated").first().map(String::as_str) != Some("true") {
+                return Ok(keys);
+            }
+            continuation_token = Some(
+                xml_values(&body, "NextContinuationToken")
+                    .pop()
+                    .ok_or_else(|| {
+                        format_err!("Truncated listing without a continuation token.")
+                    })?,
+            );
+        }
+    }
+
+    pub async fn delete_object(&self, key: &str) -> Result<()> {
+        self.send(Request::new(Method::DELETE, key)).await?;
+        Ok(())
+    }
+
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let response = self
+            .send(Request::new(Method::POST, key).query("uploads", ""))
+            .await?;
+        let body = String::from_utf8_lossy(&response.body);
+        xml_values(&body, "UploadId")
+            .pop()
+            .ok_or_else(|| format_err!("No UploadId for {}.", key))
+    }
+
+    /// Returns the ETag of the part, to pass to `complete_multipart_upload`.
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        body: Bytes,
+    ) -> Result<String> {
+        let response = self
+            .send(
+                Request::new(Method::PUT, key)
+                    .query("partNumber", part_number)
+                    .query("uploadId", upload_id)
+                    .body(body),
+            )
+            .await?;
+        response
+            .headers
+            .get(reqwest::header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| format_err!("No ETag for part {} of {}.", part_number, key))
+    }
+
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[(usize, String)],
+    ) -> Result<()> {
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (part_number, etag) in parts {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part_number,
+                xml_escape(etag),
+            ));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+        let request = Request::new(Method::POST, key)
+            .query("uploadId", upload_id)
+            .body(Bytes::from(body));
+        match self.send_with_attempts(&request).await {
+            (Ok(_), _) => Ok(()),
+            // Completing isn't idempotent: an attempt can complete the upload and still fail, in
+            // which case the retries don't find the upload anymore, but the object exists.
+            (Err(e), attempts) if attempts > 1 && has_code(&e, "NoSuchUpload") => {
+                match self.head_object(key).await? {
+                    Some(_) => Ok(()),
+                    None => Err(e),
+                }
+            }
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        self.send(Request::new(Method::DELETE, key).query("uploadId", upload_id))
+            .await?;
+        Ok(())
+    }
+
+    /// Sends the request, retrying with exponential backoff on connection errors and on the
+    /// responses S3 expects to be retried.
+    async fn send(&self, request: Request<'_>) -> Result<Response> {
+        self.send_with_attempts(&request).await.0
+    }
+
+    /// Like `send`, also returning the number of attempts made.
+    async fn send_with_attempts(&self, request: &Request<'_>) -> (Result<Response>, usize) {
+        let mut attempt = 0;
+        loop {
+            match self.send_once(request).await {
+                Err(e) if attempt < self.max_retries && is_retryable(&e) => {
+                    attempt += 1;
+                    let delay = Self::RETRY_BASE_DELAY * 2u32.pow(attempt as u32 - 1);
+                    warn!(
+                        method = %request.method,
+                        key = %request.key,
+                        attempt = %attempt,
+                        error = %e,
+                        "S3 request failed, retrying in {:?}.",
+                        delay,
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                res => return (res, attempt + 1),
+            }
+        }
+    }
+
+    async fn send_once(&self, request: &Request<'_>) -> Result<Response> {
+        let mut url = self.url(request.key)?;
+        if !request.query.is_empty() {
+            url.set_query(Some(&canonical_query(&request.query)));
+        }
+        let headers = self.signed_headers(
+            &request.method,
+            &url,
+            &request.headers,
+            &request.body,
+            chrono::Utc::now(),
+        );
+
+        let mut builder = self.client.request(request.method.clone(), url);
+        for (name, value) in headers {
+            // Set by reqwest from the url.
+            if name != "host" {
+                builder = builder.header(name.as_str(), value);
+            }
+        }
+        let response = builder.body(request.body.clone()).send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+
+        // CompleteMultipartUpload can fail after responding 200, with the error in the body.
+        let failed_in_body = request.method == Method::POST && body.starts_with(b"<?xml") && {
+            let body = String::from_utf8_lossy(&body);
+            body.contains("<Error>")
+        };
+        if !status.is_success() || failed_in_body {
+            return Err(ResponseError {
+                status: if status.is_success() {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                } else {
+                    status
+                },
+                code: xml_values(&String::from_utf8_lossy(&body), "Code").pop(),
+            }
+            .into());
+        }
+        Ok(Response { headers, body })
+    }
+
+    fn url(&self, key: &str) -> Result<Url> {
+        let mut url = self.endpoint.clone();
+        let path = if self.path_style {
+            format!("/{}/{}", self.bucket, key)
+        } else {
+            let host = url
+                .host_str()
+                .ok_or_else(|| format_err!("No host in endpoint {}.", self.endpoint))?;
+            url.set_host(Some(&format!("{}.{}", self.bucket, host)))?;
+            format!("/{}", key)
+        };
+        url.set_path(&uri_encode(&path, false));
+        Ok(url)
+    }
+
+    /// Returns the headers of the request, including the AWS Signature Version 4 ones.
+    pub(super) fn signed_headers(
+        &self,
+        method: &Method,
+        url: &Url,
+        headers: &[(&'static str, String)],
+        body: &[u8],
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> BTreeMap<String, String> {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(body));
+
+        let mut host = url.host_str().unwrap_or_default().to_string();
+        if let Some(port) = url.port() {
+            host.push_str(&format!(":{}", port));
+        }
+        let mut signed: BTreeMap<String, String> = headers
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+            .collect();
+        signed.insert("host".to_string(), host);
+        signed.insert("x-amz-content-sha256".to_string(), payload_hash.clone());
+        signed.insert("x-amz-date".to_string(), amz_date.clone());
+        if let Some(token) = &self.credentials.session_token {
+            signed.insert("x-amz-security-token".to_string(), token.clone());
+        }
+
+        let signed_header_names = signed.keys().cloned().collect::<Vec<_>>().join(";");
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method,
+            url.path(),
+            url.query().unwrap_or_default(),
+            signed
+                .iter()
+                .map(|(name, value)| format!("{}:{}\n", name, value))
+                .collect::<String>(),
+            signed_header_names,
+            payload_hash,
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes())),
+        );
+
+        let mut key = hmac(
+            format!("AWS4{}", self.credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region.as_bytes(), &b"s3"[..], &b"aws4_request"[..]] {
+            key = hmac(&key, part);
+        }
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+        signed.insert(
+            "authorization".to_string(),
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.credentials.access_key_id, scope, signed_header_names, signature,
+            ),
+        );
+        signed
+    }
+}
+
+/// An error response of the S3 API.
+#[derive(Debug)]
+pub(super) struct ResponseError {
+    pub status: StatusCode,
+    pub code: Option<String>,
+}
+
+impl std::fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "S3 responded {}", self.status)?;
+        if let Some(code) = &self.code {
+            write!(f, " ({})", code)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ResponseError {}
+
+fn is_retryable(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<ResponseError>() {
+        Some(ResponseError { status, .. }) => {
+            status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+        }
+        // Connection errors, timeouts and the like.
+        None => error.downcast_ref::<reqwest::Error>().is_some(),
+    }
+}
+
+fn has_code(error: &anyhow::Error, expected: &str) -> bool {
+    matches!(
+        error.downcast_ref::<ResponseError>(),
+        Some(ResponseError { code: Some(code), .. }) if code == expected
+    )
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC takes keys of any size.");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// URI encodes as required by the signature: everything but the unreserved characters, and `/`
+/// unless `encode_slash`.
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// The query string, sorted by parameter name as required by the signature.
+fn canonical_query(query: &[(&str, String)]) -> String {
+    let mut params = query
+        .iter()
+        .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+        .collect::<Vec<_>>();
+    params.sort();
+    params
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// The text of the elements named `tag` of an XML document. The S3 responses are simple enough
+/// for this to do without a full XML parser.
+pub(super) fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        match rest.find(&close) {
+            Some(end) => {
+                values.push(xml_unescape(&rest[..end]));
+                rest = &rest[end + close.len()..];
+            }
+            None => break,
+        }
+    }
+    values
+}
+
+pub(super) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
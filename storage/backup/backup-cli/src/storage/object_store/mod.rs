@@ -0,0 +1,393 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        object_store::client::{Credentials, S3Client},
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use anyhow::{ensure, Result};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::{
+    future::BoxFuture,
+    stream::{self, StreamExt, TryStreamExt},
+    FutureExt,
+};
+use reqwest::Url;
+use std::{
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::{mpsc, oneshot},
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::{compat::FuturesAsyncReadCompatExt, sync::PollSender};
+
+#[derive(StructOpt)]
+pub struct ObjectStoreOpt {
+    #[structopt(long = "bucket", help = "Bucket to hold backups.")]
+    pub bucket: String,
+    #[structopt(
+        long = "prefix",
+        default_value = "",
+        help = "Prefix of the keys of the backup files in the bucket, like a folder."
+    )]
+    pub prefix: String,
+    #[structopt(
+        long = "region",
+        default_value = "us-east-1",
+        env = "AWS_REGION",
+        help = "Region of the bucket."
+    )]
+    pub region: String,
+    #[structopt(
+        long = "endpoint",
+        help = "Endpoint of the S3 compatible service, like \"http://localhost:9000\" for MinIO. \
+        [Defaults to AWS S3 in --region]"
+    )]
+    pub endpoint: Option<String>,
+    #[structopt(
+        long = "path-style",
+        help = "Address the bucket in the path of the urls instead of in the host name, as most \
+        S3 compatible services require."
+    )]
+    pub path_style: bool,
+    #[structopt(
+        long = "access-key-id",
+        env = "AWS_ACCESS_KEY_ID",
+        hide_env_values = true
+    )]
+    pub access_key_id: String,
+    #[structopt(
+        long = "secret-access-key",
+        env = "AWS_SECRET_ACCESS_KEY",
+        hide_env_values = true
+    )]
+    pub secret_access_key: String,
+    #[structopt(
+        long = "session-token",
+        env = "AWS_SESSION_TOKEN",
+        hide_env_values = true
+    )]
+    pub session_token: Option<String>,
+    #[structopt(
+        long = "part-size-mb",
+        default_value = "16",
+        help = "Size of the parts files are uploaded in, in MiB. S3 requires at least 5."
+    )]
+    pub part_size_mb: usize,
+    #[structopt(
+        long = "concurrency",
+        default_value = "4",
+        help = "Number of parts uploaded or downloaded concurrently for each file."
+    )]
+    pub concurrency: usize,
+    #[structopt(
+        long = "max-retries",
+        default_value = "5",
+        help = "Number of times a failed request is retried, with exponential backoff."
+    )]
+    pub max_retries: usize,
+}
+
+/// A storage backend that stores everything in a bucket of S3, or of a service compatible with
+/// its API like MinIO.
+/// Files are uploaded in parts, using multipart uploads once they are larger than a part, and
+/// downloaded in ranges of the same size.
+pub struct ObjectStore {
+    client: Arc<S3Client>,
+    /// Prefix of all the keys, empty or ending with '/'.
+    prefix: String,
+    part_size: usize,
+    concurrency: usize,
+}
+
+impl ObjectStore {
+    const METADATA_DIR: &'static str = "metadata";
+    const MIN_PART_SIZE_MB: usize = 5;
+
+    pub fn new_with_opt(opt: ObjectStoreOpt) -> Result<Self> {
+        ensure!(
+            opt.part_size_mb >= Self::MIN_PART_SIZE_MB,
+            "--part-size-mb must be at least {}.",
+            Self::MIN_PART_SIZE_MB,
+        );
+        let endpoint = match &opt.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => format!("https://s3.{}.amazonaws.com", opt.region),
+        };
+        let client = S3Client::new(
+            Url::parse(&endpoint).err_notes(&endpoint)?,
+            opt.bucket,
+            opt.path_style,
+            opt.region,
+            Credentials {
+                access_key_id: opt.access_key_id,
+                secret_access_key: opt.secret_access_key,
+                session_token: opt.session_token,
+            },
+            opt.max_retries,
+        );
+        Ok(Self::new(
+            client,
+            &opt.prefix,
+            opt.part_size_mb << 20,
+            opt.concurrency,
+        ))
+    }
+
+    fn new(client: S3Client, prefix: &str, part_size: usize, concurrency: usize) -> Self {
+        let prefix = prefix.trim_matches('/');
+        Self {
+            client: Arc::new(client),
+            prefix: if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("{}/", prefix)
+            },
+            part_size,
+            concurrency: concurrency.max(1),
+        }
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        format!("{}{}", self.prefix, file_handle)
+    }
+}
+
+#[async_trait]
+impl BackupStorage for ObjectStore {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no folders to create in a bucket.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let key = self.key(&file_handle);
+        ensure!(
+            self.client
+                .head_object(&key)
+                .await
+                .err_notes(&key)?
+                .is_none(),
+            "{} already exists.",
+            key,
+        );
+        let writer = ObjectWriter::new(self.client.clone(), key, self.part_size, self.concurrency);
+        Ok((file_handle, Box::new(writer)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let key = self.key(file_handle);
+        let size = match self.client.head_object(&key).await.err_notes(&key)? {
+            Some(size) => size,
+            None => anyhow::bail!("{} doesn't exist.", key),
+        };
+
+        let client = self.client.clone();
+        let part_size = self.part_size as u64;
+        let ranges = (0..size)
+            .step_by(part_size as usize)
+            .map(move |start| (start, (start + part_size).min(size)));
+        let reader = stream::iter(ranges)
+            .map(move |(start, end)| {
+                let client = client.clone();
+                let key = key.clone();
+                async move { client.get_object_range(&key, start, end).await }
+            })
+            .buffered(self.concurrency)
+            .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
+            .into_async_read()
+            .compat();
+        Ok(Box::new(reader))
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        let key = self.key(&format!("{}/{}", Self::METADATA_DIR, name.as_ref()));
+        let content: String = lines.iter().map(|line| line.as_ref()).collect();
+        self.client
+            .put_object(&key, Bytes::from(content))
+            .await
+            .err_notes(&key)
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = self.key(&format!("{}/", Self::METADATA_DIR));
+        let keys = self.client.list_objects(&prefix).await.err_notes(&prefix)?;
+        Ok(keys
+            .into_iter()
+            .map(|key| key[self.prefix.len()..].to_string())
+            .collect())
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        // There are no folders to clean up in a bucket either.
+        let key = self.key(file_handle);
+        self.client.delete_object(&key).await.err_notes(&key)
+    }
+}
+
+/// Uploads what's written to it in parts, by a task which it waits for on shutdown.
+struct ObjectWriter {
+    buf: BytesMut,
+    part_size: usize,
+    parts: Option<PollSender<Bytes>>,
+    /// Tells the upload task all parts were sent, so that it doesn't upload a truncated file if the
+    /// writer is dropped without being shut down.
+    finished: Option<oneshot::Sender<()>>,
+    upload: BoxFuture<'static, Result<()>>,
+}
+
+impl ObjectWriter {
+    fn new(client: Arc<S3Client>, key: String, part_size: usize, concurrency: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(1);
+        let (finished, finished_receiver) = oneshot::channel();
+        let upload = tokio::spawn(upload(
+            client,
+            key,
+            receiver,
+            finished_receiver,
+            concurrency,
+        ))
+        .map(|res| res.unwrap_or_else(|e| Err(e.into())))
+        .boxed();
+        Self {
+            buf: BytesMut::with_capacity(part_size),
+            part_size,
+            parts: Some(PollSender::new(sender)),
+            finished: Some(finished),
+            upload,
+        }
+    }
+
+    /// Sends the buffer to the upload task as a part.
+    fn poll_send_part(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let parts = self.parts.as_mut().expect("Not shut down.");
+        if futures::ready!(parts.poll_reserve(cx)).is_err()
+            || parts.send_item(self.buf.split().freeze()).is_err()
+        {
+            // The upload task failed, get its error.
+            return self.poll_upload(cx);
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_upload(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.upload
+            .poll_unpin(cx)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+impl AsyncWrite for ObjectWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.parts.is_none() {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        if self.buf.len() >= self.part_size {
+            futures::ready!(self.poll_send_part(cx))?;
+        }
+        let len = buf.len().min(self.part_size - self.buf.len());
+        self.buf.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Parts can't be flushed before they are full.
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.parts.is_some() {
+            // The last part, which is sent even if empty so that empty files are uploaded.
+            futures::ready!(self.poll_send_part(cx))?;
+            self.parts = None;
+            if let Some(finished) = self.finished.take() {
+                finished.send(()).ok();
+            }
+        }
+        self.poll_upload(cx)
+    }
+}
+
+/// Uploads the parts received until the writer shuts down, as a single object if there's only one.
+async fn upload(
+    client: Arc<S3Client>,
+    key: String,
+    mut parts: mpsc::Receiver<Bytes>,
+    finished: oneshot::Receiver<()>,
+    concurrency: usize,
+) -> Result<()> {
+    let first = parts.recv().await.unwrap_or_default();
+    let second = match parts.recv().await {
+        Some(part) => part,
+        None => {
+            ensure!(finished.await.is_ok(), "{} dropped before shut down.", key);
+            return client.put_object(&key, first).await.err_notes(&key);
+        }
+    };
+
+    let upload_id = client.create_multipart_upload(&key).await.err_notes(&key)?;
+    let res = async {
+        let etags = stream::iter(vec![first, second])
+            .chain(ReceiverStream::new(parts))
+            .enumerate()
+            .map(|(index, part)| {
+                let part_number = index + 1;
+                let client = &client;
+                let key = &key;
+                let upload_id = &upload_id;
+                async move {
+                    let etag = client
+                        .upload_part(key, upload_id, part_number, part)
+                        .await?;
+                    Ok::<_, anyhow::Error>((part_number, etag))
+                }
+            })
+            .buffered(concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+        ensure!(finished.await.is_ok(), "{} dropped before shut down.", key);
+        client
+            .complete_multipart_upload(&key, &upload_id, &etags)
+            .await
+    }
+    .await
+    .err_notes(&key);
+
+    if res.is_err() {
+        // Otherwise the uploaded parts are kept, and charged for.
+        client
+            .abort_multipart_upload(&key, &upload_id)
+            .await
+            .err_notes(&key)
+            .ok();
+    }
+    res
+}
@@ -68,11 +68,32 @@ pub async fn test_save_and_list_metadata_files_impl(
         store.save_metadata_line(name, content).await.unwrap();
     }
 
+    let expected = input
+        .into_iter()
+        .map(|(_name, content)| content)
+        .sorted()
+        .collect::<Vec<_>>();
+    assert_eq!(read_metadata_lines(store.as_ref()).await, expected);
+
+    // Compact all metadata files into one.
+    let file_handles = store.list_metadata_files().await.unwrap();
+    store
+        .save_metadata_lines(&"compacted_metadata.meta".parse().unwrap(), &expected)
+        .await
+        .unwrap();
+    for file_handle in &file_handles {
+        store.delete_file(file_handle).await.unwrap();
+    }
+    assert_eq!(store.list_metadata_files().await.unwrap().len(), 1);
+    assert_eq!(read_metadata_lines(store.as_ref()).await, expected);
+}
+
+async fn read_metadata_lines(store: &dyn BackupStorage) -> Vec<TextLine> {
     let mut read_back = Vec::new();
     for file_handle in store.list_metadata_files().await.unwrap() {
         let mut buf = String::new();
         store
-            .open_for_read(&file_handle)
+            .open_metadata_for_read(&file_handle)
             .await
             .unwrap()
             .read_to_string(&mut buf)
@@ -87,14 +108,7 @@ pub async fn test_save_and_list_metadata_files_impl(
         )
     }
     read_back.sort();
-
-    let expected = input
-        .into_iter()
-        .map(|(_name, content)| content)
-        .sorted()
-        .collect::<Vec<_>>();
-
-    assert_eq!(read_back, expected)
+    read_back
 }
 
 pub fn arb_metadata_files() -> impl Strategy<Value = Vec<(ShellSafeName, TextLine)>> {
@@ -7,6 +7,7 @@ use aptos_crypto::HashValue;
 use aptos_types::transaction::Version;
 use aptosdb::backup::backup_handler::DbState;
 use futures::TryStreamExt;
+use std::time::Duration;
 use structopt::StructOpt;
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio_util::compat::FuturesAsyncReadCompatExt;
@@ -63,6 +64,25 @@ impl BackupServiceClient {
         Ok(bcs::from_bytes(&buf)?)
     }
 
+    /// Long polls for the `DbState` once there are commits after `known_committed_version`,
+    /// getting the current one anyway on `timeout`.
+    pub async fn get_new_db_state(
+        &self,
+        known_committed_version: Version,
+        timeout: Duration,
+    ) -> Result<Option<DbState>> {
+        let mut buf = Vec::new();
+        self.get(&format!(
+            "new_db_state/{}/{}",
+            known_committed_version,
+            timeout.as_millis()
+        ))
+        .await?
+        .read_to_end(&mut buf)
+        .await?;
+        Ok(bcs::from_bytes(&buf)?)
+    }
+
     pub async fn get_account_range_proof(
         &self,
         key: HashValue,
@@ -5,14 +5,16 @@ mod utils;
 
 use crate::handlers::utils::{
     handle_rejection, reply_with_async_channel_writer, reply_with_bcs_bytes,
-    send_size_prefixed_bcs_bytes, unwrap_or_500, LATENCY_HISTOGRAM,
+    send_size_prefixed_bcs_bytes, unwrap_or_500, wait_for_new_db_state, LATENCY_HISTOGRAM,
 };
 use aptos_crypto::hash::HashValue;
 use aptos_types::transaction::Version;
 use aptosdb::backup::backup_handler::BackupHandler;
+use std::{convert::Infallible, time::Duration};
 use warp::{filters::BoxedFilter, reply::Reply, Filter};
 
 static DB_STATE: &str = "db_state";
+static NEW_DB_STATE: &str = "new_db_state";
 static STATE_RANGE_PROOF: &str = "state_range_proof";
 static STATE_SNAPSHOT: &str = "state_snapshot";
 static STATE_ROOT_PROOF: &str = "state_root_proof";
@@ -28,6 +30,25 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .map(unwrap_or_500)
         .recover(handle_rejection);
 
+    // GET new_db_state/<known_committed_version>/<timeout_ms>
+    let bh = backup_handler.clone();
+    let new_db_state = warp::path!(Version / u64)
+        .and_then(move |known_committed_version, timeout_ms| {
+            let bh = bh.clone();
+            async move {
+                let db_state = wait_for_new_db_state(
+                    &bh,
+                    known_committed_version,
+                    Duration::from_millis(timeout_ms),
+                )
+                .await;
+                Ok::<_, Infallible>(unwrap_or_500(
+                    db_state.and_then(|db_state| reply_with_bcs_bytes(NEW_DB_STATE, &db_state)),
+                ))
+            }
+        })
+        .recover(handle_rejection);
+
     // GET state_range_proof/<version>/<end_key>
     let bh = backup_handler.clone();
     let state_range_proof = warp::path!(Version / HashValue)
@@ -110,6 +131,7 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
     // Route by endpoint name.
     let routes = warp::any()
         .and(warp::path(DB_STATE).and(db_state))
+        .or(warp::path(NEW_DB_STATE).and(new_db_state))
         .or(warp::path(STATE_RANGE_PROOF).and(state_range_proof))
         .or(warp::path(STATE_SNAPSHOT).and(state_snapshot))
         .or(warp::path(STATE_ROOT_PROOF).and(state_root_proof))
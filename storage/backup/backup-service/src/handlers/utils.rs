@@ -6,12 +6,13 @@ use aptos_logger::prelude::*;
 use aptos_metrics::{
     register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec,
 };
-use aptosdb::backup::backup_handler::BackupHandler;
+use aptos_types::transaction::Version;
+use aptosdb::backup::backup_handler::{BackupHandler, DbState};
 use bytes::Bytes;
 use hyper::Body;
 use once_cell::sync::Lazy;
 use serde::Serialize;
-use std::{convert::Infallible, future::Future};
+use std::{convert::Infallible, future::Future, time::Duration};
 use warp::{reply::Response, Rejection, Reply};
 
 pub(super) static LATENCY_HISTOGRAM: Lazy<HistogramVec> = Lazy::new(|| {
@@ -43,6 +44,25 @@ pub(super) fn reply_with_bcs_bytes<R: Serialize>(
     Ok(Box::new(bytes))
 }
 
+/// Upper bound of the timeout of a long poll, so that connections don't hang indefinitely.
+const MAX_LONG_POLL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Returns the `DbState` as soon as there are commits after `known_committed_version`, or the
+/// current one when `timeout` is reached without any.
+pub(super) async fn wait_for_new_db_state(
+    backup_handler: &BackupHandler,
+    known_committed_version: Version,
+    timeout: Duration,
+) -> Result<Option<DbState>> {
+    let backup_handler = backup_handler.clone();
+    let timeout = std::cmp::min(timeout, MAX_LONG_POLL_TIMEOUT);
+    // Waiting to be woken up by a commit blocks the thread.
+    tokio::task::spawn_blocking(move || {
+        backup_handler.wait_for_new_db_state(known_committed_version, timeout)
+    })
+    .await?
+}
+
 pub(super) struct BytesSender {
     endpoint: &'static str,
     inner: hyper::body::Sender,
@@ -71,6 +71,8 @@ mod tests {
         assert_eq!(resp.status(), 400);
         let resp = get(&format!("http://127.0.0.1:{}/state_snapshot", port)).unwrap();
         assert_eq!(resp.status(), 400);
+        let resp = get(&format!("http://127.0.0.1:{}/new_db_state/0", port)).unwrap();
+        assert_eq!(resp.status(), 400);
 
         // Params fail to parse (HashValue)
         let resp = get(&format!("http://127.0.0.1:{}/state_range_proof/1/ff", port)).unwrap();
@@ -87,6 +89,16 @@ mod tests {
         let resp = get(&format!("http://127.0.0.1:{}/state_root_proof/0", port,)).unwrap();
         assert_eq!(resp.status(), 500);
 
+        // Long poll times out, returning the current DbState: None, for the non-bootstrapped DB.
+        let start = std::time::Instant::now();
+        let resp = get(&format!("http://127.0.0.1:{}/new_db_state/0/200", port)).unwrap();
+        assert_eq!(resp.status(), 200);
+        assert!(start.elapsed() >= std::time::Duration::from_millis(200));
+        assert_eq!(
+            resp.bytes().unwrap().as_ref(),
+            bcs::to_bytes(&None::<u8>).unwrap()
+        );
+
         // an endpoint handled by `reply_with_async_channel_writer' always returns 200,
         // connection terminates prematurely when the channel writer errors.
         let resp = get(&format!("http://127.0.0.1:{}/state_snapshot/1", port,)).unwrap();
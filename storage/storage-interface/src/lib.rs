@@ -6,7 +6,7 @@ use aptos_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
 use aptos_types::{
     access_path::AccessPath,
     account_address::AccountAddress,
-    account_config::aptos_root_address,
+    account_config::{aptos_root_address, NewBlockEvent},
     contract_event::{ContractEvent, EventByVersionWithProof, EventWithProof},
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
@@ -346,6 +346,28 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns the height of the block containing `version`, i.e. the sequence number of the last
+    /// `NewBlockEvent` emitted at or before `version`. Returns `None` if `version` precedes the
+    /// first block (e.g. the genesis transaction).
+    fn get_block_height_by_version(
+        &self,
+        version: Version,
+        ledger_version: Version,
+    ) -> Result<Option<u64>> {
+        unimplemented!()
+    }
+
+    /// Returns the first version, the last version and the `NewBlockEvent` of the block at
+    /// `height`. The block is considered to extend to `ledger_version` if no later block has been
+    /// committed yet.
+    fn get_block_info_by_height(
+        &self,
+        height: u64,
+        ledger_version: Version,
+    ) -> Result<(Version, Version, NewBlockEvent)> {
+        unimplemented!()
+    }
+
     /// See [AptosDB::get_latest_account_state].
     ///
     /// [AptosDB::get_latest_account_state]:
@@ -365,6 +387,19 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns an iterator over the key, value pairs for a particular state key prefix at the
+    /// desired version, ordered by state key and starting at `cursor` if provided. Unlike
+    /// [`get_state_values_by_key_prefix`](DbReader::get_state_values_by_key_prefix), values are
+    /// read lazily, so that a page of them can be fetched without reading the whole prefix.
+    fn get_prefixed_state_value_iterator(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        cursor: Option<&StateKey>,
+        version: Version,
+    ) -> Result<Box<dyn Iterator<Item = Result<(StateKey, StateValue)>> + '_>> {
+        unimplemented!()
+    }
+
     /// Returns the latest ledger info, if any.
     fn get_latest_ledger_info_option(&self) -> Result<Option<LedgerInfoWithSignatures>> {
         unimplemented!()
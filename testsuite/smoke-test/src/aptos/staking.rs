@@ -31,35 +31,29 @@ impl AptosTest for Staking {
         )
         .unwrap();
         let txn_factory = ctx.aptos_transaction_factory();
-        // let locked_period = 86400 * 2;
-        // let current_time = std::time::SystemTime::now()
-        //     .duration_since(std::time::UNIX_EPOCH)
-        //     .unwrap()
-        //     .as_secs()
-        //     + locked_period;
-        // for account in &mut accounts {
-        //     let txn = account.sign_with_transaction_builder(txn_factory.payload(
-        //         aptos_stdlib::encode_stake_delegate_stake(validator_addr, 100, current_time),
-        //     ));
-        //     ctx.client().submit_and_wait(&txn).await?;
-        // }
+        let locked_period = 86400 * 2;
+        let current_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + locked_period;
+        for account in &mut accounts {
+            let txn = account.sign_with_transaction_builder(txn_factory.payload(
+                aptos_stdlib::encode_stake_delegate_stake(validator_addr, 100, current_time),
+            ));
+            ctx.client().submit_and_wait(&txn).await?;
+        }
         let stake_pool = stake_pool_from_addr(ctx, validator_addr).await;
-        assert_eq!(
-            stake_pool["pending_active"].as_array().unwrap().len(),
-            accounts.len()
-        );
+        assert_eq!(table_length(&stake_pool["pending_active"]), accounts.len());
         // force epoch change to make pending changes take effect
         let txn = ctx.root_account().sign_with_transaction_builder(
             txn_factory.payload(aptos_stdlib::encode_reconfiguration_force_reconfigure()),
         );
         ctx.client().submit_and_wait(&txn).await?;
         let stake_pool = stake_pool_from_addr(ctx, validator_addr).await;
+        assert_eq!(table_length(&stake_pool["active"]), accounts.len() + 1);
         assert_eq!(
-            stake_pool["active"].as_array().unwrap().len(),
-            accounts.len() + 1
-        );
-        assert_eq!(
-            stake_pool["current_stake"]
+            stake_pool["voting_power"]
                 .as_str()
                 .unwrap()
                 .parse::<u64>()
@@ -86,6 +80,15 @@ async fn stake_pool_from_addr(ctx: &AptosContext<'_>, addr: AccountAddress) -> s
         .data
 }
 
+/// Number of entries of a `0x1::IterableTable::IterableTable`
+fn table_length(table: &serde_json::Value) -> usize {
+    table["inner"]["length"]
+        .as_str()
+        .unwrap()
+        .parse::<usize>()
+        .unwrap()
+}
+
 async fn validator_set(ctx: &AptosContext<'_>) -> serde_json::Value {
     ctx.client()
         .get_account_resource(aptos_root_address(), "0x1::Stake::ValidatorSet")
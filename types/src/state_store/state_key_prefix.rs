@@ -6,7 +6,7 @@ use move_deps::move_core_types::account_address::AccountAddress;
 
 // Struct for defining prefix of a state key, which can be used for finding all the values with a
 // particular key prefix
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct StateKeyPrefix {
     tag: StateKeyTag,
     bytes: Vec<u8>,
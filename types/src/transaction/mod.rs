@@ -490,6 +490,14 @@ pub struct SignedTransaction {
 pub struct SignatureCheckedTransaction(SignedTransaction);
 
 impl SignatureCheckedTransaction {
+    /// Wraps a `SignedTransaction` without verifying its signature.
+    ///
+    /// This must only be used for transactions whose output is never committed, e.g. when
+    /// simulating a transaction that has not been signed yet.
+    pub fn new_unchecked(txn: SignedTransaction) -> Self {
+        Self(txn)
+    }
+
     /// Returns the `SignedTransaction` within.
     pub fn into_inner(self) -> SignedTransaction {
         self.0